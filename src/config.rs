@@ -1,12 +1,93 @@
 //! Configuration management with validation
 use crate::error::{AppError, Result};
+use crate::models::discord::DiscordTarget;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use dotenvy::dotenv;
 use once_cell::sync::OnceCell;
+use std::collections::HashMap;
 use std::env;
 
 /// Global configuration instance (lazy-loaded)
 static CONFIG: OnceCell<Config> = OnceCell::new();
 
+/// What to do with a transcript that looks like mostly music/non-speech
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LowQualityAction {
+    /// Summarize with the low-quality fallback prompt
+    #[default]
+    Describe,
+    /// Skip summarization entirely
+    Skip,
+}
+
+/// Order in which catch-up processes (and posts) unseen videos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CatchUpOrder {
+    /// Latest upload first
+    #[default]
+    NewestFirst,
+    /// Chronological, so the Discord channel reads top to bottom
+    OldestFirst,
+}
+
+/// What to do when a transcript covers less of the video than expected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PartialTranscriptAction {
+    /// Log a warning and summarize as usual
+    #[default]
+    Warn,
+    /// Also prefix the summary with a "partial transcript" note
+    Tag,
+}
+
+/// Which summarizer produces the posted summary
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Summarizer {
+    /// The configured AI backend
+    #[default]
+    Ai,
+    /// Offline top-sentence extraction; no AI calls are made
+    Extractive,
+}
+
+/// Request/response format of the AI backend at `MY_AI_API_URL`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AiBackend {
+    /// `{persona, user_id, messages}` in, `{answer, events}` out
+    #[default]
+    Custom,
+    /// OpenAI-compatible `/chat/completions`
+    OpenAi,
+}
+
+/// How transcript segments are joined into the text sent to the AI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TranscriptJoin {
+    /// One space between segments
+    #[default]
+    Space,
+    /// One segment per line
+    Newline,
+    /// Spaces within a sentence, a newline after segments ending a sentence
+    Sentence,
+}
+
+/// Persona used for videos whose title/description mention one of `keywords`
+#[derive(Debug, Clone, Default, PartialEq, serde_derive::Deserialize)]
+pub struct TopicPersona {
+    pub topic: String,
+    pub keywords: Vec<String>,
+    pub persona: String,
+}
+
+/// One transcript→summary pair shown to the AI before the real transcript
+#[derive(Debug, Clone, Default, PartialEq, serde_derive::Deserialize)]
+pub struct FewShotExample {
+    pub input: String,
+    pub output: String,
+}
+
 /// Application configuration
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -16,10 +97,19 @@ pub struct Config {
     pub token: String,
     /// YouTube Data API key
     pub youtube_api_key: String,
+    /// YouTube Data API base URL; `/search`, `/videos`, ... are appended (`YOUTUBE_API_BASE`)
+    pub youtube_api_base: String,
     /// Supabase/transcript API key
     pub supabase_api_key: String,
-    /// KS Forward YouTube channel ID
+    /// KS Forward YouTube channel ID (the first of `ksforword_channel_ids`)
     pub ksforword_channel_id: String,
+    /// Every KS Forward channel polled per run (`KSFORWORD_CHANNEL_IDS`,
+    /// comma-separated; falls back to `KSFORWORD_CHANNEL_ID`)
+    pub ksforword_channel_ids: Vec<String>,
+    /// Additional channel IDs checked by `channels check` (`EXTRA_CHANNEL_IDS`, comma-separated)
+    pub extra_channel_ids: Vec<String>,
+    /// Playlist summarized by the `playlist` subcommand when no id is given (`PLAYLIST_ID`)
+    pub playlist_id: Option<String>,
     /// Use mock data for testing
     pub use_mock_data: bool,
     /// AI service API URL
@@ -27,55 +117,742 @@ pub struct Config {
     /// AI service API Key (X-API-Key header)
     pub my_ai_api_key: String,
     /// Discord bot webhook URL
+    #[allow(dead_code)]
     pub discord_ks_bot_token: String,
+    /// Webhooks parsed from `DISCORD_KS_BOT_TOKEN` (a URL or a JSON array of targets)
+    pub discord_targets: Vec<DiscordTarget>,
+    /// Forum/thread to post into, sent as `?thread_id=` on every webhook (`DISCORD_THREAD_ID`)
+    pub discord_thread_id: Option<String>,
+    /// Chars per embed description before a message is split (`DISCORD_MAX_DESC`, at most 4096)
+    pub discord_max_desc: usize,
+    /// Display name for posted messages instead of the webhook's own (`DISCORD_USERNAME`)
+    pub discord_username: Option<String>,
+    /// Avatar image URL for posted messages (`DISCORD_AVATAR_URL`)
+    pub discord_avatar_url: Option<String>,
+    /// Reject transcripts with inconsistent segment timing
+    pub strict_transcript: bool,
+    /// Show the video thumbnail as a large embed image
+    pub embed_large_image: bool,
+    /// Languages to summarize into (empty = single default summary)
+    pub summary_languages: Vec<String>,
+    /// Post each language as its own message instead of grouping them
+    pub summary_languages_separate: bool,
+    /// Ask the AI for a TL;DR plus the full summary in one call and post both (`SUMMARY_TLDR`)
+    pub summary_tldr: bool,
+    /// Post a heartbeat message when no new video is found
+    pub notify_on_no_new_video: bool,
+    /// Rewrite tables/headings in summaries into Discord-friendly markdown
+    pub discord_markdown_compat: bool,
+    /// Log request/response bodies at trace level (secrets redacted)
+    pub log_http_bodies: bool,
+    /// Log the AI backend's reasoning events at debug level (`AI_DEBUG_EVENTS`)
+    pub ai_debug_events: bool,
+    /// Speech words-per-minute below which a transcript is low quality (0 = off)
+    pub low_quality_min_wpm: f64,
+    /// Share of `[Music]`-style marker segments above which a transcript is low quality
+    pub low_quality_marker_ratio: f64,
+    /// Handling for low-quality transcripts
+    pub low_quality_action: LowQualityAction,
+    /// Prompt used instead of the normal summary for low-quality transcripts
+    pub low_quality_prompt: String,
+    /// SQLite database for archiving summaries (requires the `sqlite` feature)
+    pub db_path: Option<String>,
+    /// Write each processed video's report as JSON to this file (`REPORT_PATH`)
+    pub report_path: Option<String>,
+    /// Skip Discord delivery; caching, archiving and reports still run (`DRY_RUN`)
+    pub dry_run: bool,
+    /// Only process videos whose title contains the current year
+    pub require_year_in_title: bool,
+    /// Sampling temperature sent to the AI backend (0–2, omitted when unset)
+    pub ai_temperature: Option<f32>,
+    /// Completion token limit sent to the AI backend (omitted when unset)
+    pub ai_max_tokens: Option<u32>,
+    /// Truncate AI answers longer than this many chars before posting (`AI_MAX_ANSWER_CHARS`)
+    pub ai_max_answer_chars: Option<usize>,
+    /// Refuse AI calls whose estimated input tokens exceed this (`MAX_ESTIMATED_TOKENS`)
+    pub max_estimated_tokens: Option<usize>,
+    /// System message placed before all other AI messages (`AI_SYSTEM_PROMPT`)
+    pub ai_system_prompt: Option<String>,
+    /// Example pairs sent as prior user/assistant turns (from `FEW_SHOT_EXAMPLES_PATH`)
+    pub few_shot_examples: Vec<FewShotExample>,
+    /// Skip videos whose transcript is at least this similar (0–1) to a recent one
+    pub dedup_threshold: Option<f64>,
+    /// JSON file holding recent transcript fingerprints for dedup
+    pub dedup_state_path: String,
+    /// Seen-video state file; when set, runs catch up on every unseen matching video
+    pub state_path: Option<String>,
+    /// Most unseen videos processed per catch-up run
+    pub catch_up_limit: usize,
+    /// Without a state file, process every matching video in the search page
+    /// instead of only the first (`PROCESS_ALL_MATCHING`, capped by `catch_up_limit`)
+    pub process_all_matching: bool,
+    /// Newest matching videos processed per run without a state file (`LATEST_COUNT`, default 1)
+    pub latest_count: usize,
+    /// Summarize videos again even if already recorded as processed (`FORCE_REPROCESS`)
+    pub force_reprocess: bool,
+    /// Wall-clock limit for fetching and summarizing one video, not counting
+    /// its Discord post (`PER_VIDEO_BUDGET_SECS`); unset = no limit
+    pub per_video_budget_secs: Option<u64>,
+    /// Only process videos published after this time (`SINCE`, or `--since`);
+    /// searches page back to it and videos without a publish time are skipped
+    pub since: Option<DateTime<Utc>>,
+    /// Videos whose transcript and summary are fetched at once in a multi-video
+    /// run (`MAX_CONCURRENCY`, default 3); posting stays one at a time
+    pub max_concurrency: usize,
+    /// Most videos processed when the state file does not exist yet
+    pub first_run_limit: usize,
+    /// Processing order for catch-up runs
+    pub catch_up_order: CatchUpOrder,
+    /// Append the video's channel title to the Discord footer
+    pub show_channel_in_footer: bool,
+    /// Append the video's view and like counts to the Discord footer (`SHOW_VIDEO_STATS`)
+    pub show_video_stats: bool,
+    /// Add the summary's detected language (e.g. "Language: Thai") to the footer
+    pub show_detected_language: bool,
+    /// Directory of cached transcripts (`CACHE_DIR`)
+    pub cache_dir: String,
+    /// Age after which a cached transcript is fetched again (`CACHE_TTL_SECS`, unset = never)
+    pub cache_ttl_secs: Option<u64>,
+    /// Refuse transcripts larger than this many bytes (unset = no limit)
+    pub max_transcript_bytes: Option<usize>,
+    /// Transcript API attempts before giving up (`TRANSCRIPT_MAX_RETRIES`, default 3)
+    pub transcript_max_retries: usize,
+    /// First transcript retry delay, doubled per attempt with ±25% jitter (`TRANSCRIPT_BACKOFF_SECS`)
+    pub transcript_backoff_secs: u64,
+    /// Separator used when joining transcript segments
+    pub transcript_join: TranscriptJoin,
+    /// Prefix each transcript line with its `[mm:ss]` offset instead of joining (`TRANSCRIPT_WITH_TIMESTAMPS`)
+    pub transcript_with_timestamps: bool,
+    /// Collapse stray whitespace and fix punctuation spacing in summaries
+    pub tidy_summaries: bool,
+    /// Transcript API base URL (`SUPADATA_BASE_URL`)
+    pub supadata_base_url: String,
+    /// Transcript endpoint path appended to the base URL (`SUPADATA_TRANSCRIPT_PATH`)
+    pub supadata_transcript_path: String,
+    /// Preferred transcript language, e.g. "th" (`TRANSCRIPT_LANG`; default track when unavailable)
+    pub preferred_transcript_lang: Option<String>,
+    /// Transcript/video length ratio below which a transcript is partial (0 = off)
+    pub min_transcript_coverage: f64,
+    /// Handling for partial transcripts
+    pub partial_transcript_action: PartialTranscriptAction,
+    /// Skip videos whose transcript has fewer chars than this (`SKIP_IF_TRANSCRIPT_SHORTER_THAN`, 0 = off)
+    pub skip_if_transcript_shorter_than: usize,
+    /// Skip videos whose transcript contains none of these words, case-insensitively (`REQUIRE_KEYWORDS`)
+    pub require_keywords: Vec<String>,
+    /// AI persona for summaries (`AI_PERSONA`, default "ks-summary")
+    pub ai_persona: String,
+    /// User id sent with summary requests (`AI_USER_ID`, default "ks-summary")
+    pub ai_user_id: String,
+    /// Dot-separated JSON path of the answer text in AI responses (`AI_ANSWER_PATH`)
+    pub ai_answer_path: String,
+    /// Request format of the AI backend (`AI_BACKEND`: custom or openai)
+    pub ai_backend: AiBackend,
+    /// Model name sent to an OpenAI-compatible backend (`AI_MODEL`)
+    pub ai_model: Option<String>,
+    /// Topic keyword → persona overrides, checked in order (`TOPIC_PERSONAS` JSON)
+    pub topic_personas: Vec<TopicPersona>,
+    /// Summarizer backend (`SUMMARIZER`: ai or extractive)
+    pub summarizer: Summarizer,
+    /// Sentences kept by the extractive summarizer
+    pub extractive_sentences: usize,
+    /// chrono format for the video publish date shown on embeds (`PUBLISHED_DATE_FORMAT`)
+    pub published_date_format: String,
+    /// Remove sponsor reads from transcripts before summarizing (`SPONSOR_FILTER`)
+    pub sponsor_filter: bool,
+    /// Phrases that mark a sponsor read (`SPONSOR_PHRASES`, comma-separated; built-in list when empty)
+    pub sponsor_phrases: Vec<String>,
+}
+
+/// Variables `from_env` cannot run without
+const REQUIRED_ENV_VARS: [&str; 8] = [
+    "API_URL",
+    "TOKEN",
+    "YOUTUBE_API_KEY",
+    "SUPABASE_API_KEY",
+    "KSFORWORD_CHANNEL_ID",
+    "MY_AI_API_URL",
+    "MY_AI_API_KEY",
+    "DISCORD_KS_BOT_TOKEN",
+];
+
+/// Default AI persona for summaries
+pub const DEFAULT_AI_PERSONA: &str = "ks-summary";
+
+/// Default user id sent with summary requests
+pub const DEFAULT_AI_USER_ID: &str = "ks-summary";
+
+/// JSON path of the answer text when `AI_ANSWER_PATH` is unset
+pub const DEFAULT_AI_ANSWER_PATH: &str = "answer";
+
+/// Embed description size used when `DISCORD_MAX_DESC` is unset, below Discord's 4096
+pub const DEFAULT_DISCORD_MAX_DESC: usize = 4000;
+
+/// Parse a `--since`/`SINCE` value: a `YYYY-MM-DD` date (midnight UTC) or an
+/// RFC 3339 timestamp
+pub fn parse_since(raw: &str) -> std::result::Result<DateTime<Utc>, String> {
+    let raw = raw.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        return Ok(date.and_time(NaiveTime::MIN).and_utc());
+    }
+    DateTime::parse_from_rfc3339(raw)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| format!("invalid date '{}', expected YYYY-MM-DD or an RFC 3339 timestamp", raw))
+}
+
+/// Publish date format used when `PUBLISHED_DATE_FORMAT` is unset, e.g. "15 Jan 2025"
+pub const DEFAULT_PUBLISHED_DATE_FORMAT: &str = "%d %b %Y";
+
+/// Transcript cache directory used when `CACHE_DIR` is unset
+pub const DEFAULT_CACHE_DIR: &str = "transcript_cache";
+
+/// Default YouTube Data API base URL
+pub const DEFAULT_YOUTUBE_API_BASE: &str = "https://www.googleapis.com/youtube/v3";
+
+/// Default transcript API base URL
+pub const DEFAULT_SUPADATA_BASE_URL: &str = "https://api.supadata.ai";
+
+/// Default transcript API endpoint path
+pub const DEFAULT_SUPADATA_TRANSCRIPT_PATH: &str = "/v1/transcript";
+
+/// Default prompt for transcripts that are mostly music or other non-speech audio
+pub const DEFAULT_LOW_QUALITY_PROMPT: &str = "This transcript is mostly music or other non-speech audio. \
+Do not summarize it as a discussion; briefly describe what the video appears to contain.";
+
+/// Sentences kept by the extractive summarizer when `EXTRACTIVE_SENTENCES` is unset
+pub const DEFAULT_EXTRACTIVE_SENTENCES: usize = 5;
+
+/// Non-speech marker share above which a transcript is low quality (`LOW_QUALITY_MARKER_RATIO`)
+pub const DEFAULT_LOW_QUALITY_MARKER_RATIO: f64 = 0.5;
+
+/// Dedup state file used when `DEDUP_STATE_PATH` is unset
+pub const DEFAULT_DEDUP_STATE_PATH: &str = "dedup_state.json";
+
+/// Videos processed per catch-up run when `CATCH_UP_LIMIT` is unset
+pub const DEFAULT_CATCH_UP_LIMIT: usize = 3;
+
+/// Videos processed on a run without state when `FIRST_RUN_LIMIT` is unset
+pub const DEFAULT_FIRST_RUN_LIMIT: usize = 1;
+
+/// Newest matching videos processed per channel when `LATEST_COUNT` is unset
+pub const DEFAULT_LATEST_COUNT: usize = 1;
+
+/// Videos prepared at once when `MAX_CONCURRENCY` is unset
+pub const DEFAULT_MAX_CONCURRENCY: usize = 3;
+
+/// Transcript attempts when `TRANSCRIPT_MAX_RETRIES` is unset
+pub const DEFAULT_TRANSCRIPT_MAX_RETRIES: usize = 3;
+
+/// First transcript retry delay when `TRANSCRIPT_BACKOFF_SECS` is unset
+pub const DEFAULT_TRANSCRIPT_BACKOFF_SECS: u64 = 2;
+
+/// Whether summaries are tidied when `TIDY_SUMMARIES` is unset
+pub const DEFAULT_TIDY_SUMMARIES: bool = true;
+
+/// The values `from_env` uses for unset variables; required settings are empty
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            api_url: Default::default(),
+            token: Default::default(),
+            youtube_api_key: Default::default(),
+            youtube_api_base: DEFAULT_YOUTUBE_API_BASE.to_string(),
+            supabase_api_key: Default::default(),
+            ksforword_channel_id: Default::default(),
+            ksforword_channel_ids: Default::default(),
+            extra_channel_ids: Default::default(),
+            playlist_id: Default::default(),
+            use_mock_data: Default::default(),
+            my_ai_api_url: Default::default(),
+            my_ai_api_key: Default::default(),
+            discord_ks_bot_token: Default::default(),
+            discord_targets: Default::default(),
+            discord_thread_id: Default::default(),
+            discord_max_desc: DEFAULT_DISCORD_MAX_DESC,
+            discord_username: Default::default(),
+            discord_avatar_url: Default::default(),
+            strict_transcript: Default::default(),
+            embed_large_image: Default::default(),
+            summary_languages: Default::default(),
+            summary_languages_separate: Default::default(),
+            summary_tldr: Default::default(),
+            notify_on_no_new_video: Default::default(),
+            discord_markdown_compat: Default::default(),
+            log_http_bodies: Default::default(),
+            ai_debug_events: Default::default(),
+            low_quality_min_wpm: Default::default(),
+            low_quality_marker_ratio: DEFAULT_LOW_QUALITY_MARKER_RATIO,
+            low_quality_action: Default::default(),
+            low_quality_prompt: DEFAULT_LOW_QUALITY_PROMPT.to_string(),
+            db_path: Default::default(),
+            report_path: Default::default(),
+            dry_run: Default::default(),
+            require_year_in_title: Default::default(),
+            ai_temperature: Default::default(),
+            ai_max_tokens: Default::default(),
+            ai_max_answer_chars: Default::default(),
+            max_estimated_tokens: Default::default(),
+            ai_system_prompt: Default::default(),
+            few_shot_examples: Default::default(),
+            dedup_threshold: Default::default(),
+            dedup_state_path: DEFAULT_DEDUP_STATE_PATH.to_string(),
+            state_path: Default::default(),
+            catch_up_limit: DEFAULT_CATCH_UP_LIMIT,
+            process_all_matching: Default::default(),
+            latest_count: DEFAULT_LATEST_COUNT,
+            force_reprocess: Default::default(),
+            per_video_budget_secs: Default::default(),
+            since: Default::default(),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            first_run_limit: DEFAULT_FIRST_RUN_LIMIT,
+            catch_up_order: Default::default(),
+            show_channel_in_footer: Default::default(),
+            show_video_stats: Default::default(),
+            show_detected_language: Default::default(),
+            cache_dir: DEFAULT_CACHE_DIR.to_string(),
+            cache_ttl_secs: Default::default(),
+            max_transcript_bytes: Default::default(),
+            transcript_max_retries: DEFAULT_TRANSCRIPT_MAX_RETRIES,
+            transcript_backoff_secs: DEFAULT_TRANSCRIPT_BACKOFF_SECS,
+            transcript_join: Default::default(),
+            transcript_with_timestamps: Default::default(),
+            tidy_summaries: DEFAULT_TIDY_SUMMARIES,
+            supadata_base_url: DEFAULT_SUPADATA_BASE_URL.to_string(),
+            supadata_transcript_path: DEFAULT_SUPADATA_TRANSCRIPT_PATH.to_string(),
+            preferred_transcript_lang: Default::default(),
+            min_transcript_coverage: Default::default(),
+            partial_transcript_action: Default::default(),
+            skip_if_transcript_shorter_than: Default::default(),
+            require_keywords: Default::default(),
+            ai_persona: DEFAULT_AI_PERSONA.to_string(),
+            ai_user_id: DEFAULT_AI_USER_ID.to_string(),
+            ai_answer_path: DEFAULT_AI_ANSWER_PATH.to_string(),
+            ai_backend: Default::default(),
+            ai_model: Default::default(),
+            topic_personas: Default::default(),
+            summarizer: Default::default(),
+            extractive_sentences: DEFAULT_EXTRACTIVE_SENTENCES,
+            published_date_format: DEFAULT_PUBLISHED_DATE_FORMAT.to_string(),
+            sponsor_filter: Default::default(),
+            sponsor_phrases: Default::default(),
+        }
+    }
 }
 
 impl Config {
     /// Load configuration from environment variables
     pub fn from_env() -> Result<Self> {
         dotenv().ok();
+        let mut required = Self::read_required(|name| env::var(name).ok())?;
+        let mut take = |name: &str| required.remove(name).unwrap_or_default();
 
-        let api_url = env::var("API_URL")
-            .map_err(|_| AppError::config("API_URL must be set"))?;
-        let token = env::var("TOKEN")
-            .map_err(|_| AppError::config("TOKEN must be set"))?;
-        let youtube_api_key = env::var("YOUTUBE_API_KEY")
-            .map_err(|_| AppError::config("YOUTUBE_API_KEY must be set"))?;
-        let supabase_api_key = env::var("SUPABASE_API_KEY")
-            .map_err(|_| AppError::config("SUPABASE_API_KEY must be set"))?;
-        let ksforword_channel_id = env::var("KSFORWORD_CHANNEL_ID")
-            .map_err(|_| AppError::config("KSFORWORD_CHANNEL_ID must be set"))?;
-        let my_ai_api_url = env::var("MY_AI_API_URL")
-            .map_err(|_| AppError::config("MY_AI_API_URL must be set"))?;
-        let my_ai_api_key = env::var("MY_AI_API_KEY")
-            .map_err(|_| AppError::config("MY_AI_API_KEY must be set"))?;
-        let discord_ks_bot_token = env::var("DISCORD_KS_BOT_TOKEN")
-            .map_err(|_| AppError::config("DISCORD_KS_BOT_TOKEN must be set"))?;
-
-        let use_mock_data = env::var("USE_MOCK_DATA")
-            .unwrap_or_else(|_| "false".to_string())
+        let api_url = take("API_URL");
+        let token = take("TOKEN");
+        let youtube_api_key = take("YOUTUBE_API_KEY");
+        let youtube_api_base = env::var("YOUTUBE_API_BASE")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_YOUTUBE_API_BASE.to_string());
+        let supabase_api_key = take("SUPABASE_API_KEY");
+        let mut ksforword_channel_ids = Self::env_list("KSFORWORD_CHANNEL_IDS");
+        if ksforword_channel_ids.is_empty() {
+            ksforword_channel_ids.push(take("KSFORWORD_CHANNEL_ID"));
+        }
+        let ksforword_channel_id = ksforword_channel_ids[0].clone();
+        let my_ai_api_url = take("MY_AI_API_URL");
+        let my_ai_api_key = take("MY_AI_API_KEY");
+        let discord_ks_bot_token = take("DISCORD_KS_BOT_TOKEN");
+
+        let discord_targets = Self::parse_discord_targets(&discord_ks_bot_token)?;
+        let discord_max_desc = Self::env_parse("DISCORD_MAX_DESC", DEFAULT_DISCORD_MAX_DESC)?;
+        let discord_thread_id = env::var("DISCORD_THREAD_ID")
+            .ok()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty());
+        let discord_username = env::var("DISCORD_USERNAME")
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        let discord_avatar_url = env::var("DISCORD_AVATAR_URL")
+            .ok()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+
+        let use_mock_data = Self::env_flag("USE_MOCK_DATA");
+        let strict_transcript = Self::env_flag("STRICT_TRANSCRIPT");
+        let embed_large_image = Self::env_flag("EMBED_LARGE_IMAGE");
+        let summary_languages = Self::env_list("SUMMARY_LANGUAGES");
+        let summary_languages_separate = Self::env_flag("SUMMARY_LANGUAGES_SEPARATE");
+        let summary_tldr = Self::env_flag("SUMMARY_TLDR");
+        let notify_on_no_new_video = Self::env_flag("NOTIFY_ON_NO_NEW_VIDEO");
+        let discord_markdown_compat = Self::env_flag("DISCORD_MARKDOWN_COMPAT");
+        let log_http_bodies = Self::env_flag("LOG_HTTP_BODIES");
+        let ai_debug_events = Self::env_flag("AI_DEBUG_EVENTS");
+        let low_quality_min_wpm = Self::env_parse("LOW_QUALITY_MIN_WPM", 0.0)?;
+        let low_quality_marker_ratio = Self::env_parse("LOW_QUALITY_MARKER_RATIO", DEFAULT_LOW_QUALITY_MARKER_RATIO)?;
+        let low_quality_action = match env::var("LOW_QUALITY_ACTION")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "describe" => LowQualityAction::Describe,
+            "skip" => LowQualityAction::Skip,
+            other => {
+                return Err(AppError::config(format!(
+                    "LOW_QUALITY_ACTION must be 'describe' or 'skip', got '{}'",
+                    other
+                )))
+            }
+        };
+        let low_quality_prompt = env::var("LOW_QUALITY_PROMPT")
+            .unwrap_or_else(|_| DEFAULT_LOW_QUALITY_PROMPT.to_string());
+        let db_path = env::var("DB_PATH").ok().filter(|p| !p.trim().is_empty());
+        let report_path = env::var("REPORT_PATH").ok().filter(|p| !p.trim().is_empty());
+        let dry_run = Self::env_flag("DRY_RUN");
+        let require_year_in_title = Self::env_flag("REQUIRE_YEAR_IN_TITLE");
+        let ai_temperature = Self::env_parse_opt("AI_TEMPERATURE")?;
+        let ai_max_tokens = Self::env_parse_opt("AI_MAX_TOKENS")?;
+        let ai_max_answer_chars = Self::env_parse_opt("AI_MAX_ANSWER_CHARS")?;
+        let max_estimated_tokens = Self::env_parse_opt("MAX_ESTIMATED_TOKENS")?;
+        let dedup_threshold = Self::env_parse_opt("DEDUP_THRESHOLD")?;
+        let dedup_state_path = env::var("DEDUP_STATE_PATH")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_DEDUP_STATE_PATH.to_string());
+        let show_channel_in_footer = Self::env_flag("SHOW_CHANNEL_IN_FOOTER");
+        let show_video_stats = Self::env_flag("SHOW_VIDEO_STATS");
+        let show_detected_language = Self::env_flag("SHOW_DETECTED_LANGUAGE");
+        let cache_dir = env::var("CACHE_DIR")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_CACHE_DIR.to_string());
+        let cache_ttl_secs = Self::env_parse_opt("CACHE_TTL_SECS")?;
+        let max_transcript_bytes = Self::env_parse_opt("MAX_TRANSCRIPT_BYTES")?;
+        let transcript_max_retries = Self::env_parse("TRANSCRIPT_MAX_RETRIES", DEFAULT_TRANSCRIPT_MAX_RETRIES)?;
+        let transcript_backoff_secs = Self::env_parse("TRANSCRIPT_BACKOFF_SECS", DEFAULT_TRANSCRIPT_BACKOFF_SECS)?;
+        let tidy_summaries = Self::env_parse("TIDY_SUMMARIES", DEFAULT_TIDY_SUMMARIES)?;
+        let min_transcript_coverage = Self::env_parse("MIN_TRANSCRIPT_COVERAGE", 0.0)?;
+        let skip_if_transcript_shorter_than = Self::env_parse("SKIP_IF_TRANSCRIPT_SHORTER_THAN", 0)?;
+        let require_keywords = Self::env_list("REQUIRE_KEYWORDS");
+        let partial_transcript_action = match env::var("PARTIAL_TRANSCRIPT_ACTION")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "warn" => PartialTranscriptAction::Warn,
+            "tag" => PartialTranscriptAction::Tag,
+            other => {
+                return Err(AppError::config(format!(
+                    "PARTIAL_TRANSCRIPT_ACTION must be 'warn' or 'tag', got '{}'",
+                    other
+                )))
+            }
+        };
+        // A set-but-blank AI_PERSONA is kept so validate() can reject it
+        let ai_persona = env::var("AI_PERSONA").unwrap_or_else(|_| DEFAULT_AI_PERSONA.to_string());
+        let ai_user_id = env::var("AI_USER_ID")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_AI_USER_ID.to_string());
+        let topic_personas = match env::var("TOPIC_PERSONAS") {
+            Ok(raw) if !raw.trim().is_empty() => serde_json::from_str(&raw).map_err(|e| {
+                AppError::config(format!("TOPIC_PERSONAS is not valid JSON: {}", e))
+            })?,
+            _ => Vec::new(),
+        };
+        let ai_backend = match env::var("AI_BACKEND")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "custom" => AiBackend::Custom,
+            "openai" => AiBackend::OpenAi,
+            other => {
+                return Err(AppError::config(format!(
+                    "AI_BACKEND must be 'custom' or 'openai', got '{}'",
+                    other
+                )))
+            }
+        };
+        let ai_model = env::var("AI_MODEL").ok().filter(|v| !v.trim().is_empty());
+        let summarizer = match env::var("SUMMARIZER")
+            .unwrap_or_default()
             .to_lowercase()
-            == "true";
+            .as_str()
+        {
+            "" | "ai" => Summarizer::Ai,
+            "extractive" => Summarizer::Extractive,
+            other => {
+                return Err(AppError::config(format!(
+                    "SUMMARIZER must be 'ai' or 'extractive', got '{}'",
+                    other
+                )))
+            }
+        };
+        let extractive_sentences = Self::env_parse("EXTRACTIVE_SENTENCES", DEFAULT_EXTRACTIVE_SENTENCES)?;
+        let published_date_format = env::var("PUBLISHED_DATE_FORMAT")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_PUBLISHED_DATE_FORMAT.to_string());
+        let ai_answer_path = env::var("AI_ANSWER_PATH")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_AI_ANSWER_PATH.to_string());
+        let extra_channel_ids = Self::env_list("EXTRA_CHANNEL_IDS");
+        let playlist_id = env::var("PLAYLIST_ID").ok().filter(|id| !id.trim().is_empty());
+        let sponsor_filter = Self::env_flag("SPONSOR_FILTER");
+        let sponsor_phrases = Self::env_list("SPONSOR_PHRASES");
+        let supadata_base_url = env::var("SUPADATA_BASE_URL")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_SUPADATA_BASE_URL.to_string());
+        let supadata_transcript_path = env::var("SUPADATA_TRANSCRIPT_PATH")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_SUPADATA_TRANSCRIPT_PATH.to_string());
+        let preferred_transcript_lang = env::var("TRANSCRIPT_LANG")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        let transcript_join = match env::var("TRANSCRIPT_JOIN")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "space" => TranscriptJoin::Space,
+            "newline" => TranscriptJoin::Newline,
+            "sentence" => TranscriptJoin::Sentence,
+            other => {
+                return Err(AppError::config(format!(
+                    "TRANSCRIPT_JOIN must be 'space', 'newline' or 'sentence', got '{}'",
+                    other
+                )))
+            }
+        };
+        let transcript_with_timestamps = Self::env_flag("TRANSCRIPT_WITH_TIMESTAMPS");
+        let state_path = env::var("STATE_PATH").ok().filter(|p| !p.trim().is_empty());
+        let process_all_matching = Self::env_flag("PROCESS_ALL_MATCHING");
+        let latest_count = Self::env_parse("LATEST_COUNT", DEFAULT_LATEST_COUNT)?;
+        let force_reprocess = Self::env_flag("FORCE_REPROCESS");
+        let per_video_budget_secs = Self::env_parse_opt("PER_VIDEO_BUDGET_SECS")?;
+        let max_concurrency = Self::env_parse("MAX_CONCURRENCY", DEFAULT_MAX_CONCURRENCY)?;
+        let since = match env::var("SINCE") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                Some(parse_since(&raw).map_err(|e| AppError::config(format!("SINCE: {}", e)))?)
+            }
+            _ => None,
+        };
+        let catch_up_limit = Self::env_parse("CATCH_UP_LIMIT", DEFAULT_CATCH_UP_LIMIT)?;
+        let first_run_limit = Self::env_parse("FIRST_RUN_LIMIT", DEFAULT_FIRST_RUN_LIMIT)?;
+        let catch_up_order = match env::var("CATCH_UP_ORDER")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "newest" | "newest_first" => CatchUpOrder::NewestFirst,
+            "oldest" | "oldest_first" => CatchUpOrder::OldestFirst,
+            other => {
+                return Err(AppError::config(format!(
+                    "CATCH_UP_ORDER must be 'newest' or 'oldest', got '{}'",
+                    other
+                )))
+            }
+        };
+        let few_shot_examples = match env::var("FEW_SHOT_EXAMPLES_PATH") {
+            Ok(path) if !path.trim().is_empty() => Self::load_few_shot_examples(path.trim())?,
+            _ => Vec::new(),
+        };
+        let ai_system_prompt = env::var("AI_SYSTEM_PROMPT")
+            .ok()
+            .filter(|v| !v.trim().is_empty());
 
         Ok(Self {
             api_url,
             token,
             youtube_api_key,
+            youtube_api_base,
             supabase_api_key,
             ksforword_channel_id,
+            ksforword_channel_ids,
             use_mock_data,
             my_ai_api_url,
             my_ai_api_key,
             discord_ks_bot_token,
+            discord_targets,
+            discord_thread_id,
+            discord_max_desc,
+            discord_username,
+            discord_avatar_url,
+            strict_transcript,
+            embed_large_image,
+            summary_languages,
+            summary_languages_separate,
+            summary_tldr,
+            notify_on_no_new_video,
+            discord_markdown_compat,
+            log_http_bodies,
+            ai_debug_events,
+            low_quality_min_wpm,
+            low_quality_marker_ratio,
+            low_quality_action,
+            low_quality_prompt,
+            db_path,
+            report_path,
+            dry_run,
+            require_year_in_title,
+            ai_temperature,
+            ai_max_tokens,
+            ai_max_answer_chars,
+            max_estimated_tokens,
+            ai_system_prompt,
+            few_shot_examples,
+            dedup_threshold,
+            dedup_state_path,
+            state_path,
+            catch_up_limit,
+            process_all_matching,
+            latest_count,
+            force_reprocess,
+            per_video_budget_secs,
+            since,
+            max_concurrency,
+            first_run_limit,
+            catch_up_order,
+            show_channel_in_footer,
+            show_video_stats,
+            show_detected_language,
+            cache_dir,
+            cache_ttl_secs,
+            max_transcript_bytes,
+            transcript_max_retries,
+            transcript_backoff_secs,
+            transcript_join,
+            transcript_with_timestamps,
+            tidy_summaries,
+            supadata_base_url,
+            supadata_transcript_path,
+            preferred_transcript_lang,
+            min_transcript_coverage,
+            partial_transcript_action,
+            skip_if_transcript_shorter_than,
+            require_keywords,
+            ai_persona,
+            ai_user_id,
+            topic_personas,
+            summarizer,
+            extractive_sentences,
+            published_date_format,
+            sponsor_filter,
+            sponsor_phrases,
+            extra_channel_ids,
+            playlist_id,
+            ai_answer_path,
+            ai_backend,
+            ai_model,
+        })
+    }
+
+    /// Read the required variables by name, reporting every missing one at once
+    /// rather than only the first. `KSFORWORD_CHANNEL_ID` is satisfied by a
+    /// non-empty `KSFORWORD_CHANNEL_IDS` too, and is then absent from the result.
+    fn read_required(lookup: impl Fn(&str) -> Option<String>) -> Result<HashMap<&'static str, String>> {
+        let missing: Vec<&str> = REQUIRED_ENV_VARS
+            .iter()
+            .copied()
+            .filter(|name| {
+                lookup(name).is_none()
+                    && !(*name == "KSFORWORD_CHANNEL_ID"
+                        && lookup("KSFORWORD_CHANNEL_IDS").is_some_and(|ids| !ids.trim().is_empty()))
+            })
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(AppError::config(format!("missing env vars: {}", missing.join(", "))));
+        }
+        Ok(REQUIRED_ENV_VARS
+            .iter()
+            .filter_map(|name| lookup(name).map(|value| (*name, value)))
+            .collect())
+    }
+
+    /// Read an optional boolean flag (`true`, case-insensitive), defaulting to false
+    fn env_flag(name: &str) -> bool {
+        env::var(name)
+            .unwrap_or_else(|_| "false".to_string())
+            .to_lowercase()
+            == "true"
+    }
+
+    /// Parse an optional value, using `default` when unset and erroring when malformed
+    fn env_parse<T: std::str::FromStr>(name: &str, default: T) -> Result<T> {
+        match env::var(name) {
+            Ok(raw) if !raw.trim().is_empty() => raw.trim().parse().map_err(|_| {
+                AppError::config(format!("{} has an invalid value: '{}'", name, raw))
+            }),
+            _ => Ok(default),
+        }
+    }
+
+    /// Parse an optional value, returning `None` when unset and erroring when malformed
+    fn env_parse_opt<T: std::str::FromStr>(name: &str) -> Result<Option<T>> {
+        match env::var(name) {
+            Ok(raw) if !raw.trim().is_empty() => raw.trim().parse().map(Some).map_err(|_| {
+                AppError::config(format!("{} has an invalid value: '{}'", name, raw))
+            }),
+            _ => Ok(None),
+        }
+    }
+
+    /// Parse `DISCORD_KS_BOT_TOKEN`: a single webhook URL, or a JSON array of
+    /// `{ url, footer, color, embed }` targets
+    fn parse_discord_targets(raw: &str) -> Result<Vec<DiscordTarget>> {
+        let raw = raw.trim();
+        if !raw.starts_with('[') {
+            return Ok(vec![DiscordTarget::from_url(raw)]);
+        }
+
+        let targets: Vec<DiscordTarget> = serde_json::from_str(raw).map_err(|e| {
+            AppError::config(format!("DISCORD_KS_BOT_TOKEN is not a valid target list: {}", e))
+        })?;
+        if targets.is_empty() {
+            return Err(AppError::config("DISCORD_KS_BOT_TOKEN target list is empty"));
+        }
+        Ok(targets)
+    }
+
+    /// Load few-shot examples from a JSON array of `{input, output}` objects
+    fn load_few_shot_examples(path: &str) -> Result<Vec<FewShotExample>> {
+        let raw = std::fs::read_to_string(path).map_err(|e| {
+            AppError::config(format!("FEW_SHOT_EXAMPLES_PATH '{}' could not be read: {}", path, e))
+        })?;
+        serde_json::from_str(&raw).map_err(|e| {
+            AppError::config(format!("FEW_SHOT_EXAMPLES_PATH '{}' is not valid JSON: {}", path, e))
         })
     }
 
+    /// Read an optional comma-separated list, skipping empty entries
+    fn env_list(name: &str) -> Vec<String> {
+        env::var(name)
+            .unwrap_or_default()
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+
     /// Validate configuration values
     pub fn validate(&self) -> Result<()> {
         // Validate URLs
         Self::validate_url(&self.api_url, "API_URL")?;
         Self::validate_url(&self.my_ai_api_url, "MY_AI_API_URL")?;
-        Self::validate_url(&self.discord_ks_bot_token, "DISCORD_KS_BOT_TOKEN")?;
+        for target in &self.discord_targets {
+            Self::validate_url(&target.url, "DISCORD_KS_BOT_TOKEN")?;
+        }
+        if let Some(thread_id) = &self.discord_thread_id {
+            Self::validate_thread_id(thread_id)?;
+        }
+        if let Some(avatar_url) = &self.discord_avatar_url {
+            Self::validate_url(avatar_url, "DISCORD_AVATAR_URL")?;
+        }
 
         // Validate API keys (basic format check)
         if self.youtube_api_key.len() < 10 {
@@ -89,16 +866,112 @@ impl Config {
         }
 
         // Validate channel ID format (YouTube channel IDs are typically 24 chars)
-        if self.ksforword_channel_id.len() < 10 {
+        if self.polled_channel_ids().is_empty() {
+            return Err(AppError::config(
+                "at least one channel id is required (KSFORWORD_CHANNEL_ID/KSFORWORD_CHANNEL_IDS)",
+            ));
+        }
+        for channel_id in self.polled_channel_ids() {
+            // @handles are resolved to channel ids at run time
+            if !channel_id.starts_with('@') && channel_id.len() < 10 {
+                return Err(AppError::config(format!(
+                    "KSFORWORD_CHANNEL_ID '{}' appears to be invalid (too short)",
+                    channel_id
+                )));
+            }
+        }
+
+        Self::validate_url(&self.youtube_api_base, "YOUTUBE_API_BASE")?;
+        Self::validate_url(&self.supadata_base_url, "SUPADATA_BASE_URL")?;
+        if !self.supadata_transcript_path.starts_with('/') {
+            return Err(AppError::config(format!(
+                "SUPADATA_TRANSCRIPT_PATH must start with '/', got '{}'",
+                self.supadata_transcript_path
+            )));
+        }
+
+        if self.ai_persona.trim().is_empty() {
+            return Err(AppError::config("AI_PERSONA cannot be empty"));
+        }
+
+        if self.ai_backend == AiBackend::OpenAi && self.ai_model.is_none() {
+            return Err(AppError::config("AI_MODEL is required when AI_BACKEND=openai"));
+        }
+
+        if let Some(temperature) = self.ai_temperature {
+            if !(0.0..=2.0).contains(&temperature) {
+                return Err(AppError::config(format!(
+                    "AI_TEMPERATURE must be between 0 and 2, got {}",
+                    temperature
+                )));
+            }
+        }
+
+        if let Some(threshold) = self.dedup_threshold {
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err(AppError::config(format!(
+                    "DEDUP_THRESHOLD must be between 0 and 1, got {}",
+                    threshold
+                )));
+            }
+        }
+
+        if StrftimeItems::new(&self.published_date_format).any(|item| item == Item::Error) {
+            return Err(AppError::config(format!(
+                "PUBLISHED_DATE_FORMAT is not a valid date format: '{}'",
+                self.published_date_format
+            )));
+        }
+
+        if self.latest_count == 0 {
+            return Err(AppError::config("LATEST_COUNT must be at least 1"));
+        }
+        // Both pick how many videos a run without STATE_PATH processes
+        if self.latest_count > 1 && self.process_all_matching {
             return Err(AppError::config(
-                "KSFORWORD_CHANNEL_ID appears to be invalid (too short)",
+                "set either LATEST_COUNT or PROCESS_ALL_MATCHING (with CATCH_UP_LIMIT), not both",
             ));
         }
 
+        if self.transcript_max_retries == 0 {
+            return Err(AppError::config("TRANSCRIPT_MAX_RETRIES must be at least 1"));
+        }
+
+        if self.per_video_budget_secs == Some(0) {
+            return Err(AppError::config("PER_VIDEO_BUDGET_SECS must be greater than 0"));
+        }
+
+        if self.ai_max_tokens == Some(0) {
+            return Err(AppError::config("AI_MAX_TOKENS must be greater than 0"));
+        }
+
+        if self.max_concurrency == 0 {
+            return Err(AppError::config("MAX_CONCURRENCY must be greater than 0"));
+        }
+
+        if self.discord_max_desc == 0 {
+            return Err(AppError::config("DISCORD_MAX_DESC must be greater than 0"));
+        }
+
+        if self.ai_max_answer_chars == Some(0) {
+            return Err(AppError::config("AI_MAX_ANSWER_CHARS must be greater than 0"));
+        }
+
         tracing::debug!("Configuration validation passed");
         Ok(())
     }
 
+    /// Discord thread ids are snowflakes: non-empty and all digits
+    fn validate_thread_id(thread_id: &str) -> Result<()> {
+        if thread_id.is_empty() || !thread_id.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(AppError::config(format!(
+                "DISCORD_THREAD_ID must be a numeric thread id, got '{}'",
+                thread_id
+            )));
+        }
+        Ok(())
+    }
+
     /// Validate URL format
     fn validate_url(url: &str, name: &str) -> Result<()> {
         if url.is_empty() {
@@ -116,6 +989,7 @@ impl Config {
     }
 
     /// Get global configuration instance (cached)
+    #[allow(dead_code)]
     pub fn global() -> &'static Config {
         CONFIG.get_or_init(|| {
             let config = Self::from_env()
@@ -127,6 +1001,7 @@ impl Config {
     }
 
     /// Format configuration for logging (redacts sensitive values)
+    #[allow(dead_code)]
     pub fn to_safe_string(&self) -> String {
         format!(
             "Config {{ \
@@ -136,18 +1011,95 @@ impl Config {
              my_ai_api_url: {}, \
              my_ai_api_key: {}, \
              discord_webhook: {}, \
-             use_mock_data: {} \
+             use_mock_data: {}, \
+             strict_transcript: {} \
              }}",
             self.api_url,
             Self::mask_key(&self.youtube_api_key),
             self.ksforword_channel_id,
             self.my_ai_api_url,
             Self::mask_key(&self.my_ai_api_key),
-            Self::mask_url(&self.discord_ks_bot_token),
-            self.use_mock_data
+            self.discord_targets
+                .iter()
+                .map(|target| Self::mask_url(&target.url))
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.use_mock_data,
+            self.strict_transcript
+        )
+    }
+
+    /// KS Forward channels polled each run, without duplicates
+    pub fn polled_channel_ids(&self) -> Vec<String> {
+        Self::unique_ids(std::iter::once(&self.ksforword_channel_id).chain(&self.ksforword_channel_ids))
+    }
+
+    /// Every configured channel ID (polled channels first, then
+    /// `extra_channel_ids`), without duplicates
+    pub fn channel_ids(&self) -> Vec<String> {
+        Self::unique_ids(self.polled_channel_ids().iter().chain(&self.extra_channel_ids))
+    }
+
+    fn unique_ids<'a>(candidates: impl Iterator<Item = &'a String>) -> Vec<String> {
+        let mut ids: Vec<String> = Vec::new();
+        for id in candidates {
+            if !id.trim().is_empty() && !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        }
+        ids
+    }
+
+    /// YouTube Data API endpoint: the configured base joined with `path` (e.g. "/search")
+    pub fn youtube_endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.youtube_api_base.trim_end_matches('/'), path)
+    }
+
+    /// Full transcript endpoint: base URL joined with the configured path
+    pub fn transcript_endpoint(&self) -> String {
+        format!(
+            "{}{}",
+            self.supadata_base_url.trim_end_matches('/'),
+            self.supadata_transcript_path
         )
     }
 
+    /// Persona for a video: the first topic whose keyword appears in the title or
+    /// description (case-insensitive), otherwise `ai_persona`
+    pub fn persona_for(&self, title: &str, description: &str) -> &str {
+        let haystack = format!("{}\n{}", title, description).to_lowercase();
+        self.topic_personas
+            .iter()
+            .find(|topic| {
+                topic
+                    .keywords
+                    .iter()
+                    .any(|keyword| !keyword.trim().is_empty() && haystack.contains(&keyword.trim().to_lowercase()))
+            })
+            .map(|topic| {
+                tracing::info!("Topic '{}' matched; using persona '{}'", topic.topic, topic.persona);
+                topic.persona.as_str()
+            })
+            .unwrap_or(&self.ai_persona)
+    }
+
+    /// Secret values that must never appear in logs
+    pub fn secrets(&self) -> Vec<&str> {
+        let webhook_tokens = self
+            .discord_targets
+            .iter()
+            .map(|target| target.url.rsplit('/').next().unwrap_or_default());
+
+        let mut secrets = vec![
+            self.token.as_str(),
+            self.youtube_api_key.as_str(),
+            self.supabase_api_key.as_str(),
+            self.my_ai_api_key.as_str(),
+        ];
+        secrets.extend(webhook_tokens);
+        secrets
+    }
+
     /// Mask API key for logging
     fn mask_key(key: &str) -> String {
         if key.len() <= 8 {
@@ -174,10 +1126,221 @@ impl Config {
     }
 }
 
+
+/// Generate a chainable setter per field on `ConfigBuilder`; each accepts
+/// anything convertible into the field type (`&str` for `String`, `T` for
+/// `Option<T>`, ...)
+#[cfg(test)]
+macro_rules! builder_setters {
+    ($($field:ident: $ty:ty,)*) => {
+        impl ConfigBuilder {
+            $(
+                // One setter per `Config` field; each test calls the few it needs
+                #[allow(dead_code)]
+                pub fn $field(mut self, value: impl Into<$ty>) -> Self {
+                    self.config.$field = value.into();
+                    self
+                }
+            )*
+        }
+
+        // Fails to compile when a `Config` field has no setter
+        const _: fn(Config) = |Config { $($field: _,)* }| {};
+    };
+}
+
+/// Programmatic `Config` construction for tests that must not touch
+/// process-wide env vars. Unset fields get the same defaults as `from_env`.
+/// The binary always loads its config with `from_env`, so this is test-only.
+/// There is a setter for every field, which `builder_setters!` checks at compile time.
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+#[cfg(test)]
+impl Config {
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+}
+
+#[cfg(test)]
+builder_setters! {
+    api_url: String,
+    token: String,
+    youtube_api_key: String,
+    youtube_api_base: String,
+    supabase_api_key: String,
+    ksforword_channel_id: String,
+    ksforword_channel_ids: Vec<String>,
+    extra_channel_ids: Vec<String>,
+    playlist_id: Option<String>,
+    use_mock_data: bool,
+    my_ai_api_url: String,
+    my_ai_api_key: String,
+    discord_ks_bot_token: String,
+    discord_targets: Vec<DiscordTarget>,
+    discord_thread_id: Option<String>,
+    discord_max_desc: usize,
+    discord_username: Option<String>,
+    discord_avatar_url: Option<String>,
+    strict_transcript: bool,
+    embed_large_image: bool,
+    summary_languages: Vec<String>,
+    summary_languages_separate: bool,
+    summary_tldr: bool,
+    notify_on_no_new_video: bool,
+    discord_markdown_compat: bool,
+    log_http_bodies: bool,
+    ai_debug_events: bool,
+    low_quality_min_wpm: f64,
+    low_quality_marker_ratio: f64,
+    low_quality_action: LowQualityAction,
+    low_quality_prompt: String,
+    db_path: Option<String>,
+    report_path: Option<String>,
+    dry_run: bool,
+    require_year_in_title: bool,
+    ai_temperature: Option<f32>,
+    ai_max_tokens: Option<u32>,
+    ai_max_answer_chars: Option<usize>,
+    max_estimated_tokens: Option<usize>,
+    ai_system_prompt: Option<String>,
+    few_shot_examples: Vec<FewShotExample>,
+    dedup_threshold: Option<f64>,
+    dedup_state_path: String,
+    state_path: Option<String>,
+    catch_up_limit: usize,
+    process_all_matching: bool,
+    latest_count: usize,
+    force_reprocess: bool,
+    per_video_budget_secs: Option<u64>,
+    since: Option<DateTime<Utc>>,
+    max_concurrency: usize,
+    first_run_limit: usize,
+    catch_up_order: CatchUpOrder,
+    show_channel_in_footer: bool,
+    show_video_stats: bool,
+    show_detected_language: bool,
+    cache_dir: String,
+    cache_ttl_secs: Option<u64>,
+    max_transcript_bytes: Option<usize>,
+    transcript_max_retries: usize,
+    transcript_backoff_secs: u64,
+    transcript_join: TranscriptJoin,
+    transcript_with_timestamps: bool,
+    tidy_summaries: bool,
+    supadata_base_url: String,
+    supadata_transcript_path: String,
+    preferred_transcript_lang: Option<String>,
+    min_transcript_coverage: f64,
+    partial_transcript_action: PartialTranscriptAction,
+    skip_if_transcript_shorter_than: usize,
+    require_keywords: Vec<String>,
+    ai_persona: String,
+    ai_user_id: String,
+    ai_answer_path: String,
+    ai_backend: AiBackend,
+    ai_model: Option<String>,
+    topic_personas: Vec<TopicPersona>,
+    summarizer: Summarizer,
+    extractive_sentences: usize,
+    published_date_format: String,
+    sponsor_filter: bool,
+    sponsor_phrases: Vec<String>,
+}
+
+#[cfg(test)]
+impl ConfigBuilder {
+    /// Finish the config, filling the fields `from_env` derives from others:
+    /// the primary/polled channel ids and the Discord targets
+    pub fn build(self) -> Config {
+        let mut config = self.config;
+        if config.ksforword_channel_ids.is_empty() && !config.ksforword_channel_id.is_empty() {
+            config.ksforword_channel_ids = vec![config.ksforword_channel_id.clone()];
+        }
+        if config.ksforword_channel_id.is_empty() {
+            if let Some(first) = config.ksforword_channel_ids.first() {
+                config.ksforword_channel_id = first.clone();
+            }
+        }
+        if config.discord_targets.is_empty() && !config.discord_ks_bot_token.trim().is_empty() {
+            config.discord_targets =
+                Config::parse_discord_targets(&config.discord_ks_bot_token).unwrap_or_default();
+        }
+        config
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_missing_env_vars_are_listed_together() {
+        let mut vars: HashMap<&str, &str> = HashMap::from([
+            ("API_URL", "https://api.example.com"),
+            ("YOUTUBE_API_KEY", "AIza-example-key"),
+            ("SUPABASE_API_KEY", "supabase-key"),
+            ("MY_AI_API_KEY", "ai-key"),
+            ("DISCORD_KS_BOT_TOKEN", "https://discord.com/api/webhooks/1/abc"),
+            ("USE_MOCK_DATA", "true"),
+        ]);
+        let check = |vars: &HashMap<&str, &str>| {
+            Config::read_required(|name| vars.get(name).map(|v| v.to_string()))
+        };
+
+        let err = check(&vars).unwrap_err().to_string();
+        assert!(err.ends_with("missing env vars: TOKEN, KSFORWORD_CHANNEL_ID, MY_AI_API_URL"), "{}", err);
+
+        vars.insert("TOKEN", "token");
+        vars.insert("MY_AI_API_URL", "https://ai.example.com");
+        vars.insert("KSFORWORD_CHANNEL_IDS", "UC_first,UC_second");
+        let required = check(&vars).unwrap();
+        assert_eq!(required["TOKEN"], "token");
+        assert!(!required.contains_key("KSFORWORD_CHANNEL_ID"));
+    }
+
+    #[test]
+    fn test_builder_fills_env_defaults() {
+        let config = Config::builder()
+            .my_ai_api_url("http://127.0.0.1:9000/chat")
+            .ksforword_channel_id("UC_first")
+            .discord_ks_bot_token("http://127.0.0.1:9000/api/webhooks/1/token")
+            .ai_temperature(0.2)
+            .build();
+
+        assert_eq!(config.my_ai_api_url, "http://127.0.0.1:9000/chat");
+        assert_eq!(config.ai_temperature, Some(0.2));
+        assert_eq!(config.polled_channel_ids(), ["UC_first"]);
+        assert_eq!(config.discord_targets[0].url, "http://127.0.0.1:9000/api/webhooks/1/token");
+        assert_eq!(config.ai_persona, DEFAULT_AI_PERSONA);
+        assert_eq!(config.published_date_format, DEFAULT_PUBLISHED_DATE_FORMAT);
+        assert_eq!(config.catch_up_limit, 3);
+        assert!(config.tidy_summaries);
+    }
+
+    #[test]
+    fn test_channel_ids_combine_polled_and_extra() {
+        let config = Config {
+            ksforword_channel_id: "UC_first".to_string(),
+            ksforword_channel_ids: vec!["UC_first".to_string(), "UC_second".to_string()],
+            extra_channel_ids: vec!["UC_second".to_string(), "UC_extra".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(config.polled_channel_ids(), ["UC_first", "UC_second"]);
+        assert_eq!(config.channel_ids(), ["UC_first", "UC_second", "UC_extra"]);
+
+        // Configs built without the list still poll the single channel
+        let single = Config {
+            ksforword_channel_id: "UC_only".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(single.polled_channel_ids(), ["UC_only"]);
+    }
+
     #[test]
     fn test_validate_url() {
         assert!(Config::validate_url("https://example.com", "TEST").is_ok());
@@ -186,9 +1349,127 @@ mod tests {
         assert!(Config::validate_url("", "TEST").is_err());
     }
 
+    #[test]
+    fn test_validate_thread_id() {
+        assert!(Config::validate_thread_id("1234567890123456789").is_ok());
+        assert!(Config::validate_thread_id("thread-42").is_err());
+        assert!(Config::validate_thread_id("").is_err());
+    }
+
     #[test]
     fn test_mask_key() {
         assert_eq!(Config::mask_key("12345678"), "***");
         assert_eq!(Config::mask_key("ABCDEFGHIJKL"), "ABCD...IJKL");
     }
+
+    #[test]
+    fn test_parse_discord_targets() {
+        let single = Config::parse_discord_targets("https://discord.com/api/webhooks/1/a").unwrap();
+        assert_eq!(single, vec![DiscordTarget::from_url("https://discord.com/api/webhooks/1/a")]);
+
+        let raw = r#"[{"url":"https://discord.com/api/webhooks/1/a"},
+                     {"url":"https://discord.com/api/webhooks/2/b","footer":"Mirror","color":15158332,"embed":false}]"#;
+        let targets = Config::parse_discord_targets(raw).unwrap();
+        assert_eq!(targets.len(), 2);
+        assert!(targets[0].embed);
+        assert_eq!(targets[1].footer.as_deref(), Some("Mirror"));
+        assert_eq!(targets[1].color, Some(15158332));
+        assert!(!targets[1].embed);
+
+        assert!(Config::parse_discord_targets("[]").is_err());
+        assert!(Config::parse_discord_targets("[{\"footer\":\"x\"}]").is_err());
+    }
+
+    #[test]
+    fn test_transcript_endpoint_uses_configured_path() {
+        let config = Config {
+            supadata_base_url: "https://api.supadata.ai/".to_string(),
+            supadata_transcript_path: "/v2/youtube/transcript".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(
+            config.transcript_endpoint(),
+            "https://api.supadata.ai/v2/youtube/transcript"
+        );
+    }
+
+    #[test]
+    fn test_persona_for_topic_keyword() {
+        let config = Config {
+            ai_persona: DEFAULT_AI_PERSONA.to_string(),
+            topic_personas: vec![
+                TopicPersona {
+                    topic: "property".to_string(),
+                    keywords: vec!["คอนโด".to_string(), "Property".to_string()],
+                    persona: "ks-property".to_string(),
+                },
+                TopicPersona {
+                    topic: "markets".to_string(),
+                    keywords: vec!["SET".to_string()],
+                    persona: "ks-markets".to_string(),
+                },
+            ],
+            ..Config::default()
+        };
+
+        assert_eq!(config.persona_for("KS Forward: property outlook 2025", ""), "ks-property");
+        assert_eq!(config.persona_for("KS Forward", "วิเคราะห์ SET วันนี้"), "ks-markets");
+        assert_eq!(config.persona_for("KS Forward: weekly recap", ""), DEFAULT_AI_PERSONA);
+    }
+
+    #[test]
+    fn test_blank_persona_is_rejected() {
+        let config = Config::builder()
+            .api_url("https://api.example.com")
+            .youtube_api_key("AIza-example-key")
+            .supabase_api_key("supabase-key")
+            .ksforword_channel_id("UCxxxxxxxxxxxxxxxxxx")
+            .my_ai_api_url("https://ai.example.com/chat")
+            .discord_ks_bot_token("https://discord.com/api/webhooks/1/abc")
+            .build();
+        assert!(config.validate().is_ok());
+
+        let blank = Config { ai_persona: "  ".to_string(), ..config };
+        let err = blank.validate().unwrap_err().to_string();
+        assert!(err.contains("AI_PERSONA cannot be empty"), "{}", err);
+    }
+
+    #[test]
+    fn test_latest_count_conflicts_with_process_all_matching() {
+        let config = Config::builder()
+            .api_url("https://api.example.com")
+            .youtube_api_key("AIza-example-key")
+            .supabase_api_key("supabase-key")
+            .ksforword_channel_id("UCxxxxxxxxxxxxxxxxxx")
+            .my_ai_api_url("https://ai.example.com/chat")
+            .process_all_matching(true)
+            .build();
+        assert!(config.validate().is_ok());
+
+        let both = Config { latest_count: 5, ..config };
+        let err = both.validate().unwrap_err().to_string();
+        assert!(err.contains("either LATEST_COUNT or PROCESS_ALL_MATCHING"), "{}", err);
+    }
+
+    #[test]
+    fn test_empty_channel_list_is_reported() {
+        let config = Config::builder()
+            .api_url("https://api.example.com")
+            .youtube_api_key("AIza-example-key")
+            .supabase_api_key("supabase-key")
+            .my_ai_api_url("https://ai.example.com/chat")
+            .build();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("at least one channel id is required"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_since_accepts_dates_and_rfc3339() {
+        assert_eq!(parse_since("2024-01-01").unwrap().to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(
+            parse_since(" 2024-01-01T09:30:00+07:00 ").unwrap().to_rfc3339(),
+            "2024-01-01T02:30:00+00:00"
+        );
+        assert!(parse_since("01/01/2024").unwrap_err().contains("expected YYYY-MM-DD"));
+    }
 }
@@ -32,6 +32,9 @@ pub enum AppError {
     #[error("Transcript not found for video: {video_id}")]
     TranscriptNotFound { video_id: String },
 
+    #[error("Transcript too large: {bytes} bytes (max: {max})")]
+    TranscriptTooLarge { bytes: usize, max: usize },
+
     /// AI service errors
     #[error("AI service error: {0}")]
     AIService(String),
@@ -44,12 +47,18 @@ pub enum AppError {
     Discord { status: u16 },
 
     #[error("Discord message too long: {length} chars (max: {max})")]
+    #[allow(dead_code)]
     MessageTooLong { length: usize, max: usize },
 
     /// Cache errors
     #[error("Cache error: {0}")]
     Cache(String),
 
+    /// Database errors
+    #[cfg(feature = "sqlite")]
+    #[error("Database error: {0}")]
+    Database(String),
+
     /// IO errors
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -82,6 +91,15 @@ impl AppError {
         AppError::Cache(message.into())
     }
 
+    /// Recover the `AppError` inside a boxed pipeline error so `is_retryable`
+    /// still sees it; any other error becomes `Internal`, prefixed with `context`
+    pub fn from_boxed(err: Box<dyn std::error::Error>, context: &str) -> Self {
+        match err.downcast::<AppError>() {
+            Ok(err) => *err,
+            Err(err) => AppError::Internal(format!("{}: {}", context, err)),
+        }
+    }
+
     /// Check if error is retryable
     pub fn is_retryable(&self) -> bool {
         matches!(
@@ -100,15 +118,41 @@ impl AppError {
             AppError::ApiError { .. } | AppError::ApiTimeout { .. } => "api",
             AppError::Network(_) => "network",
             AppError::JsonParse { .. } | AppError::InvalidResponse(_) => "parse",
-            AppError::YouTube(_) | AppError::TranscriptNotFound { .. } => "youtube",
+            AppError::YouTube(_)
+            | AppError::TranscriptNotFound { .. }
+            | AppError::TranscriptTooLarge { .. } => "youtube",
             AppError::AIService(_) | AppError::AIParse(_) => "ai_service",
             AppError::Discord { .. } | AppError::MessageTooLong { .. } => "discord",
             AppError::Cache(_) => "cache",
+            #[cfg(feature = "sqlite")]
+            AppError::Database(_) => "database",
             AppError::Io(_) => "io",
             AppError::Internal(_) => "internal",
         }
     }
 }
 
+impl From<serde_json::Error> for AppError {
+    /// Keep the position of the failure; errors from `from_value` have none
+    fn from(e: serde_json::Error) -> Self {
+        let location = if e.line() == 0 {
+            "JSON value".to_string()
+        } else {
+            format!("line {}, column {}", e.line(), e.column())
+        };
+        AppError::JsonParse {
+            location,
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        AppError::Database(e.to_string())
+    }
+}
+
 /// Result type alias for convenience
 pub type Result<T> = std::result::Result<T, AppError>;
@@ -5,18 +5,107 @@ mod config;
 mod error;
 mod models;
 mod services;
+mod utils;
 
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
 use config::Config;
 use error::{AppError, Result};
-use services::ksForword_service::get_lastest_ksForword;
+use services::app::App;
+use services::ksForword_service::{get_lastest_ksForword, RunOutcome};
+use std::path::PathBuf;
+use std::time::Instant;
 use tracing::{error, info};
 use tracing_subscriber::{
     fmt, prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, EnvFilter,
 };
 
+/// Command-line interface
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Process the latest KS Forward video (default)
+    #[command(alias = "latest")]
+    Run {
+        /// Process every matching video published after this date instead
+        /// (YYYY-MM-DD or RFC 3339; overrides SINCE)
+        #[arg(long, value_parser = config::parse_since)]
+        since: Option<DateTime<Utc>>,
+    },
+    /// Summarize an arbitrary video link and post it to Discord
+    Summarize {
+        /// YouTube video URL
+        url: String,
+    },
+    /// Export a video's transcript as subtitles
+    ExportSubs {
+        /// YouTube video URL
+        url: String,
+        /// Subtitle format
+        #[arg(long, value_enum, default_value_t = SubtitleFormat::Srt)]
+        format: SubtitleFormat,
+        /// Output file (defaults to <video_id>.<format>)
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Summarize every matching video published since a date (`run --since`)
+    Backfill {
+        /// Start date (YYYY-MM-DD at midnight UTC, or RFC 3339; defaults to SINCE)
+        #[arg(long, value_parser = config::parse_since)]
+        since: Option<DateTime<Utc>>,
+    },
+    /// Post one comparative summary of a channel's recent matching videos
+    Compare {
+        /// YouTube channel ID
+        channel_id: String,
+        /// Number of recent videos to compare
+        #[arg(long, default_value_t = 5)]
+        last: usize,
+    },
+    /// Summarize every video of a playlist that was not processed yet
+    Playlist {
+        /// YouTube playlist ID (defaults to PLAYLIST_ID)
+        playlist_id: Option<String>,
+    },
+    /// Check outbound HTTPS and that YouTube, the transcript API, the AI backend and Discord are reachable
+    Healthcheck,
+    /// Print the most recently archived summaries from DB_PATH
+    #[cfg(feature = "sqlite")]
+    Archive {
+        /// Number of summaries to show
+        #[arg(long, default_value_t = 10)]
+        last: usize,
+    },
+    /// Inspect the configured YouTube channels
+    Channels {
+        #[command(subcommand)]
+        action: ChannelsCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ChannelsCommand {
+    /// Report whether each configured channel ID is reachable
+    Check,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
 /// Application entry point
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
     // Initialize tracing
     init_tracing();
 
@@ -24,12 +113,37 @@ async fn main() -> Result<()> {
     info!("📅 Version: {}", env!("CARGO_PKG_VERSION"));
 
     // Load configuration
-    let config = Config::from_env()?;
+    let mut config = Config::from_env()?;
+    if let Some(Command::Run { since: Some(since) } | Command::Backfill { since: Some(since) }) = &cli.command {
+        config.since = Some(*since);
+    }
     config.validate()?;
     info!("✅ Configuration loaded and validated");
 
-    // Run main processing
-    match process(&config).await {
+    // The run's configuration and HTTP client, shared by every command
+    let app = App::new(config);
+    let config = app.config();
+
+    let result = match cli.command.unwrap_or(Command::Run { since: None }) {
+        Command::Run { .. } => process(&app).await,
+        Command::Summarize { url } => summarize(&app, &url).await,
+        Command::ExportSubs {
+            url,
+            format,
+            output,
+        } => export_subs(&app, &url, format, output).await,
+        Command::Backfill { .. } => backfill(&app).await,
+        Command::Compare { channel_id, last } => compare(&app, &channel_id, last).await,
+        Command::Playlist { playlist_id } => playlist(&app, playlist_id).await,
+        Command::Healthcheck => healthcheck(config).await,
+        #[cfg(feature = "sqlite")]
+        Command::Archive { last } => archive(config, last),
+        Command::Channels {
+            action: ChannelsCommand::Check,
+        } => check_channels(config).await,
+    };
+
+    match result {
         Ok(_) => {
             info!("✅ Processing completed successfully");
             Ok(())
@@ -63,14 +177,160 @@ fn init_tracing() {
 }
 
 /// Main processing logic
-async fn process(config: &Config) -> Result<()> {
+async fn process(app: &App) -> Result<()> {
     info!("🎬 Processing latest KS Forward video");
 
-    get_lastest_ksForword(config)
+    let started = Instant::now();
+    let (outcome, metrics) = utils::metrics::scope(get_lastest_ksForword(app)).await;
+    info!("📈 Run metrics: {}", metrics.summary_line(started.elapsed()));
+    let outcome = outcome.map_err(|e| AppError::from_boxed(e, "Failed to process KS Forward"))?;
+
+    // Every outcome exits 0; the status line lets cron monitoring tell them apart
+    let status = match outcome.outcome {
+        RunOutcome::Processed if outcome.posted => "posted",
+        RunOutcome::Processed => "summarized (not posted)",
+        RunOutcome::NoNewVideo => "nothing new",
+        RunOutcome::NoMatches => "no matching videos",
+        _ => "skipped",
+    };
+    info!("🏁 Run status: {}", status);
+
+    info!(
+        "📊 Latest KS Forward run finished: {:?} (video: {}, title: {}, transcript: {} chars, summary: {} chars, posted: {})",
+        outcome.outcome,
+        outcome.video_id.as_deref().unwrap_or("-"),
+        outcome.title.as_deref().unwrap_or("-"),
+        outcome.transcript_chars,
+        outcome.answer_chars,
+        outcome.posted
+    );
+    Ok(())
+}
+
+/// Summarize one video link and post it
+async fn summarize(app: &App, url: &str) -> Result<()> {
+    services::youtube_service::extract_video_id(url)
+        .await
+        .map_err(|_| AppError::youtube(format!("'{}' is not a YouTube video URL", url)))?;
+    info!("📝 Summarizing {}", url);
+
+    services::ksForword_service::get_summary_link(app, url)
+        .await
+        .map_err(|e| AppError::from_boxed(e, &format!("Failed to summarize {}", url)))?;
+
+    info!("📊 Summary posted");
+    Ok(())
+}
+
+/// Summarize all matching videos since a date: a `run` that requires `--since`/`SINCE`
+async fn backfill(app: &App) -> Result<()> {
+    let since = app
+        .config()
+        .since
+        .ok_or_else(|| AppError::config("no start date given; pass --since or set SINCE"))?;
+    info!("🗂️  Backfilling KS Forward videos since {}", since.date_naive());
+    process(app).await
+}
+
+/// Summarize a channel's recent videos into one comparative post
+async fn compare(app: &App, channel_id: &str, last: usize) -> Result<()> {
+    info!("⚖️  Comparing the last {} video(s) of {}", last, channel_id);
+
+    services::ksForword_service::compare_recent(app, channel_id, last)
         .await
-        .map_err(|e| AppError::Internal(format!("Failed to process KS Forward: {}", e)))?;
+        .map_err(|e| AppError::from_boxed(e, "Failed to compare videos"))?;
+
+    info!("📊 Comparative summary posted");
+    Ok(())
+}
+
+/// Summarize a playlist given on the command line or in `PLAYLIST_ID`
+async fn playlist(app: &App, playlist_id: Option<String>) -> Result<()> {
+    let playlist_id = playlist_id
+        .or_else(|| app.config().playlist_id.clone())
+        .ok_or_else(|| AppError::config("no playlist given; pass a playlist ID or set PLAYLIST_ID"))?;
+    info!("📼 Summarizing playlist {}", playlist_id);
+
+    let started = Instant::now();
+    let (outcome, metrics) =
+        utils::metrics::scope(services::ksForword_service::summarize_playlist(app, &playlist_id)).await;
+    info!("📈 Run metrics: {}", metrics.summary_line(started.elapsed()));
+    let outcome =
+        outcome.map_err(|e| AppError::from_boxed(e, &format!("Failed to summarize playlist {}", playlist_id)))?;
+
+    info!(
+        "📊 Playlist processed {} video(s), skipped {}",
+        outcome.processed, outcome.skipped
+    );
+    Ok(())
+}
+
+/// Probe every configured channel and print a reachability table
+async fn check_channels(config: &Config) -> Result<()> {
+    info!("🔎 Checking {} configured channel(s)", config.channel_ids().len());
+
+    let results = services::youtube_service::check_channels(config).await;
+    println!("{}", services::youtube_service::format_channel_report(&results));
+
+    let failed = results
+        .iter()
+        .filter(|(_, status)| matches!(status, services::youtube_service::ChannelStatus::Error { .. }))
+        .count();
+    if failed > 0 {
+        return Err(AppError::youtube(format!("{} channel(s) could not be reached", failed)));
+    }
+    Ok(())
+}
+
+/// Probe every external service and fail if any of them is unhealthy
+async fn healthcheck(config: &Config) -> Result<()> {
+    info!("🩺 Checking external services");
+
+    let report = services::health_service::run_healthcheck(config).await?;
+    println!("{}", services::health_service::format_health_report(&report));
+
+    if !report.is_healthy() {
+        return Err(AppError::Internal(format!(
+            "{} service check(s) failed",
+            report.failures()
+        )));
+    }
+    Ok(())
+}
+
+/// List the latest summaries stored in the SQLite archive, newest first
+#[cfg(feature = "sqlite")]
+fn archive(config: &Config, last: usize) -> Result<()> {
+    let path = config
+        .db_path
+        .as_deref()
+        .ok_or_else(|| AppError::config("no archive configured; set DB_PATH"))?;
+    let conn = services::db_service::open(path)?;
+    let reports = services::db_service::recent_summaries(&conn, last)?;
+    info!("🗄️  {} archived summary(ies) in {}", reports.len(), path);
+
+    for report in &reports {
+        println!("{}  {}  {}", report.created_at, report.video_id, report.title);
+        println!("    {}", report.link);
+    }
+    Ok(())
+}
+
+/// Fetch a video's transcript and write it out as subtitles
+async fn export_subs(app: &App, url: &str, format: SubtitleFormat, output: Option<PathBuf>) -> Result<()> {
+    info!("🎞️  Exporting subtitles for {}", url);
+
+    let video_id = services::youtube_service::extract_video_id(url).await?;
+    let transcript = app.get_transcript(url).await?;
+
+    let (contents, extension) = match format {
+        SubtitleFormat::Srt => (transcript.to_srt(), "srt"),
+        SubtitleFormat::Vtt => (transcript.to_vtt(), "vtt"),
+    };
+    let path = output.unwrap_or_else(|| PathBuf::from(format!("{}.{}", video_id, extension)));
 
-    info!("📊 Latest KS Forward video processed");
+    tokio::fs::write(&path, contents).await?;
+    info!("💾 Wrote {} cues to {}", transcript.content.len(), path.display());
     Ok(())
 }
 
@@ -88,4 +348,175 @@ mod tests {
         assert_eq!(err.category(), "api");
         assert!(err.is_retryable());
     }
+
+    #[test]
+    fn test_boxed_app_errors_keep_their_kind() {
+        let boxed: Box<dyn std::error::Error> = AppError::ApiError {
+            url: "https://www.googleapis.com/youtube/v3/search".to_string(),
+            status: 503,
+        }
+        .into();
+        let err = AppError::from_boxed(boxed, "Failed to process KS Forward");
+        assert_eq!(err.category(), "api");
+        assert!(err.is_retryable());
+
+        let err = AppError::from_boxed("need at least two matching videos".into(), "Failed to compare videos");
+        assert_eq!(err.to_string(), "Internal error: Failed to compare videos: need at least two matching videos");
+        assert!(!err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_summarize_keeps_retryable_youtube_errors() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let config = Config::builder()
+            .youtube_api_base(server.uri())
+            .youtube_api_key("key")
+            .build();
+        let err = summarize(&App::new(config), "https://youtu.be/JB5FbXxSZ3o").await.unwrap_err();
+        assert!(err.is_retryable(), "{}", err);
+    }
+
+    #[test]
+    fn test_cli_parses_export_subs() {
+        let cli = Cli::parse_from([
+            "schRust",
+            "export-subs",
+            "https://youtu.be/JB5FbXxSZ3o",
+            "--format",
+            "vtt",
+        ]);
+        match cli.command {
+            Some(Command::ExportSubs { url, format, output }) => {
+                assert_eq!(url, "https://youtu.be/JB5FbXxSZ3o");
+                assert!(matches!(format, SubtitleFormat::Vtt));
+                assert!(output.is_none());
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        assert!(Cli::parse_from(["schRust"]).command.is_none());
+    }
+
+    #[test]
+    fn test_cli_parses_summarize_and_latest() {
+        match Cli::parse_from(["schRust", "summarize", "https://youtu.be/JB5FbXxSZ3o"]).command {
+            Some(Command::Summarize { url }) => assert_eq!(url, "https://youtu.be/JB5FbXxSZ3o"),
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(matches!(
+            Cli::parse_from(["schRust", "latest"]).command,
+            Some(Command::Run { since: None })
+        ));
+        assert!(Cli::try_parse_from(["schRust", "summarize"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_summarize_rejects_invalid_url() {
+        let err = summarize(&App::new(Config::default()), "not a link").await.unwrap_err();
+        assert_eq!(err.to_string(), "YouTube API error: 'not a link' is not a YouTube video URL");
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_cli_parses_backfill_since() {
+        let cli = Cli::parse_from(["schRust", "backfill", "--since", "2025-01-15"]);
+        match cli.command {
+            Some(Command::Backfill { since }) => {
+                assert_eq!(since.unwrap().to_rfc3339(), "2025-01-15T00:00:00+00:00");
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        let err = Cli::try_parse_from(["schRust", "backfill", "--since", "15/01/2025"]).unwrap_err();
+        assert!(err.to_string().contains("expected YYYY-MM-DD"));
+    }
+
+    #[test]
+    fn test_cli_parses_run_since() {
+        match Cli::parse_from(["schRust", "run", "--since", "2024-01-01T12:00:00Z"]).command {
+            Some(Command::Run { since }) => {
+                assert_eq!(since.unwrap().to_rfc3339(), "2024-01-01T12:00:00+00:00");
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfill_requires_a_start_date() {
+        let err = backfill(&App::new(Config::default())).await.unwrap_err();
+        assert_eq!(err.category(), "config");
+    }
+
+    #[test]
+    fn test_cli_parses_channels_check() {
+        let cli = Cli::parse_from(["schRust", "channels", "check"]);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Channels {
+                action: ChannelsCommand::Check
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cli_parses_playlist() {
+        match Cli::parse_from(["schRust", "playlist", "PL123"]).command {
+            Some(Command::Playlist { playlist_id }) => assert_eq!(playlist_id.as_deref(), Some("PL123")),
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(matches!(
+            Cli::parse_from(["schRust", "playlist"]).command,
+            Some(Command::Playlist { playlist_id: None })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_playlist_requires_an_id() {
+        let err = playlist(&App::new(Config::default()), None).await.unwrap_err();
+        assert_eq!(err.category(), "config");
+        assert!(err.to_string().contains("PLAYLIST_ID"));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_archive_lists_stored_summaries() {
+        let db_path = std::env::temp_dir().join(format!("ks-archive-{}.db", uuid::Uuid::new_v4()));
+        let config = Config {
+            db_path: Some(db_path.to_string_lossy().into_owned()),
+            ..Config::default()
+        };
+        let report = models::run_report::RunReport {
+            video_id: "JB5FbXxSZ3o".to_string(),
+            ..Default::default()
+        };
+        let conn = services::db_service::open(config.db_path.as_deref().unwrap()).unwrap();
+        services::db_service::store_summary(&conn, &report).unwrap();
+
+        match Cli::parse_from(["schRust", "archive", "--last", "3"]).command {
+            Some(Command::Archive { last }) => assert!(archive(&config, last).is_ok()),
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert_eq!(archive(&Config::default(), 3).unwrap_err().category(), "config");
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    #[test]
+    fn test_cli_parses_compare() {
+        let cli = Cli::parse_from(["schRust", "compare", "UC123", "--last", "3"]);
+        match cli.command {
+            Some(Command::Compare { channel_id, last }) => {
+                assert_eq!(channel_id, "UC123");
+                assert_eq!(last, 3);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
 }
@@ -1,25 +1,91 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Discord Webhook Payload
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct DiscordWebhook {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
     pub embeds: Option<Vec<DiscordEmbed>>,
+    /// Display name overriding the webhook's default for this message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Avatar image URL overriding the webhook's default for this message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
 }
 
 /// Discord Embed
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DiscordEmbed {
+    /// Empty for continuation embeds of a long message
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub title: String,
+    /// Link opened by clicking the title
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
     pub description: String,
     pub color: u32,
     pub timestamp: String,
     pub footer: Option<DiscordFooter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<DiscordImage>,
+    /// Small image shown in the top-right corner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<DiscordImage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<DiscordField>,
 }
 
 /// Discord Footer
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DiscordFooter {
     pub text: String,
 }
+
+/// Discord Embed Field (name/value pair shown under the description)
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscordField {
+    pub name: String,
+    pub value: String,
+    pub inline: bool,
+}
+
+/// Discord Embed Image, used for both the large `image` and the corner `thumbnail`
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscordImage {
+    pub url: String,
+}
+
+/// One webhook destination with its own branding
+///
+/// `DISCORD_KS_BOT_TOKEN` may hold a JSON array of these; a plain URL becomes a
+/// single target with default formatting.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DiscordTarget {
+    pub url: String,
+    /// Footer text (defaults to "KS Forward")
+    #[serde(default)]
+    pub footer: Option<String>,
+    /// Embed color (defaults to Discord Blurple)
+    #[serde(default)]
+    pub color: Option<u32>,
+    /// Send embeds (`true`, default) or plain-text content messages
+    #[serde(default = "default_embed")]
+    pub embed: bool,
+}
+
+fn default_embed() -> bool {
+    true
+}
+
+impl DiscordTarget {
+    /// Target with default formatting for a plain webhook URL
+    pub fn from_url(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            footer: None,
+            color: None,
+            embed: true,
+        }
+    }
+}
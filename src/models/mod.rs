@@ -1,5 +1,9 @@
+#[cfg(feature = "demo")]
 pub mod todo;
 pub mod youtube_snippet; 
 pub mod youtube_transcript;
+#[allow(non_snake_case)]
 pub mod myAI_response;
-pub mod discord;
\ No newline at end of file
+pub mod openai_response;
+pub mod discord;
+pub mod run_report;
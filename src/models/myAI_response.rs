@@ -1,7 +1,11 @@
+use crate::error::{AppError, Result};
 use serde_derive::Deserialize;
 use serde_derive::Serialize;
 use serde_json::Value;
 
+/// Answer locations tried after the configured `AI_ANSWER_PATH`
+const FALLBACK_ANSWER_PATHS: [&str; 3] = ["answer", "response", "data.answer"];
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Root {
@@ -13,8 +17,60 @@ pub struct Root {
     pub context_used: bool,
 }
 
+impl Root {
+    /// Parse an AI backend response, reading the answer text from `answer_path`
+    /// (dot-separated, e.g. `data.answer`) and then from the known alternates.
+    /// The other fields are optional and default when missing.
+    pub fn from_api_json(body: &str, answer_path: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(body).map_err(|e| AppError::JsonParse {
+            location: "AI response".to_string(),
+            message: e.to_string(),
+        })?;
+
+        let configured = answer_path.trim();
+        let mut paths: Vec<&str> = Some(configured).filter(|p| !p.is_empty()).into_iter().collect();
+        paths.extend(FALLBACK_ANSWER_PATHS.iter().filter(|p| **p != configured));
+        let Some(answer) = paths
+            .iter()
+            .find_map(|path| value_at_path(&value, path).and_then(Value::as_str))
+        else {
+            return Err(AppError::AIParse(format!(
+                "no answer text found at any of: {}",
+                paths.join(", ")
+            )));
+        };
+
+        Ok(Self {
+            answer: answer.to_string(),
+            events: value
+                .get("events")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default(),
+            session_id: value
+                .get("session_id")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            context_used: value
+                .get("context_used")
+                .and_then(Value::as_bool)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Follow a dot-separated path of object keys (or array indices) into `value`
+fn value_at_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |current, segment| match current {
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => current.get(segment),
+        })
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Event {
     pub step: i64,
     pub agent: String,
@@ -24,3 +80,170 @@ pub struct Event {
     pub target_agent: Value,
     pub thought: String,
 }
+
+impl Event {
+    /// One-line description for logs, e.g. `step 2 planner: search -> web_search (target: writer): ...`.
+    /// Null or empty `tool`/`target_agent` values are left out.
+    pub fn describe(&self) -> String {
+        let mut line = format!("step {} {}", self.step, self.agent);
+        if !self.action.is_empty() {
+            line.push_str(&format!(": {}", self.action));
+        }
+        if let Some(tool) = value_label(&self.tool) {
+            line.push_str(&format!(" -> {}", tool));
+        }
+        if let Some(target) = value_label(&self.target_agent) {
+            line.push_str(&format!(" (target: {})", target));
+        }
+        if !self.thought.trim().is_empty() {
+            line.push_str(&format!(" - {}", self.thought.trim()));
+        }
+        line
+    }
+}
+
+/// Readable form of a loosely typed field; `None` for null or empty values
+fn value_label(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) if s.trim().is_empty() => None,
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Array(items) if items.is_empty() => None,
+        Value::Object(map) if map.is_empty() => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// Short TL;DR plus the full summary, from one AI call
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SummaryPair {
+    pub tldr: String,
+    pub full: String,
+}
+
+/// Structured answer requested by `SummaryPair` prompts
+#[derive(Deserialize)]
+struct StructuredSummary {
+    tldr: String,
+    summary: String,
+}
+
+/// Longest TL;DR derived from a summary when the structured answer is missing
+const MAX_DERIVED_TLDR_CHARS: usize = 280;
+
+impl SummaryPair {
+    /// Parse an AI answer of the form `{ "tldr": ..., "summary": ... }` (optionally
+    /// inside a code fence). When that fails the whole answer is the full summary
+    /// and the TL;DR is its first sentence.
+    pub fn from_answer(answer: &str) -> Self {
+        let structured = answer
+            .find('{')
+            .zip(answer.rfind('}'))
+            .filter(|(start, end)| start < end)
+            .and_then(|(start, end)| serde_json::from_str::<StructuredSummary>(&answer[start..=end]).ok())
+            .filter(|parsed| !parsed.summary.trim().is_empty());
+
+        match structured {
+            Some(parsed) => {
+                let tldr = if parsed.tldr.trim().is_empty() {
+                    first_sentence(&parsed.summary)
+                } else {
+                    parsed.tldr.trim().to_string()
+                };
+                Self {
+                    tldr,
+                    full: parsed.summary.trim().to_string(),
+                }
+            }
+            None => Self {
+                tldr: first_sentence(answer),
+                full: answer.trim().to_string(),
+            },
+        }
+    }
+}
+
+/// First sentence (or line) of `text`, capped at `MAX_DERIVED_TLDR_CHARS`
+fn first_sentence(text: &str) -> String {
+    let text = text.trim();
+    let end = text
+        .char_indices()
+        .find(|(i, c)| matches!(c, '!' | '?' | '\n') || (*c == '.' && text[i + 1..].starts_with(char::is_whitespace)))
+        .map(|(i, c)| if c == '\n' { i } else { i + c.len_utf8() })
+        .unwrap_or(text.len());
+
+    text[..end].trim().chars().take(MAX_DERIVED_TLDR_CHARS).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer_read_from_each_known_location() {
+        let top = r#"{"answer": "top", "events": [], "session_id": "s1", "context_used": true}"#;
+        let root = Root::from_api_json(top, "answer").unwrap();
+        assert_eq!(root.answer, "top");
+        assert_eq!(root.session_id, "s1");
+        assert!(root.context_used);
+
+        let response = r#"{"response": "from response"}"#;
+        assert_eq!(Root::from_api_json(response, "answer").unwrap().answer, "from response");
+
+        let nested = r#"{"data": {"answer": "nested"}}"#;
+        assert_eq!(Root::from_api_json(nested, "answer").unwrap().answer, "nested");
+    }
+
+    #[test]
+    fn test_events_parse_and_describe_without_nulls() {
+        let body = r#"{"answer": "ok", "events": [
+            {"step": 1, "agent": "planner", "action": "delegate", "tool": null, "target_agent": "writer", "thought": "Split into sections"},
+            {"step": 2, "agent": "writer", "action": "", "tool": "", "thought": "  "}
+        ]}"#;
+        let root = Root::from_api_json(body, "answer").unwrap();
+        assert_eq!(root.events.len(), 2);
+
+        assert_eq!(
+            root.events[0].describe(),
+            "step 1 planner: delegate (target: writer) - Split into sections"
+        );
+        assert_eq!(root.events[1].describe(), "step 2 writer");
+        assert!(!root.events.iter().any(|e| e.describe().contains("null")));
+    }
+
+    #[test]
+    fn test_configured_answer_path_wins() {
+        let body = r#"{"answer": "fallback", "choices": [{"message": {"content": "configured"}}]}"#;
+        let root = Root::from_api_json(body, "choices.0.message.content").unwrap();
+        assert_eq!(root.answer, "configured");
+    }
+
+    #[test]
+    fn test_missing_answer_is_ai_parse_error() {
+        let err = Root::from_api_json(r#"{"result": "nope"}"#, "answer").unwrap_err();
+        assert!(matches!(err, AppError::AIParse(_)));
+        assert!(err.to_string().contains("answer, response, data.answer"));
+
+        let err = Root::from_api_json("not json", "answer").unwrap_err();
+        assert_eq!(err.category(), "parse");
+    }
+
+    #[test]
+    fn test_summary_pair_from_structured_answer() {
+        let answer = "```json\n{\"tldr\": \"Banks lead the SET.\", \"summary\": \"Full notes here.\"}\n```";
+        let pair = SummaryPair::from_answer(answer);
+        assert_eq!(pair.tldr, "Banks lead the SET.");
+        assert_eq!(pair.full, "Full notes here.");
+    }
+
+    #[test]
+    fn test_summary_pair_falls_back_to_first_sentence() {
+        let answer = "Banks lead the SET higher. Energy lags on oil prices.\nMore detail.";
+        let pair = SummaryPair::from_answer(answer);
+        assert_eq!(pair.tldr, "Banks lead the SET higher.");
+        assert_eq!(pair.full, answer);
+
+        let thai = "หุ้นธนาคารนำตลาด\nรายละเอียดเพิ่มเติม";
+        assert_eq!(SummaryPair::from_answer(thai).tldr, "หุ้นธนาคารนำตลาด");
+    }
+}
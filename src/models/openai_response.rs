@@ -0,0 +1,98 @@
+use crate::error::{AppError, Result};
+use crate::models::myAI_response::Root;
+use serde_derive::Deserialize;
+
+/// Response of an OpenAI-compatible `/chat/completions` call. Only the fields
+/// the summarizer reads are modelled; the rest are ignored.
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ChatCompletion {
+    pub id: String,
+    pub model: String,
+    pub choices: Vec<Choice>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Choice {
+    pub index: u32,
+    pub message: ChoiceMessage,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ChoiceMessage {
+    pub role: String,
+    pub content: Option<String>,
+}
+
+impl ChatCompletion {
+    /// Parse a `/chat/completions` response body
+    pub fn from_api_json(body: &str) -> Result<Self> {
+        serde_json::from_str(body).map_err(|e| AppError::JsonParse {
+            location: "OpenAI chat completion".to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Text of `choices[0].message.content`
+    pub fn answer(&self) -> Option<&str> {
+        self.choices.first().and_then(|choice| choice.message.content.as_deref())
+    }
+
+    /// The same `Root` the custom backend produces, with the first choice as
+    /// the answer. There are no events; the completion id stands in for the session.
+    pub fn into_root(self) -> Result<Root> {
+        let answer = self
+            .answer()
+            .ok_or_else(|| AppError::AIParse("no choices[0].message.content in completion".to_string()))?
+            .to_string();
+        Ok(Root {
+            answer,
+            session_id: self.id,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chat_completion() {
+        let body = r#"{
+            "id": "chatcmpl-abc123",
+            "object": "chat.completion",
+            "created": 1736899200,
+            "model": "gpt-4o-mini",
+            "choices": [
+                {
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "สรุป: ตลาดปรับตัวขึ้น" },
+                    "logprobs": null,
+                    "finish_reason": "stop"
+                }
+            ],
+            "usage": { "prompt_tokens": 120, "completion_tokens": 30, "total_tokens": 150 }
+        }"#;
+
+        let completion = ChatCompletion::from_api_json(body).unwrap();
+        assert_eq!(completion.model, "gpt-4o-mini");
+        assert_eq!(completion.choices[0].finish_reason.as_deref(), Some("stop"));
+        assert_eq!(completion.answer(), Some("สรุป: ตลาดปรับตัวขึ้น"));
+
+        let root = completion.into_root().unwrap();
+        assert_eq!(root.answer, "สรุป: ตลาดปรับตัวขึ้น");
+        assert_eq!(root.session_id, "chatcmpl-abc123");
+        assert!(root.events.is_empty());
+    }
+
+    #[test]
+    fn test_completion_without_content_is_an_error() {
+        let completion = ChatCompletion::from_api_json(r#"{"choices": []}"#).unwrap();
+        assert!(completion.answer().is_none());
+        assert!(matches!(completion.into_root(), Err(AppError::AIParse(_))));
+    }
+}
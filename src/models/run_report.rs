@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// Record of one processed video and the summary that was produced for it
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub video_id: String,
+    pub title: String,
+    pub link: String,
+    pub publish_time: String,
+    /// Final summary text (all languages, in delivery order)
+    pub summary: String,
+    /// Length of the parsed transcript in characters
+    pub transcript_chars: usize,
+    /// Estimated AI input tokens for the transcript (see `myAI_service::estimate_tokens`)
+    #[serde(default)]
+    pub estimated_tokens: usize,
+    /// When the report was created (RFC3339)
+    pub created_at: String,
+}
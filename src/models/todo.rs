@@ -1,6 +1,7 @@
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
+#[allow(dead_code)]
 pub struct Todo {
     #[serde(rename = "userId")]
     pub user_id: u32,
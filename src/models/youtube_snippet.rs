@@ -7,6 +7,45 @@ pub struct SearchResult {
     pub link: String,
     pub title: String,
     pub publish_time: String,
+    /// The search item's snippet, for the description, thumbnails and channel
+    pub snippet: Snippet,
+}
+
+impl SearchResult {
+    /// Map a search item to a `SearchResult`; items without a video id (e.g.
+    /// channel or playlist results) give `None`
+    pub fn from_item(item: &Item) -> Option<Self> {
+        let video_id = item.id.as_video_id()?;
+        Some(Self {
+            link: format!("https://www.youtube.com/watch?v={}", video_id),
+            video_id,
+            title: item.snippet.title.clone().unwrap_or_default(),
+            publish_time: item.snippet.publish_time.clone().unwrap_or_default(),
+            snippet: item.snippet.clone(),
+        })
+    }
+}
+
+impl Item {
+    /// Turn a `playlistItems` entry into a video item: the id becomes the
+    /// video's id and `publish_time` its upload time. Private and deleted
+    /// videos, which have no uploader, give `None`.
+    pub fn from_playlist_item(mut item: Item) -> Option<Item> {
+        item.snippet.video_owner_channel_title.as_ref()?;
+        let video_id = item
+            .snippet
+            .resource_id
+            .as_ref()
+            .and_then(|resource| resource.video_id.clone())
+            .filter(|id| !id.is_empty())?;
+        item.id = Id::StringId(video_id);
+        item.snippet.publish_time = item
+            .content_details
+            .as_ref()
+            .and_then(|details| details.video_published_at.clone())
+            .or_else(|| item.snippet.published_at.clone());
+        Some(item)
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -20,6 +59,20 @@ pub struct Root {
     pub items: Vec<Item>,
 }
 
+/// Response of the `channels` endpoint; `items` is omitted when nothing matched
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelList {
+    #[serde(default)]
+    pub items: Vec<ChannelItem>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelItem {
+    pub id: String,
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PageInfo {
@@ -34,6 +87,101 @@ pub struct Item {
     pub etag: String,
     pub id: Id,
     pub snippet: Snippet,
+    /// Present when the request asked for `part=contentDetails`
+    #[serde(default)]
+    pub content_details: Option<ContentDetails>,
+    /// Present when the request asked for `part=statistics`
+    #[serde(default)]
+    pub statistics: Option<Statistics>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Statistics {
+    /// The API sends counts as decimal strings
+    pub view_count: Option<String>,
+    pub like_count: Option<String>,
+    pub comment_count: Option<String>,
+}
+
+impl Statistics {
+    pub fn views(&self) -> Option<u64> {
+        self.view_count.as_deref().and_then(|v| v.trim().parse().ok())
+    }
+
+    /// Missing when the owner hides likes
+    pub fn likes(&self) -> Option<u64> {
+        self.like_count.as_deref().and_then(|v| v.trim().parse().ok())
+    }
+
+    /// Footer label such as "👁 12,345 • 👍 678", or `None` without any counts
+    pub fn footer_label(&self) -> Option<String> {
+        let parts: Vec<String> = [("👁", self.views()), ("👍", self.likes())]
+            .into_iter()
+            .filter_map(|(icon, count)| count.map(|c| format!("{} {}", icon, format_count(c))))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" • "))
+    }
+}
+
+/// Format a count with comma thousands separators, e.g. 12345 → "12,345"
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentDetails {
+    /// ISO 8601 duration, e.g. `PT1H2M3S`
+    pub duration: Option<String>,
+    /// Upload time of a playlist entry's video (`snippet.publishedAt` is when it was added)
+    #[serde(default)]
+    pub video_published_at: Option<String>,
+}
+
+impl ContentDetails {
+    /// Video length in seconds, when the duration is present and well-formed
+    pub fn duration_secs(&self) -> Option<u64> {
+        self.duration.as_deref().and_then(parse_iso8601_duration)
+    }
+}
+
+/// Parse the `P[nD]T[nH][nM][nS]` durations used by the YouTube Data API
+pub fn parse_iso8601_duration(raw: &str) -> Option<u64> {
+    let rest = raw.trim().strip_prefix('P')?;
+    let mut total = 0u64;
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => in_time = true,
+            'D' | 'H' | 'M' | 'S' => {
+                let value: u64 = number.parse().ok()?;
+                number.clear();
+                total += value
+                    * match (c, in_time) {
+                        ('D', false) => 86_400,
+                        ('H', true) => 3_600,
+                        ('M', true) => 60,
+                        ('S', true) => 1,
+                        _ => return None,
+                    };
+            }
+            _ => return None,
+        }
+    }
+
+    number.is_empty().then_some(total)
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -81,6 +229,29 @@ pub struct Snippet {
     pub channel_title: Option<String>,
     pub live_broadcast_content: Option<String>,
     pub publish_time: Option<String>,
+    /// The video a `playlistItems` entry points to
+    #[serde(default)]
+    pub resource_id: Option<ResourceId>,
+    /// Uploader of a playlist entry's video; absent for private and deleted videos
+    #[serde(default)]
+    pub video_owner_channel_title: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceId {
+    pub kind: Option<String>,
+    pub video_id: Option<String>,
+}
+
+impl Snippet {
+    /// Publish time parsed from `publishTime` (or `publishedAt` as a fallback)
+    pub fn published(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        self.publish_time
+            .as_deref()
+            .or(self.published_at.as_deref())
+            .and_then(|raw| chrono::DateTime::parse_from_rfc3339(raw.trim()).ok())
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -89,6 +260,23 @@ pub struct Thumbnails {
     pub default: Option<ThumbnailDefault>,
     pub medium: Option<Medium>,
     pub high: Option<High>,
+    pub standard: Option<Standard>,
+    pub maxres: Option<Maxres>,
+}
+
+impl Thumbnails {
+    /// Return the highest-resolution thumbnail URL available.
+    ///
+    /// Resolution order: maxres → standard → high → medium → default.
+    pub fn best_url(&self) -> Option<String> {
+        self.maxres
+            .as_ref()
+            .and_then(|t| t.url.clone())
+            .or_else(|| self.standard.as_ref().and_then(|t| t.url.clone()))
+            .or_else(|| self.high.as_ref().and_then(|t| t.url.clone()))
+            .or_else(|| self.medium.as_ref().and_then(|t| t.url.clone()))
+            .or_else(|| self.default.as_ref().and_then(|t| t.url.clone()))
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -114,3 +302,147 @@ pub struct High {
     pub width: Option<i64>,
     pub height: Option<i64>,
 }
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Standard {
+    pub url: Option<String>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Maxres {
+    pub url: Option<String>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THUMBNAILS_JSON: &str = r#"{
+        "default": { "url": "https://i.ytimg.com/vi/abc/default.jpg", "width": 120, "height": 90 },
+        "medium": { "url": "https://i.ytimg.com/vi/abc/mqdefault.jpg", "width": 320, "height": 180 },
+        "high": { "url": "https://i.ytimg.com/vi/abc/hqdefault.jpg", "width": 480, "height": 360 },
+        "standard": { "url": "https://i.ytimg.com/vi/abc/sddefault.jpg", "width": 640, "height": 480 },
+        "maxres": { "url": "https://i.ytimg.com/vi/abc/maxresdefault.jpg", "width": 1280, "height": 720 }
+    }"#;
+
+    #[test]
+    fn test_deserialize_maxres_thumbnail() {
+        let thumbnails: Thumbnails = serde_json::from_str(THUMBNAILS_JSON).unwrap();
+        let maxres = thumbnails.maxres.unwrap();
+        assert_eq!(maxres.width, Some(1280));
+        assert_eq!(maxres.height, Some(720));
+        assert_eq!(
+            thumbnails.standard.unwrap().url.as_deref(),
+            Some("https://i.ytimg.com/vi/abc/sddefault.jpg")
+        );
+    }
+
+    #[test]
+    fn test_best_url_prefers_maxres() {
+        let thumbnails: Thumbnails = serde_json::from_str(THUMBNAILS_JSON).unwrap();
+        assert_eq!(
+            thumbnails.best_url().as_deref(),
+            Some("https://i.ytimg.com/vi/abc/maxresdefault.jpg")
+        );
+    }
+
+    #[test]
+    fn test_best_url_falls_back_without_maxres() {
+        let mut thumbnails: Thumbnails = serde_json::from_str(THUMBNAILS_JSON).unwrap();
+        thumbnails.maxres = None;
+        thumbnails.standard = None;
+        assert_eq!(
+            thumbnails.best_url().as_deref(),
+            Some("https://i.ytimg.com/vi/abc/hqdefault.jpg")
+        );
+        assert_eq!(Thumbnails::default().best_url(), None);
+    }
+
+    #[test]
+    fn test_published_prefers_publish_time() {
+        let snippet = Snippet {
+            published_at: Some("2025-01-01T00:00:00Z".to_string()),
+            publish_time: Some("2025-01-15T01:00:00Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(snippet.published().unwrap().to_rfc3339(), "2025-01-15T01:00:00+00:00");
+
+        let fallback = Snippet {
+            published_at: Some("2025-01-01T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        assert!(fallback.published().is_some());
+        assert!(Snippet::default().published().is_none());
+    }
+
+    #[test]
+    fn test_playlist_items_skip_private_and_deleted() {
+        let root: Root = serde_json::from_str(include_str!("../mock_data/example_playlist_items.json")).unwrap();
+        let videos: Vec<Item> = root.items.into_iter().filter_map(Item::from_playlist_item).collect();
+
+        assert_eq!(videos.len(), 1);
+        let mapped = SearchResult::from_item(&videos[0]).unwrap();
+        assert_eq!(mapped.video_id, "JB5FbXxSZ3o");
+        assert_eq!(mapped.link, "https://www.youtube.com/watch?v=JB5FbXxSZ3o");
+        assert_eq!(mapped.title, "KS Forward 15 Jan 2025");
+        // The upload time, not when the video was added to the playlist
+        assert_eq!(mapped.publish_time, "2025-01-15T01:00:00Z");
+    }
+
+    #[test]
+    fn test_parse_iso8601_duration() {
+        assert_eq!(parse_iso8601_duration("PT1H2M3S"), Some(3723));
+        assert_eq!(parse_iso8601_duration("PT45M"), Some(2700));
+        assert_eq!(parse_iso8601_duration("P1DT1S"), Some(86_401));
+        assert_eq!(parse_iso8601_duration("PT"), Some(0));
+        assert_eq!(parse_iso8601_duration("1H"), None);
+        assert_eq!(parse_iso8601_duration("PT5X"), None);
+    }
+
+    #[test]
+    fn test_statistics_deserialize_from_string_counts() {
+        let item: Item = serde_json::from_str(
+            r#"{
+                "kind": "youtube#video",
+                "etag": "e",
+                "id": "abc",
+                "snippet": {},
+                "statistics": { "viewCount": "12345", "likeCount": "678", "favoriteCount": "0" }
+            }"#,
+        )
+        .unwrap();
+
+        let stats = item.statistics.unwrap();
+        assert_eq!(stats.views(), Some(12_345));
+        assert_eq!(stats.likes(), Some(678));
+        assert!(stats.comment_count.is_none());
+    }
+
+    #[test]
+    fn test_statistics_footer_label() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1_234_567), "1,234,567");
+
+        let stats = Statistics {
+            view_count: Some("12345".to_string()),
+            like_count: Some("678".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(stats.footer_label().as_deref(), Some("👁 12,345 • 👍 678"));
+
+        // Likes hidden by the channel owner
+        let hidden = Statistics {
+            like_count: None,
+            ..stats
+        };
+        assert_eq!(hidden.footer_label().as_deref(), Some("👁 12,345"));
+        assert!(Statistics::default().footer_label().is_none());
+    }
+}
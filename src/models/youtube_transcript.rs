@@ -1,3 +1,4 @@
+use crate::error::{AppError, Result};
 use serde_derive::Deserialize;
 use serde_derive::Serialize;
 
@@ -11,6 +12,228 @@ pub struct Root {
     pub content: Vec<Content>,  // API returns content as array of objects
 }
 
+/// Top-level keys that older/alternate transcript API responses use instead of `content`
+const ALTERNATE_CONTENT_KEYS: [&str; 2] = ["transcript", "segments"];
+
+impl Root {
+    /// Parse a transcript API response, accepting known alternate shapes.
+    ///
+    /// The current shape has a `content` array. Older responses put the segments
+    /// under `transcript` or `segments` (with `start`/`dur` timing keys), or return
+    /// `transcript` as one plain string. Anything else is an `InvalidResponse`
+    /// naming the keys that were found.
+    pub fn from_api_json(body: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(body)?;
+        let Some(object) = value.as_object() else {
+            return Err(AppError::InvalidResponse(
+                "transcript response is not a JSON object".to_string(),
+            ));
+        };
+
+        if object.contains_key("content") {
+            // Parse the text again rather than `value` so a schema mismatch keeps its line/column
+            return Ok(serde_json::from_str(body)?);
+        }
+
+        let Some((key, segments)) = ALTERNATE_CONTENT_KEYS
+            .iter()
+            .find_map(|key| object.get(*key).map(|v| (*key, v)))
+        else {
+            let mut keys: Vec<&str> = object.keys().map(String::as_str).collect();
+            keys.sort_unstable();
+            return Err(AppError::InvalidResponse(format!(
+                "transcript response has no 'content' field; found keys: [{}]",
+                keys.join(", ")
+            )));
+        };
+
+        let lang = object.get("lang").and_then(|v| v.as_str()).map(str::to_string);
+        let available_langs = object
+            .get("availableLangs")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+
+        let content = match segments {
+            serde_json::Value::String(text) => vec![Content {
+                lang: lang.clone().unwrap_or_default(),
+                text: text.clone(),
+                offset: 0.0,
+                duration: 0.0,
+            }],
+            other => serde_json::from_value::<Vec<CompatSegment>>(other.clone())
+                .map_err(|e| AppError::JsonParse {
+                    location: format!("transcript '{}' segments", key),
+                    message: e.to_string(),
+                })?
+                .into_iter()
+                .map(|segment| Content {
+                    lang: segment.lang,
+                    text: segment.text,
+                    offset: segment.offset,
+                    duration: segment.duration,
+                })
+                .collect(),
+        };
+
+        tracing::warn!("Transcript response used legacy '{}' shape; mapped to content", key);
+        Ok(Self {
+            lang,
+            available_langs,
+            content,
+        })
+    }
+
+    /// Check segment timing integrity.
+    ///
+    /// Offsets must be non-negative and monotonically non-decreasing, and every
+    /// segment must have a positive duration.
+    pub fn validate(&self) -> Result<()> {
+        let mut previous_offset = 0.0;
+
+        for (index, segment) in self.content.iter().enumerate() {
+            if segment.offset.is_nan() || segment.offset < 0.0 {
+                return Err(AppError::InvalidResponse(format!(
+                    "transcript segment {} has negative offset {}",
+                    index, segment.offset
+                )));
+            }
+            if segment.offset < previous_offset {
+                return Err(AppError::InvalidResponse(format!(
+                    "transcript segment {} offset {} is before previous offset {}",
+                    index, segment.offset, previous_offset
+                )));
+            }
+            if segment.duration.is_nan() || segment.duration <= 0.0 {
+                return Err(AppError::InvalidResponse(format!(
+                    "transcript segment {} has non-positive duration {}",
+                    index, segment.duration
+                )));
+            }
+            previous_offset = segment.offset;
+        }
+
+        Ok(())
+    }
+
+    /// Compute simple quality statistics over the transcript segments.
+    pub fn stats(&self) -> TranscriptStats {
+        let segments = self.content.len();
+        let words = self
+            .content
+            .iter()
+            .filter(|c| !is_non_speech_marker(&c.text))
+            .map(|c| c.text.split_whitespace().count())
+            .sum();
+        let non_speech_markers = self
+            .content
+            .iter()
+            .filter(|c| is_non_speech_marker(&c.text))
+            .count();
+
+        let duration_ms = match (self.content.first(), self.content.last()) {
+            (Some(first), Some(last)) => (last.offset + last.duration - first.offset).max(0.0),
+            _ => 0.0,
+        };
+        let words_per_minute = if duration_ms > 0.0 {
+            Some(words as f64 / (duration_ms / 60_000.0))
+        } else {
+            None
+        };
+        let marker_ratio = if segments > 0 {
+            non_speech_markers as f64 / segments as f64
+        } else {
+            0.0
+        };
+
+        TranscriptStats {
+            segments,
+            words,
+            duration_ms,
+            words_per_minute,
+            non_speech_markers,
+            marker_ratio,
+        }
+    }
+
+    /// Share of a video of `video_secs` seconds covered by the transcript, based
+    /// on where the last segment ends. `None` when the video length is unknown.
+    pub fn coverage(&self, video_secs: f64) -> Option<f64> {
+        if video_secs.is_nan() || video_secs <= 0.0 {
+            return None;
+        }
+        let end_ms = self
+            .content
+            .iter()
+            .map(|segment| segment.offset + segment.duration)
+            .fold(0.0, f64::max);
+        Some((end_ms / 1000.0 / video_secs).min(1.0))
+    }
+
+    /// Render the transcript as SubRip (`.srt`) subtitles.
+    ///
+    /// Offsets and durations are in milliseconds, as returned by the transcript API.
+    pub fn to_srt(&self) -> String {
+        let mut out = String::new();
+        for (index, segment) in self.content.iter().enumerate() {
+            out.push_str(&format!(
+                "{}\n{} --> {}\n{}\n\n",
+                index + 1,
+                format_cue_time(segment.offset, ','),
+                format_cue_time(segment.offset + segment.duration, ','),
+                segment.text.trim()
+            ));
+        }
+        out
+    }
+
+    /// Render the transcript as WebVTT (`.vtt`) subtitles.
+    pub fn to_vtt(&self) -> String {
+        let mut out = String::from("WEBVTT\n\n");
+        for segment in &self.content {
+            out.push_str(&format!(
+                "{} --> {}\n{}\n\n",
+                format_cue_time(segment.offset, '.'),
+                format_cue_time(segment.offset + segment.duration, '.'),
+                segment.text.trim()
+            ));
+        }
+        out
+    }
+}
+
+/// Quality statistics for a transcript (see `Root::stats`)
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct TranscriptStats {
+    /// Number of caption segments
+    pub segments: usize,
+    /// Whitespace-separated words in speech segments
+    pub words: usize,
+    /// Span from the first offset to the end of the last segment
+    pub duration_ms: f64,
+    /// Speech words per minute; `None` when the duration is zero
+    pub words_per_minute: Option<f64>,
+    /// Segments that are only a bracketed marker such as `[Music]` or `[เพลง]`
+    pub non_speech_markers: usize,
+    /// `non_speech_markers / segments`
+    pub marker_ratio: f64,
+}
+
+/// Whether a caption is only a non-speech marker like `[Music]` or `[Applause]`
+fn is_non_speech_marker(text: &str) -> bool {
+    let text = text.trim();
+    text.len() > 2 && text.starts_with('[') && text.ends_with(']')
+}
+
+/// Format milliseconds as `hh:mm:ss<sep>mmm`
+fn format_cue_time(millis: f64, separator: char) -> String {
+    let total_ms = millis.max(0.0).round() as u64;
+    let hours = total_ms / 3_600_000;
+    let minutes = (total_ms % 3_600_000) / 60_000;
+    let seconds = (total_ms % 60_000) / 1000;
+    let ms = total_ms % 1000;
+    format!("{:02}:{:02}:{:02}{}{:03}", hours, minutes, seconds, separator, ms)
+}
+
 // Content struct is no longer needed with the new API format
 // Kept for backward compatibility if needed elsewhere
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -23,8 +246,155 @@ pub struct Content {
     pub duration: f64,
 }
 
+/// Segment in an alternate transcript response shape
+#[derive(Deserialize)]
+struct CompatSegment {
+    #[serde(default)]
+    lang: String,
+    text: String,
+    #[serde(default, alias = "start")]
+    offset: f64,
+    #[serde(default, alias = "dur")]
+    duration: f64,
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
 pub struct Transcript {
     pub full_transcript:  String
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, offset: f64, duration: f64) -> Content {
+        Content {
+            lang: "th".to_string(),
+            text: text.to_string(),
+            offset,
+            duration,
+        }
+    }
+
+    fn transcript(content: Vec<Content>) -> Root {
+        Root {
+            lang: Some("th".to_string()),
+            available_langs: vec!["th".to_string()],
+            content,
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_ordered_transcript() {
+        let root = transcript(vec![
+            segment("a", 0.0, 1000.0),
+            segment("b", 1000.0, 1500.0),
+            segment("c", 1000.0, 500.0),
+        ]);
+        assert!(root.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_out_of_order_offsets() {
+        let root = transcript(vec![
+            segment("a", 0.0, 1000.0),
+            segment("b", 5000.0, 1000.0),
+            segment("c", 2000.0, 1000.0),
+        ]);
+        let err = root.validate().unwrap_err();
+        assert!(matches!(err, AppError::InvalidResponse(_)));
+        assert!(err.to_string().contains("segment 2"));
+    }
+
+    #[test]
+    fn test_stats_counts_markers_and_rate() {
+        let root = transcript(vec![
+            segment("[Music]", 0.0, 30_000.0),
+            segment("hello there everyone", 30_000.0, 15_000.0),
+            segment("[Applause]", 45_000.0, 15_000.0),
+        ]);
+        let stats = root.stats();
+
+        assert_eq!(stats.segments, 3);
+        assert_eq!(stats.words, 3);
+        assert_eq!(stats.non_speech_markers, 2);
+        assert!((stats.marker_ratio - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.duration_ms, 60_000.0);
+        assert_eq!(stats.words_per_minute, Some(3.0));
+        assert_eq!(Root::default().stats().words_per_minute, None);
+    }
+
+    #[test]
+    fn test_format_cue_time() {
+        assert_eq!(format_cue_time(0.0, ','), "00:00:00,000");
+        assert_eq!(format_cue_time(18340.0, ','), "00:00:18,340");
+        assert_eq!(format_cue_time(3_723_004.0, '.'), "01:02:03.004");
+    }
+
+    #[test]
+    fn test_to_srt_numbers_cues() {
+        let root = transcript(vec![
+            segment("[เพลง]", 18340.0, 2020.0),
+            segment(" สวัสดีครับ ", 28599.0, 4921.0),
+        ]);
+        assert_eq!(
+            root.to_srt(),
+            "1\n00:00:18,340 --> 00:00:20,360\n[เพลง]\n\n\
+             2\n00:00:28,599 --> 00:00:33,520\nสวัสดีครับ\n\n"
+        );
+    }
+
+    #[test]
+    fn test_to_vtt_has_header_and_dot_millis() {
+        let root = transcript(vec![segment("hello", 61_500.0, 1_000.0)]);
+        assert_eq!(
+            root.to_vtt(),
+            "WEBVTT\n\n00:01:01.500 --> 00:01:02.500\nhello\n\n"
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_negative_offset_and_zero_duration() {
+        let negative = transcript(vec![segment("a", -1.0, 1000.0)]);
+        assert!(matches!(
+            negative.validate(),
+            Err(AppError::InvalidResponse(_))
+        ));
+
+        let zero_duration = transcript(vec![segment("a", 0.0, 0.0)]);
+        assert!(matches!(
+            zero_duration.validate(),
+            Err(AppError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_api_json_current_shape() {
+        let body = r#"{"lang":"th","availableLangs":["th"],"content":[{"lang":"th","text":"hi","offset":0,"duration":1000}]}"#;
+        let root = Root::from_api_json(body).unwrap();
+        assert_eq!(root.content.len(), 1);
+        assert_eq!(root.content[0].text, "hi");
+        assert_eq!(root.lang.as_deref(), Some("th"));
+    }
+
+    #[test]
+    fn test_from_api_json_alternate_shape() {
+        let body = r#"{"lang":"en","segments":[{"text":"a","start":0,"dur":500},{"text":"b","start":500,"dur":700}]}"#;
+        let root = Root::from_api_json(body).unwrap();
+        assert_eq!(root.content.len(), 2);
+        assert_eq!(root.content[1].offset, 500.0);
+        assert_eq!(root.content[1].duration, 700.0);
+
+        let root = Root::from_api_json(r#"{"transcript":"full text"}"#).unwrap();
+        assert_eq!(root.content[0].text, "full text");
+    }
+
+    #[test]
+    fn test_from_api_json_unexpected_shape() {
+        let err = Root::from_api_json(r#"{"data":[],"status":"ok"}"#).unwrap_err();
+        assert!(matches!(err, AppError::InvalidResponse(_)));
+        assert!(err.to_string().contains("data, status"));
+    }
+}
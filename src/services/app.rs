@@ -0,0 +1,140 @@
+//! Shared state for one run: the loaded configuration and the HTTP client
+//!
+//! `main` builds a single `App` and hands it to each command. The summarizing
+//! pipeline searches, fetches transcripts, calls the AI and posts to Discord
+//! through its methods, so every call uses the run's client and configuration;
+//! a video with its own persona gets an `App` of its own via `with_config`.
+use crate::config::Config;
+use crate::error::Result;
+use crate::models::myAI_response::Root as AiResponse;
+use crate::models::youtube_snippet::SearchResult;
+use crate::models::youtube_transcript::Root as Transcript;
+use crate::services::discord_service::{self, EmbedMetadata, MessageSection};
+use crate::services::{http_client, myAI_service, supabase_service, youtube_service};
+use reqwest::Client;
+
+pub struct App {
+    config: Config,
+    client: Client,
+}
+
+impl App {
+    /// App on the process-wide pooled client
+    pub fn new(config: Config) -> Self {
+        // Cloning a `Client` shares its connection pool
+        Self::with_client(config, http_client::client().clone())
+    }
+
+    pub fn with_client(config: Config, client: Client) -> Self {
+        Self { config, client }
+    }
+
+    /// This app with `config` in place of its own, on the same client
+    pub fn with_config(&self, config: Config) -> Self {
+        Self::with_client(config, self.client.clone())
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Latest completed videos of a channel, dropping results without a video id.
+    /// `max_results` is clamped to the API maximum of 50; 0 means the default of 5.
+    pub async fn youtube_search(&self, channel_id: &str, max_results: u8) -> Result<Vec<SearchResult>> {
+        let root = youtube_service::search_latest(&self.config, &self.client, channel_id, max_results).await?;
+        Ok(youtube_service::map_search_results(&root))
+    }
+
+    /// Transcript of a video link, in `PREFERRED_TRANSCRIPT_LANG` when one is
+    /// configured, from the cache when available
+    pub async fn get_transcript(&self, url: &str) -> Result<Transcript> {
+        match self.config.preferred_transcript_lang.as_deref() {
+            Some(lang) => supabase_service::get_youtube_transcript_lang(&self.config, &self.client, url, lang).await,
+            None => supabase_service::get_youtube_transcript(&self.config, &self.client, url).await,
+        }
+    }
+
+    /// Send `content` to the AI backend with the configured persona
+    pub async fn chat(&self, content: String) -> std::result::Result<AiResponse, Box<dyn std::error::Error>> {
+        myAI_service::chat_with_client(&self.config, &self.client, content).await
+    }
+
+    /// Send `content` to the AI backend with the `ks-discord` persona, which
+    /// shortens and formats an answer for Discord
+    pub async fn chat_for_discord(&self, content: String) -> std::result::Result<AiResponse, Box<dyn std::error::Error>> {
+        myAI_service::chat_with_ai_msg4Discord(&self.config, &self.client, content).await
+    }
+
+    /// Post a message to every configured Discord target. Webhook posts keep
+    /// their own client, which has no overall request timeout.
+    pub async fn send_discord(
+        &self,
+        title: &str,
+        message: &str,
+        metadata: &EmbedMetadata,
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        discord_service::send_message(&self.config, title, message, metadata).await
+    }
+
+    /// `send_discord` for a message made of headed sections
+    pub async fn send_discord_sections(
+        &self,
+        title: &str,
+        sections: &[MessageSection],
+        metadata: &EmbedMetadata,
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        discord_service::send_sections(&self.config, title, sections, metadata).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_app_uses_its_config_for_each_service() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("channelId", "UC123"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_search.json")))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/transcript"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_transcript.json")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"answer":"สรุป","session_id":"s1"}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let cache_dir = std::env::temp_dir().join(format!("ks-app-{}", uuid::Uuid::new_v4()));
+        let config = Config::builder()
+            .youtube_api_key("yt-key")
+            .youtube_api_base(server.uri())
+            .supabase_api_key("sd-key")
+            .supadata_base_url(server.uri())
+            .my_ai_api_url(format!("{}/chat", server.uri()))
+            .cache_dir(cache_dir.to_string_lossy().to_string())
+            .build();
+        let app = App::with_client(config, Client::new());
+
+        let videos = app.youtube_search("UC123", 1).await.unwrap();
+        assert!(!videos.is_empty());
+        let transcript = app.get_transcript("https://youtu.be/dQw4w9WgXcQ").await.unwrap();
+        assert!(!transcript.content.is_empty());
+        assert_eq!(app.chat("summarize this".to_string()).await.unwrap().answer, "สรุป");
+
+        let _ = tokio::fs::remove_dir_all(&cache_dir).await;
+    }
+}
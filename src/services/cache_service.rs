@@ -0,0 +1,112 @@
+//! On-disk transcript cache keyed by video id
+//!
+//! Each transcript is stored as `<CACHE_DIR>/<video_id>.json`. With
+//! `CACHE_TTL_SECS` set, entries older than the TTL count as misses so the
+//! transcript is fetched again.
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::models::youtube_transcript::Root;
+use crate::services::supabase_service::check_transcript_size;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::fs;
+use tracing::info;
+
+fn cache_path(config: &Config, video_id: &str) -> PathBuf {
+    Path::new(&config.cache_dir).join(format!("{}.json", video_id))
+}
+
+fn cache_error(path: &Path, e: impl std::fmt::Display) -> AppError {
+    AppError::cache(format!("transcript cache '{}': {}", path.display(), e))
+}
+
+/// Whether an entry of the given age has outlived `ttl`; an unknown age is kept
+fn is_stale(age: Option<Duration>, ttl: Option<Duration>) -> bool {
+    matches!((age, ttl), (Some(age), Some(ttl)) if age > ttl)
+}
+
+/// Load a cached transcript; `None` on a miss or a stale entry
+pub async fn get_cached_transcript(config: &Config, video_id: &str) -> Result<Option<Root>> {
+    let path = cache_path(config, video_id);
+    let metadata = match fs::metadata(&path).await {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(cache_error(&path, e)),
+    };
+
+    let age = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+    if is_stale(age, config.cache_ttl_secs.map(Duration::from_secs)) {
+        info!("Cached transcript is stale: {}", path.display());
+        return Ok(None);
+    }
+
+    check_transcript_size(config, metadata.len() as usize)?;
+    let raw = fs::read_to_string(&path).await.map_err(|e| cache_error(&path, e))?;
+    info!("Loading transcript from cache: {}", path.display());
+    Root::from_api_json(&raw)
+        .map(Some)
+        .map_err(|e| cache_error(&path, e))
+}
+
+/// Store a transcript, creating the cache directory when needed
+pub async fn put_cached_transcript(config: &Config, video_id: &str, transcript: &Root) -> Result<()> {
+    let path = cache_path(config, video_id);
+    fs::create_dir_all(&config.cache_dir)
+        .await
+        .map_err(|e| cache_error(&path, e))?;
+    let raw = serde_json::to_string(transcript).map_err(|e| cache_error(&path, e))?;
+    fs::write(&path, raw).await.map_err(|e| cache_error(&path, e))?;
+    info!("Transcript saved to cache: {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::youtube_transcript::Content;
+
+    fn test_config() -> Config {
+        Config {
+            cache_dir: std::env::temp_dir()
+                .join(format!("ks-cache-{}", uuid::Uuid::new_v4()))
+                .to_string_lossy()
+                .into_owned(),
+            ..Config::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_round_trip() {
+        let config = test_config();
+        assert_eq!(get_cached_transcript(&config, "abc123").await.unwrap(), None);
+
+        let transcript = Root {
+            lang: Some("th".to_string()),
+            content: vec![Content {
+                text: "สวัสดีครับ".to_string(),
+                ..Content::default()
+            }],
+            ..Root::default()
+        };
+        put_cached_transcript(&config, "abc123", &transcript).await.unwrap();
+        assert_eq!(
+            get_cached_transcript(&config, "abc123").await.unwrap(),
+            Some(transcript)
+        );
+
+        fs::remove_dir_all(&config.cache_dir).await.unwrap();
+    }
+
+    #[test]
+    fn test_ttl_staleness() {
+        let ttl = Some(Duration::from_secs(60));
+        assert!(is_stale(Some(Duration::from_secs(61)), ttl));
+        assert!(!is_stale(Some(Duration::from_secs(59)), ttl));
+        assert!(!is_stale(Some(Duration::from_secs(86_400)), None));
+        assert!(!is_stale(None, ttl));
+    }
+}
@@ -0,0 +1,118 @@
+//! SQLite archive of posted summaries (enabled with the `sqlite` feature)
+//!
+//! Each call takes its own `Connection`; open one per operation with [`open`]
+//! so concurrent runs rely on SQLite's file locking rather than shared state.
+use crate::error::Result;
+use crate::models::run_report::RunReport;
+use rusqlite::{params, Connection};
+use std::time::Duration;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS summaries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    video_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    link TEXT NOT NULL,
+    publish_time TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    transcript_chars INTEGER NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_summaries_video_id ON summaries (video_id);";
+
+/// Open (or create) the database at `path`, waiting up to 5s on a locked file
+pub fn open(path: &str) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(Duration::from_secs(5))?;
+    Ok(conn)
+}
+
+/// Insert a processed video's report, creating the schema on first use
+pub fn store_summary(conn: &Connection, report: &RunReport) -> Result<()> {
+    conn.execute_batch(SCHEMA)?;
+    conn.execute(
+        "INSERT INTO summaries
+            (video_id, title, link, publish_time, summary, transcript_chars, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            report.video_id,
+            report.title,
+            report.link,
+            report.publish_time,
+            report.summary,
+            report.transcript_chars as i64,
+            report.created_at,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Return the `n` most recently stored reports, newest first
+pub fn recent_summaries(conn: &Connection, n: usize) -> Result<Vec<RunReport>> {
+    conn.execute_batch(SCHEMA)?;
+    let mut stmt = conn.prepare(
+        "SELECT video_id, title, link, publish_time, summary, transcript_chars, created_at
+         FROM summaries ORDER BY id DESC LIMIT ?1",
+    )?;
+
+    let rows = stmt.query_map(params![n as i64], |row| {
+        Ok(RunReport {
+            video_id: row.get(0)?,
+            title: row.get(1)?,
+            link: row.get(2)?,
+            publish_time: row.get(3)?,
+            summary: row.get(4)?,
+            transcript_chars: row.get::<_, i64>(5)? as usize,
+            created_at: row.get(6)?,
+            ..Default::default()
+        })
+    })?;
+
+    Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(video_id: &str, summary: &str) -> RunReport {
+        RunReport {
+            video_id: video_id.to_string(),
+            title: format!("KS Forward {}", video_id),
+            link: format!("https://www.youtube.com/watch?v={}", video_id),
+            publish_time: "2025-01-15T01:00:00Z".to_string(),
+            summary: summary.to_string(),
+            transcript_chars: 1234,
+            created_at: "2025-01-15T02:00:00+00:00".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_store_and_query_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        let first = report("AAAAAAAAAAA", "สรุปแรก");
+        let second = report("BBBBBBBBBBB", "second summary");
+
+        store_summary(&conn, &first).unwrap();
+        store_summary(&conn, &second).unwrap();
+
+        let recent = recent_summaries(&conn, 10).unwrap();
+        assert_eq!(recent, vec![second.clone(), first]);
+
+        let latest = recent_summaries(&conn, 1).unwrap();
+        assert_eq!(latest, vec![second]);
+    }
+
+    #[test]
+    fn test_recent_summaries_on_fresh_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert!(recent_summaries(&conn, 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sqlite_errors_map_to_database() {
+        let dir = std::env::temp_dir().join(format!("ks-db-{}", uuid::Uuid::new_v4()));
+        let err = open(&dir.join("missing").join("summaries.db").to_string_lossy()).unwrap_err();
+        assert_eq!(err.category(), "database");
+    }
+}
@@ -0,0 +1,101 @@
+//! Near-duplicate episode detection backed by a small JSON state file
+//!
+//! Recently processed transcripts are remembered by SimHash fingerprint so a
+//! re-upload (e.g. an audio-only version of the same episode) can be skipped.
+use crate::error::{AppError, Result};
+use crate::utils::simhash;
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::fs;
+
+/// How many recent fingerprints are kept in the state file
+const MAX_RECENT: usize = 50;
+
+/// Fingerprint of one processed video's transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub video_id: String,
+    pub simhash: u64,
+}
+
+/// Recently processed fingerprints, newest last
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DedupState {
+    pub recent: Vec<Fingerprint>,
+}
+
+impl DedupState {
+    /// Load the state file, starting empty when it does not exist yet
+    pub async fn load(path: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(path).await?;
+        serde_json::from_str(&raw).map_err(|e| AppError::cache(format!("dedup state '{}': {}", path, e)))
+    }
+
+    /// Write the state file
+    pub async fn save(&self, path: &str) -> Result<()> {
+        let raw = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::cache(format!("dedup state '{}': {}", path, e)))?;
+        fs::write(path, raw).await?;
+        Ok(())
+    }
+
+    /// Most similar earlier video whose transcript is at least `threshold` similar
+    pub fn find_similar(&self, video_id: &str, transcript: &str, threshold: f64) -> Option<(&Fingerprint, f64)> {
+        let hash = simhash::simhash(transcript);
+        self.recent
+            .iter()
+            .filter(|entry| entry.video_id != video_id)
+            .map(|entry| (entry, simhash::similarity(entry.simhash, hash)))
+            .filter(|(_, score)| *score >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Remember a processed transcript, dropping the oldest beyond `MAX_RECENT`
+    pub fn record(&mut self, video_id: &str, transcript: &str) {
+        self.recent.retain(|entry| entry.video_id != video_id);
+        self.recent.push(Fingerprint {
+            video_id: video_id.to_string(),
+            simhash: simhash::simhash(transcript),
+        });
+        if self.recent.len() > MAX_RECENT {
+            let excess = self.recent.len() - MAX_RECENT;
+            self.recent.drain(..excess);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPISODE: &str = "welcome back to ks forward today we discuss the set index outlook, \
+        the fed rate decision and what it means for thai bank stocks, then we review \
+        energy names and close with our top picks for the coming week";
+
+    #[test]
+    fn test_near_duplicate_is_skipped_and_dissimilar_processed() {
+        let mut state = DedupState::default();
+        state.record("video-a", EPISODE);
+
+        let audio_only = format!("{} see you next time", EPISODE);
+        let matched = state.find_similar("video-b", &audio_only, 0.8);
+        assert_eq!(matched.map(|(entry, _)| entry.video_id.as_str()), Some("video-a"));
+
+        let different = "this week we travel to chiang mai to taste khao soi, visit night \
+            markets and talk with local craftspeople about silver and umbrella making";
+        assert!(state.find_similar("video-c", different, 0.8).is_none());
+    }
+
+    #[test]
+    fn test_record_keeps_recent_window() {
+        let mut state = DedupState::default();
+        for i in 0..(MAX_RECENT + 5) {
+            state.record(&format!("video-{}", i), &format!("episode number {}", i));
+        }
+        assert_eq!(state.recent.len(), MAX_RECENT);
+        assert_eq!(state.recent[0].video_id, "video-5");
+    }
+}
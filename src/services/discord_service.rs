@@ -1,9 +1,14 @@
 //! Discord webhook service with professional logging and error handling
 use crate::{
     config::Config,
-    models::discord::{DiscordEmbed, DiscordFooter, DiscordWebhook},
+    error::AppError,
+    models::discord::{DiscordEmbed, DiscordField, DiscordFooter, DiscordImage, DiscordTarget, DiscordWebhook},
+    services::http_client,
+    utils::{language, redact::size_label},
 };
 use chrono::{Local, Datelike, Timelike};
+use once_cell::sync::Lazy;
+use reqwest::redirect::Policy;
 use reqwest::Client;
 use serde_json::Value;
 use tokio::time::{sleep, Duration};
@@ -33,71 +38,266 @@ fn format_discord_timestamp(dt: &chrono::DateTime<Local>) -> String {
     )
 }
 
-/// Send a message to Discord webhook with professional logging
+/// Footer text used when no target or channel branding applies
+const DEFAULT_FOOTER: &str = "KS Forward";
+
+/// Footer text for a message, e.g. "KS Forward • KS Forward Channel • Language: Thai"
+fn footer_text(metadata: &EmbedMetadata, message: &str) -> String {
+    let mut footer = DEFAULT_FOOTER.to_string();
+    if let Some(channel) = metadata.channel_title.as_deref().filter(|c| !c.trim().is_empty()) {
+        footer.push_str(&format!(" • {}", channel.trim()));
+    }
+    if let Some(statistics) = metadata.statistics.as_deref() {
+        footer.push_str(&format!(" • {}", statistics));
+    }
+    if metadata.show_detected_language {
+        if let Some(language) = language::detect_language(message) {
+            footer.push_str(&format!(" • Language: {}", language));
+        }
+    }
+    footer
+}
+
+/// Maximum redirects followed (re-POSTing the body) for one webhook request
+const MAX_WEBHOOK_REDIRECTS: usize = 3;
+
+/// Longest `Retry-After` wait honoured for a rate-limited (429) batch
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Wait requested by a `Retry-After` header, in (possibly fractional) seconds
+fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let seconds: f64 = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    (seconds.is_finite() && seconds >= 0.0).then(|| Duration::from_secs_f64(seconds).min(MAX_RETRY_AFTER))
+}
+
+/// Optional per-video details attached to the first embed of a message
+#[derive(Debug, Clone, Default)]
+pub struct EmbedMetadata {
+    /// Large image shown below the summary (usually the video thumbnail)
+    pub image_url: Option<String>,
+    /// Small corner image, used when no large image is shown
+    pub thumbnail_url: Option<String>,
+    /// Video link attached to the header embed's title
+    pub video_url: Option<String>,
+    /// Publish date shown as a field on the header embed
+    pub published: Option<String>,
+    /// Channel name shown as a field on the header embed
+    pub channel: Option<String>,
+    /// Show name appended to the footer (e.g. the YouTube channel title)
+    pub channel_title: Option<String>,
+    /// View/like counts appended to the footer, e.g. "👁 12,345 • 👍 678"
+    pub statistics: Option<String>,
+    /// Add the detected language of each message to the footer
+    pub show_detected_language: bool,
+}
+
+/// A titled block of text delivered alongside others in one message
+#[derive(Debug, Clone)]
+pub struct MessageSection {
+    pub heading: String,
+    pub body: String,
+}
+
+/// Send a message to every webhook in `config.discord_targets`
+///
+/// The caller passes the run's `Config`; the environment is not re-read, so
+/// tests can point the targets at a mock webhook.
 ///
 /// # Errors
 /// Returns an error if:
-/// - Configuration is invalid
 /// - Discord API request fails (after retries)
 /// - Discord returns 4xx error (client error)
 /// - Discord returns 500 error after all retries
-pub async fn send_message(title: &str, message: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let client = Client::new();
+pub async fn send_message(
+    config: &Config,
+    title: &str,
+    message: &str,
+    metadata: &EmbedMetadata,
+) -> Result<(), Box<dyn std::error::Error>> {
     let now = Local::now();
 
     info!("📤 Preparing to send message to Discord");
     info!("   Title: {}", title);
-    info!("   Message byte length: {}", message.len());
-    info!("   Message char length: {}", message.chars().count());
+    info!("   Message size: {}", size_label(message));
 
     // Try to parse message as JSON and extract "answer" field if it exists
     let clean_message = extract_clean_message(message);
 
-    info!("✓ Clean message length: {} chars", clean_message.len());
+    info!("✓ Clean message length: {} chars", clean_message.chars().count());
     info!("   Preview: {}", &clean_message.chars().take(100).collect::<String>());
 
     // Build embeds and split long messages into multiple embeds if needed
-    let embeds = build_embeds(title, &clean_message, now);
+    let embeds = build_embeds(title, &clean_message, max_desc(config), now, metadata);
 
     info!("📦 Created {} embed(s)", embeds.len());
 
-    let discord_webhook_url = Config::from_env()?.discord_ks_bot_token;
+    post_embeds(config, embeds).await
+}
 
-    // Discord accepts up to 10 embeds per webhook request
-    let total_batches = (embeds.len() + 9) / 10;
-    info!("🚀 Sending to Discord in {} batch(es)", total_batches);
+/// Send several titled sections (e.g. one summary per language) as one Discord message
+///
+/// Each section is split into embeds like `send_message`; the embeds of all
+/// sections are posted together, in order, to `config.discord_targets`.
+pub async fn send_sections(
+    config: &Config,
+    title: &str,
+    sections: &[MessageSection],
+    metadata: &EmbedMetadata,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("📤 Preparing {} section(s) for Discord", sections.len());
 
-    for (batch_idx, batch) in embeds.chunks(10).enumerate() {
-        let batch_num = batch_idx + 1;
-        let batch_embeds: Vec<DiscordEmbed> = batch
-            .iter()
-            .map(|e| DiscordEmbed {
-                title: e.title.clone(),
-                description: e.description.clone(),
-                color: e.color,
-                timestamp: e.timestamp.clone(),
-                footer: e.footer.as_ref().map(|f| DiscordFooter {
-                    text: f.text.clone(),
-                }),
-            })
-            .collect();
+    let embeds = build_section_embeds(title, sections, max_desc(config), Local::now(), metadata);
+    info!("📦 Created {} embed(s)", embeds.len());
 
-        let webhook = DiscordWebhook {
-            content: None,
-            embeds: Some(batch_embeds),
+    post_embeds(config, embeds).await
+}
+
+/// Post embeds to every configured webhook target
+async fn post_embeds(config: &Config, embeds: Vec<DiscordEmbed>) -> Result<(), Box<dyn std::error::Error>> {
+    deliver_to_targets(config, webhook_client(), &embeds).await
+}
+
+/// Deliver embeds to each target with its own formatting.
+///
+/// A failing target does not stop delivery to the others; an error is returned
+/// only when no target accepted the message, and it is the first target's own
+/// error (e.g. `AppError::Discord`) so callers can still match on it.
+async fn deliver_to_targets(
+    config: &Config,
+    client: &Client,
+    embeds: &[DiscordEmbed],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut failures = Vec::new();
+
+    for target in &config.discord_targets {
+        let url = with_thread_id(&normalize_webhook_url(&target.url), config.discord_thread_id.as_deref());
+        info!("🎯 Delivering to webhook {}", mask_webhook_url(&url));
+
+        let styled = style_for_target(embeds, target);
+        // Optional display name/avatar override, the same on every message
+        let identity = DiscordWebhook {
+            username: config.discord_username.clone(),
+            avatar_url: config.discord_avatar_url.clone(),
+            ..Default::default()
         };
+        let payloads: Vec<DiscordWebhook> = if target.embed {
+            batch_embeds(&styled)
+                .into_iter()
+                .map(|batch| DiscordWebhook {
+                    embeds: Some(batch),
+                    ..identity.clone()
+                })
+                .collect()
+        } else {
+            embeds_as_content(&styled)
+                .into_iter()
+                .map(|content| DiscordWebhook {
+                    content: Some(content),
+                    ..identity.clone()
+                })
+                .collect()
+        };
+
+        if let Err(e) = post_payloads(config, client, &url, &payloads).await {
+            error!("❌ Delivery to {} failed: {}", mask_webhook_url(&url), e);
+            failures.push(e);
+        }
+    }
+
+    if !failures.is_empty() && failures.len() == config.discord_targets.len() {
+        error!("❌ Discord delivery failed for all {} target(s)", failures.len());
+        return Err(failures.swap_remove(0));
+    }
+    if !failures.is_empty() {
+        warn!(
+            "⚠️  Delivered to {}/{} Discord targets",
+            config.discord_targets.len() - failures.len(),
+            config.discord_targets.len()
+        );
+    }
+    Ok(())
+}
+
+/// Apply a target's footer and color to a copy of the embeds
+fn style_for_target(embeds: &[DiscordEmbed], target: &DiscordTarget) -> Vec<DiscordEmbed> {
+    embeds
+        .iter()
+        .cloned()
+        .map(|mut embed| {
+            if let Some(color) = target.color {
+                embed.color = color;
+            }
+            if let (Some(footer), Some(current)) = (&target.footer, embed.footer.as_mut()) {
+                // Keep any channel suffix; only the brand part is per target
+                current.text = current.text.replacen(DEFAULT_FOOTER, footer, 1);
+            }
+            embed
+        })
+        .collect()
+}
+
+/// Discord's limit for a message's plain `content`, in chars
+const MAX_CONTENT: usize = 2000;
+
+/// Render embeds as plain-text messages within Discord's 2000-char content limit
+fn embeds_as_content(embeds: &[DiscordEmbed]) -> Vec<String> {
+    embeds
+        .iter()
+        .flat_map(|embed| {
+            let text = if embed.title.is_empty() {
+                embed.description.clone()
+            } else {
+                format!("**{}**\n{}", embed.title, embed.description)
+            };
+            split_message(&text, MAX_CONTENT)
+        })
+        .collect()
+}
+
+/// Plain-content copy of an embed payload, truncated to `MAX_CONTENT`, for when
+/// Discord rejects the embeds themselves; `None` for payloads without embeds
+fn content_fallback(webhook: &DiscordWebhook) -> Option<DiscordWebhook> {
+    let embeds = webhook.embeds.as_ref().filter(|embeds| !embeds.is_empty())?;
+    let content: String = embeds_as_content(embeds)
+        .join("\n\n")
+        .chars()
+        .take(MAX_CONTENT)
+        .collect();
+    Some(DiscordWebhook {
+        content: Some(content),
+        embeds: None,
+        ..webhook.clone()
+    })
+}
+
+/// Post payloads to one webhook in order, retrying transient failures
+async fn post_payloads(
+    config: &Config,
+    client: &Client,
+    discord_webhook_url: &str,
+    payloads: &[DiscordWebhook],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let total_batches = payloads.len();
+    info!("🚀 Sending to Discord in {} batch(es)", total_batches);
+
+    for (batch_idx, webhook) in payloads.iter().enumerate() {
+        let batch_num = batch_idx + 1;
 
         // Log the payload for debugging (only on first batch to avoid spam)
         if batch_num == 1 {
-            let json_payload = serde_json::to_string_pretty(&webhook).unwrap_or_default();
-            info!("📋 Discord webhook payload:");
-            //info!("{}", json_payload);
+            let json_payload = serde_json::to_string_pretty(webhook).unwrap_or_default();
+            info!("📋 Discord webhook payload: {} bytes", json_payload.len());
         }
 
         // Retry logic for transient Discord errors
         let max_retries = 3;
         let mut attempt = 0;
-        let mut last_error = None;
 
         loop {
             attempt += 1;
@@ -106,23 +306,56 @@ pub async fn send_message(title: &str, message: &str) -> Result<(), Box<dyn std:
                 batch_num, total_batches, attempt, max_retries
             );
 
-            match send_discord_request(&client, &discord_webhook_url, &webhook).await {
-                Ok(status) if status.is_success() => {
+            match send_discord_request(config, client, discord_webhook_url, webhook).await {
+                Ok((status, _)) if status.is_success() => {
                     info!("✅ Discord batch {} accepted (status: {})", batch_num, status);
                     break;
                 }
-                Ok(status) => {
+                Ok((status, retry_after)) => {
                     // Discord returned an error status
                     let is_retryable = status.is_server_error() || status.as_u16() == 429; // 5xx or 429 (rate limit)
 
                     if is_retryable && attempt < max_retries {
+                        // Rate limits say how long to wait; otherwise back off linearly
+                        let backoff = retry_after
+                            .filter(|_| status.as_u16() == 429)
+                            .unwrap_or_else(|| Duration::from_millis(1000 * attempt as u64));
                         warn!(
-                            "⚠️  Discord returned {} for batch {} - retrying (attempt {}/{})",
-                            status, batch_num, attempt, max_retries
+                            "⚠️  Discord returned {} for batch {} - retrying in {:?} (attempt {}/{})",
+                            status, batch_num, backoff, attempt, max_retries
                         );
-                        let backoff = Duration::from_millis(1000 * attempt as u64);
                         sleep(backoff).await;
                         continue;
+                    } else if status.as_u16() == 429 {
+                        error!(
+                            "❌ Discord rate limit persisted for batch {} after {} attempts",
+                            batch_num, attempt
+                        );
+                        return Err(AppError::Discord { status: 429 }.into());
+                    } else if let (400, Some(fallback)) = (status.as_u16(), content_fallback(webhook)) {
+                        // A malformed embed would be rejected again; send it as plain text instead
+                        warn!(
+                            "⚠️  Discord rejected the embeds of batch {} (400) - falling back to plain content",
+                            batch_num
+                        );
+                        match send_discord_request(config, client, discord_webhook_url, &fallback).await {
+                            Ok((status, _)) if status.is_success() => {
+                                info!("✅ Batch {} delivered as plain content (status: {})", batch_num, status);
+                                break;
+                            }
+                            Ok((status, _)) => {
+                                return Err(format!(
+                                    "Discord rejected batch {} as embeds (400) and as plain content ({})",
+                                    batch_num, status
+                                ).into());
+                            }
+                            Err(e) => {
+                                return Err(format!(
+                                    "Discord rejected batch {} as embeds (400); plain-content fallback failed: {}",
+                                    batch_num, e
+                                ).into());
+                            }
+                        }
                     } else {
                         // Client error (4xx) or exhausted retries
                         error!(
@@ -130,7 +363,7 @@ pub async fn send_message(title: &str, message: &str) -> Result<(), Box<dyn std:
                             batch_num
                         );
                         error!("   Status: {}", status);
-                        error!("   URL: {}", mask_webhook_url(&discord_webhook_url));
+                        error!("   URL: {}", mask_webhook_url(discord_webhook_url));
 
                         if status.is_client_error() {
                             error!("   Type: Client error (4xx) - check webhook URL and permissions");
@@ -146,8 +379,6 @@ pub async fn send_message(title: &str, message: &str) -> Result<(), Box<dyn std:
                 }
                 Err(e) => {
                     // Network/transport error
-                    last_error = Some(e.to_string());
-
                     if attempt < max_retries {
                         warn!(
                             "⚠️  Network error sending batch {} (attempt {}/{}): {}",
@@ -157,14 +388,13 @@ pub async fn send_message(title: &str, message: &str) -> Result<(), Box<dyn std:
                         sleep(backoff).await;
                         continue;
                     } else {
-                        // Extract error once to avoid ownership issues
-                        let error_msg = last_error.clone().unwrap_or_default();
+                        let error_msg = e.to_string();
                         error!(
                             "❌ Failed to send to Discord after {} attempts: {}",
                             max_retries, error_msg
                         );
                         error!("   Batch: {}", batch_num);
-                        error!("   URL: {}", mask_webhook_url(&discord_webhook_url));
+                        error!("   URL: {}", mask_webhook_url(discord_webhook_url));
 
                         return Err(format!(
                             "Failed to send to Discord after {} retries: {}",
@@ -176,7 +406,7 @@ pub async fn send_message(title: &str, message: &str) -> Result<(), Box<dyn std:
         }
     }
 
-    info!("✅ All messages sent to Discord successfully");
+    info!("✅ All messages sent to {}", mask_webhook_url(discord_webhook_url));
     Ok(())
 }
 
@@ -209,7 +439,7 @@ fn extract_clean_message(message: &str) -> String {
         Ok(json_val) => {
             info!("✓ Parsed message as JSON successfully");
             if let Some(answer) = json_val.get("answer").and_then(|v| v.as_str()) {
-                info!("✓ Found 'answer' field, length: {}", answer.len());
+                info!("✓ Found 'answer' field, length: {} chars", answer.chars().count());
                 answer.to_string()
             } else {
                 info!("✗ No 'answer' field found in JSON, using raw content");
@@ -229,45 +459,128 @@ fn extract_clean_message(message: &str) -> String {
     }
 }
 
-/// Build Discord embeds from message, splitting if necessary
-fn build_embeds(title: &str, message: &str, now: chrono::DateTime<Local>) -> Vec<DiscordEmbed> {
-    const MAX_DESC: usize = 4000; // Safe limit for Discord embed description (Discord limit is 4096)
+/// Split `text` into parts of at most `max_chars` chars, breaking after the
+/// last newline, then the last sentence end, then the last whitespace before
+/// the limit. Only a single unbroken run longer than the limit is hard-cut.
+/// The parts concatenate back to `text`.
+fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = text;
 
-    let chars: Vec<char> = message.chars().collect();
-    let total = if chars.is_empty() {
-        0
-    } else {
-        (chars.len() + MAX_DESC - 1) / MAX_DESC
-    };
+    while let Some((limit, _)) = rest.char_indices().nth(max_chars) {
+        let window = &rest[..limit];
+        let cut = break_point(window).unwrap_or(limit);
+        parts.push(rest[..cut].to_string());
+        rest = &rest[cut..];
+    }
+    if !rest.is_empty() {
+        parts.push(rest.to_string());
+    }
+    parts
+}
+
+/// Byte index just after the preferred break in `window`, if it has one
+fn break_point(window: &str) -> Option<usize> {
+    if let Some(i) = window.rfind('\n') {
+        return Some(i + 1);
+    }
+
+    let mut next_is_space = false;
+    let mut last_space = None;
+    for (i, c) in window.char_indices().rev() {
+        if next_is_space && matches!(c, '.' | '!' | '?') {
+            return Some(i + c.len_utf8());
+        }
+        next_is_space = c.is_whitespace();
+        if next_is_space && last_space.is_none() {
+            last_space = Some(i + c.len_utf8());
+        }
+    }
+    last_space
+}
+
+/// Discord's limit for one embed description, in chars
+const DISCORD_DESC_LIMIT: usize = 4096;
+
+/// Discord's limit for the combined text of all embeds in one message, in chars
+const MAX_MESSAGE_EMBED_CHARS: usize = 6000;
 
+/// Discord accepts up to 10 embeds per webhook request
+const MAX_EMBEDS_PER_REQUEST: usize = 10;
+
+/// Configured description size, clamped to Discord's limit
+fn max_desc(config: &Config) -> usize {
+    config.discord_max_desc.min(DISCORD_DESC_LIMIT)
+}
+
+/// Text Discord counts towards `MAX_MESSAGE_EMBED_CHARS`
+fn embed_chars(embed: &DiscordEmbed) -> usize {
+    embed.title.chars().count()
+        + embed.description.chars().count()
+        + embed.footer.as_ref().map_or(0, |f| f.text.chars().count())
+        + embed
+            .fields
+            .iter()
+            .map(|f| f.name.chars().count() + f.value.chars().count())
+            .sum::<usize>()
+}
+
+/// Group embeds into requests of at most `MAX_EMBEDS_PER_REQUEST` whose combined
+/// text stays within `MAX_MESSAGE_EMBED_CHARS`, keeping their order
+fn batch_embeds(embeds: &[DiscordEmbed]) -> Vec<Vec<DiscordEmbed>> {
+    let mut batches: Vec<Vec<DiscordEmbed>> = Vec::new();
+    let mut batch_chars = 0;
+
+    for embed in embeds {
+        let chars = embed_chars(embed);
+        let fits = batches.last().is_some_and(|batch| {
+            batch.len() < MAX_EMBEDS_PER_REQUEST && batch_chars + chars <= MAX_MESSAGE_EMBED_CHARS
+        });
+        if fits {
+            batch_chars += chars;
+            batches.last_mut().expect("checked above").push(embed.clone());
+        } else {
+            batch_chars = chars;
+            batches.push(vec![embed.clone()]);
+        }
+    }
+    batches
+}
+
+/// Build Discord embeds from message, splitting descriptions at `max_desc` chars
+fn build_embeds(
+    title: &str,
+    message: &str,
+    max_desc: usize,
+    now: chrono::DateTime<Local>,
+    metadata: &EmbedMetadata,
+) -> Vec<DiscordEmbed> {
     let mut embeds: Vec<DiscordEmbed> = Vec::new();
 
-    for i in 0..total {
-        let start = i * MAX_DESC;
-        let end = ((i + 1) * MAX_DESC).min(chars.len());
-        let part: String = chars[start..end].iter().collect();
+    for (i, part) in split_message(message, max_desc).into_iter().enumerate() {
         let part_bytes = part.len();
         let part_chars = part.chars().count();
 
         // Log for debugging first embed only
         if i == 0 {
-            info!("📊 Embed 1: {} chars, {} bytes (max: {})", part_chars, part_bytes, MAX_DESC);
+            info!("📊 Embed 1: {} chars, {} bytes (max: {})", part_chars, part_bytes, max_desc);
         }
 
-        let display_title = if total > 1 {
-            format!("{} ({}/{})", title, i + 1, total)
-        } else {
-            title.to_string()
-        };
+        // The first embed is the header; the rest are title-less continuations
+        let display_title = if i == 0 { title.to_string() } else { String::new() };
 
         embeds.push(DiscordEmbed {
             title: display_title,
+            url: None,
             description: part,
             color: 0x5865F2, // Discord Blurple
             timestamp: format_discord_timestamp(&now),
             footer: Some(DiscordFooter {
-                text: "KS Forward".to_string(),
+                text: footer_text(metadata, message),
             }),
+            image: None,
+            thumbnail: None,
+            fields: Vec::new(),
         });
     }
 
@@ -275,33 +588,129 @@ fn build_embeds(title: &str, message: &str, now: chrono::DateTime<Local>) -> Vec
     if embeds.is_empty() {
         embeds.push(DiscordEmbed {
             title: "Daily Summary".to_string(),
+            url: None,
             description: message.to_string(),
             color: 0x5865F2,
             timestamp: format_discord_timestamp(&now),
             footer: Some(DiscordFooter {
-                text: "KS Forward".to_string(),
+                text: footer_text(metadata, message),
             }),
+            image: None,
+            thumbnail: None,
+            fields: Vec::new(),
+        });
+    }
+
+    if let Some(first) = embeds.first_mut() {
+        apply_header(first, metadata);
+    }
+
+    embeds
+}
+
+/// Attach the per-video metadata (link, publish date, channel, image or thumbnail) to the header embed
+/// only, so it is not repeated on continuation embeds
+fn apply_header(header: &mut DiscordEmbed, metadata: &EmbedMetadata) {
+    header.url = metadata.video_url.clone();
+    if let Some(published) = &metadata.published {
+        header.fields.push(DiscordField {
+            name: "Published".to_string(),
+            value: published.clone(),
+            inline: true,
+        });
+    }
+    if let Some(channel) = metadata.channel.as_deref().filter(|c| !c.trim().is_empty()) {
+        header.fields.push(DiscordField {
+            name: "Channel".to_string(),
+            value: channel.to_string(),
+            inline: true,
         });
     }
+    if let Some(url) = &metadata.image_url {
+        header.image = Some(DiscordImage { url: url.clone() });
+    } else if let Some(url) = &metadata.thumbnail_url {
+        header.thumbnail = Some(DiscordImage { url: url.clone() });
+    }
+}
+
+/// Build embeds for several sections, titling each as "<title> — <heading>"
+fn build_section_embeds(
+    title: &str,
+    sections: &[MessageSection],
+    max_desc: usize,
+    now: chrono::DateTime<Local>,
+    metadata: &EmbedMetadata,
+) -> Vec<DiscordEmbed> {
+    let mut embeds: Vec<DiscordEmbed> = sections
+        .iter()
+        .flat_map(|section| {
+            let section_title = format!("{} — {}", title, section.heading);
+            let body = extract_clean_message(&section.body);
+            let section_metadata = EmbedMetadata {
+                image_url: None,
+                thumbnail_url: None,
+                video_url: None,
+                published: None,
+                channel: None,
+                ..metadata.clone()
+            };
+            build_embeds(&section_title, &body, max_desc, now, &section_metadata)
+        })
+        .collect();
+
+    if let Some(first) = embeds.first_mut() {
+        apply_header(first, metadata);
+    }
 
     embeds
 }
 
-/// Send a single request to Discord webhook
+/// Send a single request to Discord webhook, returning its status and any
+/// `Retry-After` wait
 async fn send_discord_request(
+    config: &Config,
     client: &Client,
     url: &str,
     webhook: &DiscordWebhook,
-) -> Result<reqwest::StatusCode, reqwest::Error> {
-    // Note: .json() automatically sets Content-Type: application/json
-    let response = client
-        .post(url)
-        .json(webhook)
-        .send()
-        .await?;
+) -> Result<(reqwest::StatusCode, Option<Duration>), reqwest::Error> {
+    if let Ok(payload) = serde_json::to_string(webhook) {
+        http_client::trace_request(config, "POST", url, Some(&payload));
+    }
+
+    // Follow redirects by hand so the JSON body is re-POSTed to the new location
+    let mut target = url.to_string();
+    let mut redirects = 0;
+    let response = loop {
+        // Note: .json() automatically sets Content-Type: application/json
+        let request = http_client::with_request_id(client.post(&target)).json(webhook);
+        let response = http_client::timed_send("discord webhook", request.send()).await?;
+
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|loc| response.url().join(loc).ok());
+
+        match location {
+            Some(next) if response.status().is_redirection() && redirects < MAX_WEBHOOK_REDIRECTS => {
+                redirects += 1;
+                warn!(
+                    "↪️  Discord webhook redirected ({}) to {} - re-posting (redirect {}/{})",
+                    response.status(),
+                    mask_webhook_url(next.as_str()),
+                    redirects,
+                    MAX_WEBHOOK_REDIRECTS
+                );
+                target = next.to_string();
+            }
+            _ => break response,
+        }
+    };
 
     let status = response.status();
+    let headers = response.headers().clone();
     let body = response.text().await.unwrap_or_default();
+    http_client::trace_response(config, url, status, &headers, &body);
 
     // Log response details
     if status.is_success() {
@@ -311,11 +720,68 @@ async fn send_discord_request(
         error!("   Response body: {}", body);
     }
 
-    Ok(status)
+    Ok((status, parse_retry_after(&headers)))
+}
+
+/// Timeout for one webhook request; a hung webhook fails the attempt instead
+/// of stalling the run
+const WEBHOOK_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Pooled client for webhook posts. Unlike `http_client::client()` it does not
+/// follow redirects; `send_discord_request` re-POSTs the body itself.
+static WEBHOOK_CLIENT: Lazy<Client> = Lazy::new(|| {
+    Client::builder()
+        .redirect(Policy::none())
+        .pool_max_idle_per_host(10)
+        .pool_idle_timeout(Duration::from_secs(90))
+        .connect_timeout(Duration::from_secs(10))
+        .timeout(WEBHOOK_REQUEST_TIMEOUT)
+        .tcp_nodelay(true)
+        .build()
+        .expect("Failed to build webhook HTTP client")
+});
+
+/// Get the shared webhook client
+fn webhook_client() -> &'static Client {
+    &WEBHOOK_CLIENT
+}
+
+/// Rewrite legacy `discordapp.com` webhook hosts to `discord.com`
+pub(crate) fn normalize_webhook_url(url: &str) -> String {
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+
+    let canonical = match parsed.host_str() {
+        Some("discordapp.com") => "discord.com".to_string(),
+        Some(host) if host.ends_with(".discordapp.com") => {
+            format!("{}.discord.com", host.trim_end_matches(".discordapp.com"))
+        }
+        _ => return url.to_string(),
+    };
+
+    if parsed.set_host(Some(&canonical)).is_err() {
+        return url.to_string();
+    }
+    info!("↪️  Normalized legacy webhook host to {}", canonical);
+    parsed.to_string()
+}
+
+/// Add `?thread_id=` so the webhook posts into that thread; unchanged when
+/// `thread_id` is unset or the URL already names a thread
+fn with_thread_id(url: &str, thread_id: Option<&str>) -> String {
+    let (Some(thread_id), Ok(mut parsed)) = (thread_id, reqwest::Url::parse(url)) else {
+        return url.to_string();
+    };
+    if parsed.query_pairs().any(|(key, _)| key == "thread_id") {
+        return url.to_string();
+    }
+    parsed.query_pairs_mut().append_pair("thread_id", thread_id);
+    parsed.to_string()
 }
 
 /// Mask webhook URL for logging (hide sensitive parts)
-fn mask_webhook_url(url: &str) -> String {
+pub(crate) fn mask_webhook_url(url: &str) -> String {
     if let Some(last_slash) = url.rfind('/') {
         let first_part = &url[..last_slash];
         format!("{}/***", first_part)
@@ -323,3 +789,602 @@ fn mask_webhook_url(url: &str) -> String {
         "***".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DEFAULT_DISCORD_MAX_DESC;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_split_message_on_paragraphs() {
+        let paragraph = "Bank stocks rallied after the rate decision. ".repeat(20);
+        let message = [paragraph.trim_end(); 10].join("\n");
+        assert!(message.chars().count() > 8_900);
+
+        let parts = split_message(&message, 4000);
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts.concat(), message);
+        for part in &parts {
+            assert!(part.chars().count() <= 4000);
+            assert!(part.starts_with("Bank"));
+            assert!(part.ends_with('\n') || part.ends_with("decision."));
+        }
+    }
+
+    #[test]
+    fn test_split_message_fallbacks() {
+        // One long paragraph breaks after a sentence, then on a word
+        let sentences = "One two three. Four five six seven.";
+        assert_eq!(split_message(sentences, 20), vec!["One two three.", " Four five six ", "seven."]);
+
+        // An unbroken run is hard-cut
+        assert_eq!(split_message(&"x".repeat(10), 4), vec!["xxxx", "xxxx", "xx"]);
+        assert!(split_message("", 4).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_webhook_url() {
+        assert_eq!(
+            normalize_webhook_url("https://discordapp.com/api/webhooks/1/abc"),
+            "https://discord.com/api/webhooks/1/abc"
+        );
+        assert_eq!(
+            normalize_webhook_url("https://ptb.discordapp.com/api/webhooks/1/abc"),
+            "https://ptb.discord.com/api/webhooks/1/abc"
+        );
+        assert_eq!(
+            normalize_webhook_url("https://discord.com/api/webhooks/1/abc"),
+            "https://discord.com/api/webhooks/1/abc"
+        );
+    }
+
+    #[test]
+    fn test_webhook_client_is_shared() {
+        assert!(std::ptr::eq(webhook_client(), webhook_client()));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_reposts_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/old"))
+            .respond_with(ResponseTemplate::new(301).insert_header("Location", "/api/webhooks/new"))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/new"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let webhook = DiscordWebhook {
+            content: Some("hello".to_string()),
+            ..Default::default()
+        };
+        let client = webhook_client();
+        let (status, _) = send_discord_request(
+            &Config::default(),
+            client,
+            &format!("{}/api/webhooks/old", server.uri()),
+            &webhook,
+        )
+        .await
+        .unwrap();
+        assert_eq!(status.as_u16(), 204);
+
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[1].url.path(), "/api/webhooks/new");
+        assert_eq!(received[1].method.as_str(), "POST");
+        let body: serde_json::Value = serde_json::from_slice(&received[1].body).unwrap();
+        assert_eq!(body["content"], "hello");
+    }
+
+    #[tokio::test]
+    async fn test_deliver_to_targets_with_own_colors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let config = Config {
+            discord_targets: vec![
+                DiscordTarget {
+                    color: Some(0xFF0000),
+                    footer: Some("Server A".to_string()),
+                    ..DiscordTarget::from_url(format!("{}/api/webhooks/1/a", server.uri()))
+                },
+                DiscordTarget {
+                    color: Some(0x00FF00),
+                    ..DiscordTarget::from_url(format!("{}/api/webhooks/2/b", server.uri()))
+                },
+            ],
+            ..Config::default()
+        };
+        let embeds = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, Local::now(), &EmbedMetadata::default());
+        let client = webhook_client();
+        deliver_to_targets(&config, client, &embeds).await.unwrap();
+
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(received.len(), 2);
+        let first: Value = serde_json::from_slice(&received[0].body).unwrap();
+        let second: Value = serde_json::from_slice(&received[1].body).unwrap();
+        assert_eq!(received[0].url.path(), "/api/webhooks/1/a");
+        assert_eq!(first["embeds"][0]["color"], 0xFF0000);
+        assert_eq!(first["embeds"][0]["footer"]["text"], "Server A");
+        assert_eq!(received[1].url.path(), "/api/webhooks/2/b");
+        assert_eq!(second["embeds"][0]["color"], 0x00FF00);
+        assert_eq!(second["embeds"][0]["footer"]["text"], "KS Forward");
+    }
+
+    #[tokio::test]
+    async fn test_username_and_avatar_override() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/api/webhooks/1/a", server.uri());
+        let embeds = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, Local::now(), &EmbedMetadata::default());
+        let branded = Config {
+            discord_targets: vec![DiscordTarget::from_url(url.clone())],
+            discord_username: Some("KS Digest".to_string()),
+            discord_avatar_url: Some("https://example.com/ks.png".to_string()),
+            ..Config::default()
+        };
+        deliver_to_targets(&branded, webhook_client(), &embeds).await.unwrap();
+        let plain = Config {
+            discord_username: None,
+            discord_avatar_url: None,
+            ..branded
+        };
+        deliver_to_targets(&plain, webhook_client(), &embeds).await.unwrap();
+
+        let received = server.received_requests().await.unwrap();
+        let first: Value = serde_json::from_slice(&received[0].body).unwrap();
+        assert_eq!(first["username"], "KS Digest");
+        assert_eq!(first["avatar_url"], "https://example.com/ks.png");
+        let second: Value = serde_json::from_slice(&received[1].body).unwrap();
+        assert!(second.get("username").is_none() && second.get("avatar_url").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_thread_id_is_sent_as_query_param() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/api/webhooks/1/a", server.uri());
+        let embeds = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, Local::now(), &EmbedMetadata::default());
+        for thread_id in [Some("1234567890"), None] {
+            let config = Config {
+                discord_targets: vec![DiscordTarget::from_url(url.clone())],
+                discord_thread_id: thread_id.map(str::to_string),
+                ..Config::default()
+            };
+            deliver_to_targets(&config, webhook_client(), &embeds).await.unwrap();
+        }
+
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].url.path(), "/api/webhooks/1/a");
+        assert_eq!(received[0].url.query(), Some("thread_id=1234567890"));
+        assert_eq!(received[1].url.query(), None);
+
+        // A thread already named in the URL is kept
+        let threaded = format!("{}?thread_id=1", url);
+        assert_eq!(with_thread_id(&threaded, Some("2")), threaded);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(reqwest::header::RETRY_AFTER, "1.5".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_millis(1500)));
+
+        headers.insert(reqwest::header::RETRY_AFTER, "3600".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(MAX_RETRY_AFTER));
+
+        headers.insert(reqwest::header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    fn batch(content: &str) -> DiscordWebhook {
+        DiscordWebhook {
+            content: Some(content.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejected_embeds_fall_back_to_content() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_string(r#"{"embeds":["0"]}"#))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let long = "Rates were held steady again this month. ".repeat(100);
+        let embeds = build_embeds("Title", &long, DEFAULT_DISCORD_MAX_DESC, Local::now(), &EmbedMetadata::default());
+        let payloads = [DiscordWebhook {
+            embeds: Some(embeds),
+            ..Default::default()
+        }];
+        let url = format!("{}/api/webhooks/1/a", server.uri());
+        post_payloads(&Config::default(), webhook_client(), &url, &payloads)
+            .await
+            .unwrap();
+
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(received.len(), 2);
+        let fallback: Value = serde_json::from_slice(&received[1].body).unwrap();
+        assert!(fallback["embeds"].is_null());
+        let content = fallback["content"].as_str().unwrap();
+        assert!(content.starts_with("**Title**\n"));
+        assert_eq!(content.chars().count(), MAX_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_batch_is_retried_once() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/api/webhooks/1/token", server.uri());
+        let payloads = [batch("first"), batch("second")];
+        post_payloads(&Config::default(), webhook_client(), &url, &payloads)
+            .await
+            .unwrap();
+
+        // The accepted first batch is not re-sent; only the limited second one is
+        let contents: Vec<String> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|request| serde_json::from_slice::<Value>(&request.body).unwrap()["content"].to_string())
+            .collect();
+        assert_eq!(contents, ["\"first\"", "\"second\"", "\"second\""]);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_rate_limit_is_discord_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/api/webhooks/1/token", server.uri());
+        let err = post_payloads(&Config::default(), webhook_client(), &url, &[batch("only")])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<AppError>(),
+            Some(AppError::Discord { status: 429 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_send_message_surfaces_rate_limit_as_discord_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let config = Config {
+            discord_targets: vec![DiscordTarget::from_url(format!(
+                "{}/api/webhooks/1/token",
+                server.uri()
+            ))],
+            ..Config::default()
+        };
+        let err = send_message(&config, "Title", "Body", &EmbedMetadata::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<AppError>(),
+            Some(AppError::Discord { status: 429 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_send_message_posts_to_configured_webhook() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/token"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = Config {
+            discord_targets: vec![DiscordTarget::from_url(format!(
+                "{}/api/webhooks/1/token",
+                server.uri()
+            ))],
+            ..Config::default()
+        };
+        send_message(&config, "Title", "Body", &EmbedMetadata::default())
+            .await
+            .unwrap();
+
+        let received = server.received_requests().await.unwrap();
+        let body: Value = serde_json::from_slice(&received[0].body).unwrap();
+        assert_eq!(body["embeds"][0]["title"], "Title");
+        assert_eq!(body["embeds"][0]["description"], "Body");
+    }
+
+    #[tokio::test]
+    async fn test_deliver_continues_after_failed_target() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/bad"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/2/good"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let config = Config {
+            discord_targets: vec![
+                DiscordTarget::from_url(format!("{}/api/webhooks/1/bad", server.uri())),
+                DiscordTarget::from_url(format!("{}/api/webhooks/2/good", server.uri())),
+            ],
+            ..Config::default()
+        };
+        let embeds = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, Local::now(), &EmbedMetadata::default());
+        let client = webhook_client();
+        assert!(deliver_to_targets(&config, client, &embeds).await.is_ok());
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_channel_title_in_footer() {
+        let metadata = EmbedMetadata {
+            channel_title: Some("KS Forward Channel".to_string()),
+            ..Default::default()
+        };
+        let embeds = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, Local::now(), &metadata);
+        assert_eq!(
+            embeds[0].footer.as_ref().unwrap().text,
+            "KS Forward • KS Forward Channel"
+        );
+
+        let sections = [MessageSection {
+            heading: "Thai".to_string(),
+            body: "Body".to_string(),
+        }];
+        let embeds = build_section_embeds("Title", &sections, DEFAULT_DISCORD_MAX_DESC, Local::now(), &metadata);
+        assert!(embeds[0].footer.as_ref().unwrap().text.ends_with("KS Forward Channel"));
+
+        let plain = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, Local::now(), &EmbedMetadata::default());
+        assert_eq!(plain[0].footer.as_ref().unwrap().text, "KS Forward");
+    }
+
+    #[test]
+    fn test_video_statistics_in_footer() {
+        let metadata = EmbedMetadata {
+            channel_title: Some("KS Forward Channel".to_string()),
+            statistics: Some("👁 12,345 • 👍 678".to_string()),
+            ..Default::default()
+        };
+        let embeds = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, Local::now(), &metadata);
+        assert_eq!(
+            embeds[0].footer.as_ref().unwrap().text,
+            "KS Forward • KS Forward Channel • 👁 12,345 • 👍 678"
+        );
+    }
+
+    #[test]
+    fn test_detected_language_in_footer() {
+        let metadata = EmbedMetadata {
+            show_detected_language: true,
+            ..Default::default()
+        };
+        let embeds = build_embeds("Title", "ตลาดหุ้นไทยปรับตัวขึ้น", DEFAULT_DISCORD_MAX_DESC, Local::now(), &metadata);
+        assert_eq!(embeds[0].footer.as_ref().unwrap().text, "KS Forward • Language: Thai");
+
+        let hidden = build_embeds("Title", "ตลาดหุ้นไทย", DEFAULT_DISCORD_MAX_DESC, Local::now(), &EmbedMetadata::default());
+        assert_eq!(hidden[0].footer.as_ref().unwrap().text, "KS Forward");
+    }
+
+    #[test]
+    fn test_image_serialized_only_when_set() {
+        let now = Local::now();
+
+        let without = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, now, &EmbedMetadata::default());
+        let json = serde_json::to_value(&without[0]).unwrap();
+        assert!(json.get("image").is_none());
+
+        let metadata = EmbedMetadata {
+            image_url: Some("https://i.ytimg.com/vi/abc/hqdefault.jpg".to_string()),
+            ..Default::default()
+        };
+        let with = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, now, &metadata);
+        let json = serde_json::to_value(&with[0]).unwrap();
+        assert_eq!(
+            json["image"]["url"],
+            "https://i.ytimg.com/vi/abc/hqdefault.jpg"
+        );
+    }
+
+    #[test]
+    fn test_section_embeds_one_per_language() {
+        let sections = vec![
+            MessageSection {
+                heading: "Thai".to_string(),
+                body: "สรุปภาษาไทย".to_string(),
+            },
+            MessageSection {
+                heading: "English".to_string(),
+                body: "English summary".to_string(),
+            },
+        ];
+        let embeds = build_section_embeds("KS Forward", &sections, DEFAULT_DISCORD_MAX_DESC, Local::now(), &EmbedMetadata::default());
+
+        assert_eq!(embeds.len(), 2);
+        assert_eq!(embeds[0].title, "KS Forward — Thai");
+        assert_eq!(embeds[0].description, "สรุปภาษาไทย");
+        assert_eq!(embeds[1].title, "KS Forward — English");
+        assert_eq!(embeds[1].description, "English summary");
+    }
+
+    #[test]
+    fn test_image_only_on_first_embed() {
+        let metadata = EmbedMetadata {
+            image_url: Some("https://example.com/thumb.jpg".to_string()),
+            ..Default::default()
+        };
+        let message = "x".repeat(9000);
+        let embeds = build_embeds("Title", &message, DEFAULT_DISCORD_MAX_DESC, Local::now(), &metadata);
+
+        assert_eq!(embeds.len(), 3);
+        assert!(embeds[0].image.is_some());
+        assert!(embeds[1..].iter().all(|e| e.image.is_none()));
+    }
+
+    #[test]
+    fn test_header_fields_for_published_and_channel() {
+        let metadata = EmbedMetadata {
+            published: Some("15 Jan 2025".to_string()),
+            channel: Some("KS Forward Channel".to_string()),
+            ..Default::default()
+        };
+        let embeds = build_embeds("Title", &"x".repeat(9000), DEFAULT_DISCORD_MAX_DESC, Local::now(), &metadata);
+
+        let names: Vec<&str> = embeds[0].fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["Published", "Channel"]);
+        assert_eq!(embeds[0].fields[1].value, "KS Forward Channel");
+        assert!(embeds[0].fields.iter().all(|f| f.inline));
+        assert!(embeds[1..].iter().all(|e| e.fields.is_empty()));
+
+        // Missing or blank values add no field
+        let metadata = EmbedMetadata {
+            channel: Some("  ".to_string()),
+            ..Default::default()
+        };
+        let embeds = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, Local::now(), &metadata);
+        assert!(embeds[0].fields.is_empty());
+    }
+
+    #[test]
+    fn test_batches_split_by_total_size() {
+        // Three 2500-char embeds are well under 10 per request but over 6000 chars together
+        let message = "x".repeat(7500);
+        let embeds = build_embeds("Title", &message, 2500, Local::now(), &EmbedMetadata::default());
+        assert_eq!(embeds.len(), 3);
+
+        let batches = batch_embeds(&embeds);
+        let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [2, 1]);
+        for batch in &batches {
+            assert!(batch.iter().map(embed_chars).sum::<usize>() <= MAX_MESSAGE_EMBED_CHARS);
+        }
+
+        // Small embeds are still capped at 10 per request
+        let small = build_embeds("Title", &"x".repeat(1200), 100, Local::now(), &EmbedMetadata::default());
+        assert_eq!(small.len(), 12);
+        let sizes: Vec<usize> = batch_embeds(&small).iter().map(Vec::len).collect();
+        assert_eq!(sizes, [10, 2]);
+    }
+
+    #[test]
+    fn test_max_desc_clamped_to_discord_limit() {
+        assert_eq!(max_desc(&Config::builder().build()), DEFAULT_DISCORD_MAX_DESC);
+        assert_eq!(max_desc(&Config::default()), DEFAULT_DISCORD_MAX_DESC);
+        let config = Config {
+            discord_max_desc: 10_000,
+            ..Default::default()
+        };
+        assert_eq!(max_desc(&config), DISCORD_DESC_LIMIT);
+    }
+
+    #[test]
+    fn test_embed_url_omitted_when_none() {
+        let embeds = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, Local::now(), &EmbedMetadata::default());
+        let json = serde_json::to_value(&embeds[0]).unwrap();
+        assert!(json.get("url").is_none());
+
+        let metadata = EmbedMetadata {
+            video_url: Some("https://www.youtube.com/watch?v=abc".to_string()),
+            ..Default::default()
+        };
+        let embeds = build_embeds("Title", "Body", DEFAULT_DISCORD_MAX_DESC, Local::now(), &metadata);
+        let json = serde_json::to_value(&embeds[0]).unwrap();
+        assert_eq!(json["url"], "https://www.youtube.com/watch?v=abc");
+    }
+
+    #[test]
+    fn test_thumbnail_only_on_first_embed() {
+        let metadata = EmbedMetadata {
+            thumbnail_url: Some("https://example.com/thumb.jpg".to_string()),
+            ..Default::default()
+        };
+        let embeds = build_embeds("Title", &"x".repeat(9000), DEFAULT_DISCORD_MAX_DESC, Local::now(), &metadata);
+
+        let header = serde_json::to_value(&embeds[0]).unwrap();
+        assert_eq!(header["thumbnail"]["url"], "https://example.com/thumb.jpg");
+        assert!(header.get("image").is_none());
+        for continuation in &embeds[1..] {
+            assert!(serde_json::to_value(continuation).unwrap().get("thumbnail").is_none());
+        }
+    }
+
+    #[test]
+    fn test_only_header_embed_carries_metadata() {
+        let metadata = EmbedMetadata {
+            image_url: Some("https://example.com/thumb.jpg".to_string()),
+            video_url: Some("https://www.youtube.com/watch?v=abc".to_string()),
+            published: Some("2025-01-15".to_string()),
+            ..Default::default()
+        };
+        let message = "x".repeat(9000);
+        let embeds = build_embeds("Title", &message, DEFAULT_DISCORD_MAX_DESC, Local::now(), &metadata);
+        assert_eq!(embeds.len(), 3);
+
+        let header = serde_json::to_value(&embeds[0]).unwrap();
+        assert_eq!(header["title"], "Title");
+        assert_eq!(header["url"], "https://www.youtube.com/watch?v=abc");
+        assert_eq!(header["fields"][0]["value"], "2025-01-15");
+        assert!(header.get("image").is_some());
+
+        for continuation in &embeds[1..] {
+            let json = serde_json::to_value(continuation).unwrap();
+            for key in ["title", "url", "fields", "image"] {
+                assert!(json.get(key).is_none(), "continuation has {}", key);
+            }
+        }
+    }
+}
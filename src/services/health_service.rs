@@ -0,0 +1,346 @@
+//! Reachability checks for the external services a run depends on
+//!
+//! A key-free request first confirms outbound HTTPS works: the jsonplaceholder
+//! todo self-test with the `demo` feature, otherwise a connectivity-check URL.
+//! Each service then gets one cheap request: a one-result YouTube search per
+//! channel, a bare call to the transcript endpoint, a "ping" chat to the AI
+//! backend and a GET on every Discord webhook. Failures are collected rather
+//! than stopping at the first one.
+use crate::config::{AiBackend, Config};
+use crate::error::Result;
+use crate::services::discord_service::{mask_webhook_url, normalize_webhook_url};
+use crate::services::http_client;
+use crate::services::myAI_service::{ai_request, openai_body, openai_request};
+#[cfg(feature = "demo")]
+use crate::services::todo_service::{selftest_network_at, TODO_BASE_URL as SELFTEST_BASE_URL};
+use crate::services::youtube_service::{check_channels_at, ChannelStatus, SEARCH_PATH};
+use reqwest::{RequestBuilder, StatusCode};
+use serde_json::json;
+use std::time::Duration;
+use tracing::info;
+
+/// Timeout for each probe; a healthy service answers well within it
+const HEALTHCHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Response body chars quoted in a failure message
+const MAX_FAILURE_BODY_CHARS: usize = 200;
+
+/// Connectivity-check host used for the network self-test without the `demo` feature
+#[cfg(not(feature = "demo"))]
+const SELFTEST_BASE_URL: &str = "https://www.gstatic.com";
+
+/// Result of probing one service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    Ok,
+    Fail(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheck {
+    pub service: &'static str,
+    pub status: HealthStatus,
+}
+
+/// Per-service outcome of `run_healthcheck`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HealthReport {
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    /// Number of services that failed their check
+    pub fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status != HealthStatus::Ok)
+            .count()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.failures() == 0
+    }
+}
+
+/// Probe outbound connectivity, YouTube, the transcript API, the AI backend and Discord
+pub async fn run_healthcheck(config: &Config) -> Result<HealthReport> {
+    run_healthcheck_at(config, &config.youtube_endpoint(SEARCH_PATH), SELFTEST_BASE_URL).await
+}
+
+async fn run_healthcheck_at(config: &Config, search_url: &str, selftest_url: &str) -> Result<HealthReport> {
+    let checks = vec![
+        HealthCheck {
+            service: "network",
+            status: match selftest_network_at(selftest_url).await {
+                Ok(()) => HealthStatus::Ok,
+                Err(e) => HealthStatus::Fail(e.to_string()),
+            },
+        },
+        HealthCheck {
+            service: "youtube",
+            status: check_youtube(config, search_url).await,
+        },
+        HealthCheck {
+            service: "transcript",
+            status: check_transcript_api(config).await,
+        },
+        HealthCheck {
+            service: "ai",
+            status: check_ai(config).await,
+        },
+        HealthCheck {
+            service: "discord",
+            status: check_discord(config).await,
+        },
+    ];
+
+    for check in &checks {
+        info!("Healthcheck {}: {:?}", check.service, check.status);
+    }
+    Ok(HealthReport { checks })
+}
+
+/// Render a report as a plain-text table
+pub fn format_health_report(report: &HealthReport) -> String {
+    let width = report
+        .checks
+        .iter()
+        .map(|check| check.service.len())
+        .chain(std::iter::once("SERVICE".len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = vec![format!("{:<width$}  STATUS", "SERVICE")];
+    for check in &report.checks {
+        let status = match &check.status {
+            HealthStatus::Ok => "ok".to_string(),
+            HealthStatus::Fail(reason) => format!("fail: {}", reason),
+        };
+        lines.push(format!("{:<width$}  {}", check.service, status));
+    }
+    lines.join("\n")
+}
+
+// One-result search per configured channel; validates the key and the channel IDs
+async fn check_youtube(config: &Config, search_url: &str) -> HealthStatus {
+    if config.youtube_api_key.trim().is_empty() {
+        return HealthStatus::Fail("YOUTUBE_API_KEY is empty".to_string());
+    }
+
+    let failed: Vec<String> = check_channels_at(config, search_url)
+        .await
+        .into_iter()
+        .filter_map(|(channel_id, status)| match status {
+            ChannelStatus::Error { message, .. } => Some(format!("{}: {}", channel_id, message)),
+            _ => None,
+        })
+        .collect();
+    if failed.is_empty() {
+        HealthStatus::Ok
+    } else {
+        HealthStatus::Fail(failed.join("; "))
+    }
+}
+
+// Call the transcript endpoint without a video URL. The API answers that with a
+// 4xx validation error, so only a rejected key or a server error counts as failure.
+async fn check_transcript_api(config: &Config) -> HealthStatus {
+    if config.supabase_api_key.trim().is_empty() {
+        return HealthStatus::Fail("SUPABASE_API_KEY is empty".to_string());
+    }
+
+    let request = http_client::with_request_id(http_client::client().get(config.transcript_endpoint()))
+        .header("x-api-key", &config.supabase_api_key);
+    match probe(request).await {
+        Ok(_) => HealthStatus::Ok,
+        Err((Some(status), _)) if status.is_client_error() && !is_auth_error(status) => HealthStatus::Ok,
+        Err((_, reason)) => HealthStatus::Fail(reason),
+    }
+}
+
+// Send a one-word chat with the configured persona (or model, for OpenAI)
+async fn check_ai(config: &Config) -> HealthStatus {
+    let client = http_client::client();
+    let ping = json!({ "role": "user", "content": "ping" });
+    let request = match config.ai_backend {
+        AiBackend::Custom => {
+            let body = json!({
+                "persona": config.ai_persona,
+                "user_id": config.ai_user_id,
+                "messages": [ping]
+            });
+            ai_request(client, &config.my_ai_api_url, &config.my_ai_api_key, &body)
+        }
+        AiBackend::OpenAi => {
+            let body = openai_body(config, vec![ping]);
+            openai_request(client, &config.my_ai_api_url, &config.my_ai_api_key, &body)
+        }
+    };
+    match probe(request).await {
+        Ok(_) => HealthStatus::Ok,
+        Err((_, reason)) => HealthStatus::Fail(reason),
+    }
+}
+
+// GET on a webhook returns its details when the URL and token are valid
+async fn check_discord(config: &Config) -> HealthStatus {
+    if config.discord_targets.is_empty() {
+        return HealthStatus::Fail("no Discord webhook configured".to_string());
+    }
+
+    let mut failed = Vec::new();
+    for target in &config.discord_targets {
+        let url = normalize_webhook_url(&target.url);
+        let request = http_client::with_request_id(http_client::client().get(&url));
+        if let Err((_, reason)) = probe(request).await {
+            failed.push(format!("{}: {}", mask_webhook_url(&url), reason));
+        }
+    }
+    if failed.is_empty() {
+        HealthStatus::Ok
+    } else {
+        HealthStatus::Fail(failed.join("; "))
+    }
+}
+
+// `/generate_204` answers an empty 204; a captive portal or proxy answers
+// something else, so only that exact status counts as connected
+#[cfg(not(feature = "demo"))]
+async fn selftest_network_at(base_url: &str) -> Result<()> {
+    let url = format!("{}/generate_204", base_url.trim_end_matches('/'));
+    let response = http_client::client()
+        .get(&url)
+        .timeout(HEALTHCHECK_TIMEOUT)
+        .send()
+        .await?;
+    let status = response.status();
+    if status != StatusCode::NO_CONTENT {
+        return Err(crate::error::AppError::InvalidResponse(format!(
+            "network self-test expected 204 from {}, got {}",
+            url, status
+        )));
+    }
+    Ok(())
+}
+
+fn is_auth_error(status: StatusCode) -> bool {
+    matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+}
+
+/// Send a probe; on failure return the status (if any) and a readable reason
+async fn probe(request: RequestBuilder) -> std::result::Result<StatusCode, (Option<StatusCode>, String)> {
+    let response = request
+        .timeout(HEALTHCHECK_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| (None, format!("request error: {}", e)))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(status);
+    }
+    let body = response.text().await.unwrap_or_default();
+    let body: String = body.trim().chars().take(MAX_FAILURE_BODY_CHARS).collect();
+    Err((Some(status), format!("{} {}", status, body).trim_end().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::discord::DiscordTarget;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_healthcheck_aggregates_failures() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_search.json")))
+            .mount(&server)
+            .await;
+        // No video URL: a validation error means the key was accepted
+        Mock::given(method("GET"))
+            .and(path("/v1/transcript"))
+            .respond_with(ResponseTemplate::new(400).set_body_string(r#"{"error":"invalid-request"}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/ai"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("maintenance"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/webhooks/1/token"))
+            .respond_with(ResponseTemplate::new(401).set_body_string(r#"{"message":"Invalid Webhook Token"}"#))
+            .mount(&server)
+            .await;
+
+        let config = Config::builder()
+            .ksforword_channel_id("UC123")
+            .youtube_api_key("yt-key")
+            .supabase_api_key("sd-key")
+            .supadata_base_url(server.uri())
+            .my_ai_api_url(format!("{}/ai", server.uri()))
+            .discord_targets(vec![DiscordTarget::from_url(format!("{}/webhooks/1/token", server.uri()))])
+            .build();
+        let report = run_healthcheck_at(&config, &format!("{}/search", server.uri()), &server.uri())
+            .await
+            .unwrap();
+
+        let status = |service: &str| {
+            report
+                .checks
+                .iter()
+                .find(|check| check.service == service)
+                .map(|check| check.status.clone())
+                .unwrap()
+        };
+        assert_eq!(status("youtube"), HealthStatus::Ok);
+        assert!(matches!(status("network"), HealthStatus::Fail(_)));
+        assert_eq!(status("transcript"), HealthStatus::Ok);
+        assert_eq!(status("ai"), HealthStatus::Fail("503 Service Unavailable maintenance".to_string()));
+        match status("discord") {
+            HealthStatus::Fail(reason) => {
+                assert!(reason.contains("401 Unauthorized"), "{}", reason);
+                assert!(!reason.contains("/token"), "webhook token leaked: {}", reason);
+            }
+            other => panic!("expected discord failure, got {:?}", other),
+        }
+        assert_eq!(report.failures(), 3);
+        assert!(!report.is_healthy());
+    }
+
+    #[cfg(not(feature = "demo"))]
+    #[tokio::test]
+    async fn test_selftest_expects_no_content() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/generate_204"))
+            .respond_with(ResponseTemplate::new(204))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/generate_204"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html>Sign in to Wi-Fi</html>"))
+            .mount(&server)
+            .await;
+
+        selftest_network_at(&server.uri()).await.unwrap();
+        let err = selftest_network_at(&server.uri()).await.unwrap_err();
+        assert_eq!(err.category(), "parse");
+        assert!(err.to_string().contains("got 200 OK"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_missing_keys_fail_without_requests() {
+        let config = Config::builder().build();
+        assert!(matches!(check_youtube(&config, "http://127.0.0.1:9").await, HealthStatus::Fail(_)));
+        assert!(matches!(check_transcript_api(&config).await, HealthStatus::Fail(_)));
+        assert_eq!(
+            check_discord(&config).await,
+            HealthStatus::Fail("no Discord webhook configured".to_string())
+        );
+    }
+}
@@ -1,8 +1,23 @@
 //! Optimized HTTP client with connection pooling and performance improvements
-use crate::error::{AppError, Result};
+use crate::config::Config;
+use crate::utils::redact::{redact, truncate_chars};
 use once_cell::sync::Lazy;
-use reqwest::{Client, ClientBuilder};
-use std::time::Duration;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, ClientBuilder, RequestBuilder, Response, StatusCode};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::{debug, enabled, trace, Level};
+
+/// Maximum characters of a body written to the trace log
+const MAX_LOGGED_BODY_CHARS: usize = 2000;
+
+/// Header carrying the per-video correlation id on outbound requests
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+tokio::task_local! {
+    /// Correlation id of the video currently being processed
+    static REQUEST_ID: String;
+}
 
 /// Global HTTP client instance with optimized settings
 static HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
@@ -25,32 +40,155 @@ pub fn client() -> &'static Client {
     &HTTP_CLIENT
 }
 
-/// Build a custom client with specific timeout settings
-pub fn build_client(timeout_secs: u64) -> Result<Client> {
-    ClientBuilder::new()
-        .pool_max_idle_per_host(5)
-        .pool_idle_timeout(Duration::from_secs(60))
-        .connect_timeout(Duration::from_secs(5))
-        .timeout(Duration::from_secs(timeout_secs))
-        .tcp_keepalive(Duration::from_secs(30))
-        .tcp_nodelay(true)
-        .build()
-        .map_err(|e| AppError::Internal(format!("Failed to build client: {}", e)))
+/// Generate a fresh correlation id
+pub fn new_request_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Run `fut` with `request_id` as the current correlation id
+pub async fn scope_request_id<F: Future>(request_id: String, fut: F) -> F::Output {
+    REQUEST_ID.scope(request_id, fut).await
+}
+
+/// Correlation id of the current scope, if any
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Attach the current correlation id (if any) as an `X-Request-Id` header
+pub fn with_request_id(builder: RequestBuilder) -> RequestBuilder {
+    match current_request_id() {
+        Some(id) => builder.header(REQUEST_ID_HEADER, id),
+        None => builder,
+    }
+}
+
+/// Await an outbound request and log at debug level how long it took, e.g.
+/// "youtube search took 412ms (status 200)", or "(no response)" when it failed
+/// before a response arrived
+pub async fn timed_send<F>(call: &str, send: F) -> reqwest::Result<Response>
+where
+    F: Future<Output = reqwest::Result<Response>>,
+{
+    let started = Instant::now();
+    let response = send.await;
+    let millis = started.elapsed().as_millis();
+    match &response {
+        Ok(response) => debug!("{} took {}ms (status {})", call, millis, response.status().as_u16()),
+        Err(_) => debug!("{} took {}ms (no response)", call, millis),
+    }
+    response
+}
+
+/// Whether request/response bodies should be logged for this run
+fn http_tracing_enabled(config: &Config) -> bool {
+    config.log_http_bodies && enabled!(Level::TRACE)
+}
+
+/// Redact secrets from a body and truncate it for logging
+pub fn loggable_body(config: &Config, body: &str) -> String {
+    truncate_chars(&redact(body, &config.secrets()), MAX_LOGGED_BODY_CHARS)
+}
+
+/// Log an outgoing request at trace level when `LOG_HTTP_BODIES` is enabled
+pub fn trace_request(config: &Config, method: &str, url: &str, body: Option<&str>) {
+    if !http_tracing_enabled(config) {
+        return;
+    }
+
+    trace!("→ {} {}", method, redact(url, &config.secrets()));
+    if let Some(body) = body {
+        trace!("  request body: {}", loggable_body(config, body));
+    }
+}
+
+/// Log a response at trace level when `LOG_HTTP_BODIES` is enabled
+pub fn trace_response(config: &Config, url: &str, status: StatusCode, headers: &HeaderMap, body: &str) {
+    if !http_tracing_enabled(config) {
+        return;
+    }
+
+    let secrets = config.secrets();
+    trace!("← {} {}", status, redact(url, &secrets));
+    trace!("  response headers: {}", redact(&format!("{:?}", headers), &secrets));
+    trace!("  response body: {}", loggable_body(config, body));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::discord::DiscordTarget;
+    use wiremock::matchers::any;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[tokio::test]
     async fn test_client_instance() {
-        let client = client();
-        assert!(client.timeout().is_some()); // Verify client is configured
+        // The global client is built once and shared
+        assert!(std::ptr::eq(client(), client()));
+    }
+
+    #[test]
+    fn test_loggable_body_redacts_secrets() {
+        let config = Config {
+            my_ai_api_key: "sk-live-0123456789".to_string(),
+            discord_targets: vec![DiscordTarget::from_url(
+                "https://discord.com/api/webhooks/123/hook-token-xyz",
+            )],
+            ..Default::default()
+        };
+        let body = r#"{"api_key":"sk-live-0123456789","webhook":"https://discord.com/api/webhooks/123/hook-token-xyz"}"#;
+
+        let logged = loggable_body(&config, body);
+        assert!(!logged.contains("sk-live-0123456789"));
+        assert!(!logged.contains("hook-token-xyz"));
+        assert_eq!(
+            logged,
+            r#"{"api_key":"***","webhook":"https://discord.com/api/webhooks/123/***"}"#
+        );
+    }
+
+    #[test]
+    fn test_loggable_body_truncates_large_bodies() {
+        let body = "x".repeat(MAX_LOGGED_BODY_CHARS + 10);
+        let logged = loggable_body(&Config::default(), &body);
+        assert!(logged.ends_with("… (10 more chars)"));
     }
 
     #[tokio::test]
-    async fn test_build_client() {
-        let client = build_client(30).unwrap();
-        assert!(client.timeout().is_some());
+    async fn test_request_id_shared_within_scope() {
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let request_id = new_request_id();
+        scope_request_id(request_id.clone(), async {
+            with_request_id(client().get(format!("{}/search", server.uri())))
+                .send()
+                .await
+                .unwrap();
+            with_request_id(client().post(format!("{}/chat", server.uri())))
+                .send()
+                .await
+                .unwrap();
+        })
+        .await;
+
+        // Outside the scope no header is attached
+        with_request_id(client().get(format!("{}/webhook", server.uri())))
+            .send()
+            .await
+            .unwrap();
+
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(received.len(), 3);
+        for req in &received[..2] {
+            assert_eq!(
+                req.headers.get(REQUEST_ID_HEADER).unwrap().to_str().unwrap(),
+                request_id
+            );
+        }
+        assert!(received[2].headers.get(REQUEST_ID_HEADER).is_none());
     }
 }
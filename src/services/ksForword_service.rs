@@ -1,186 +1,2728 @@
-use crate::config::Config;
-use crate::models::youtube_transcript::Root as TranscriptRoot;
-use crate::services::supabase_service::get_youtube_transcript;
+use crate::config::{
+    CatchUpOrder, Config, LowQualityAction, PartialTranscriptAction, Summarizer, TranscriptJoin,
+    DEFAULT_PUBLISHED_DATE_FORMAT,
+};
+use crate::error::AppError;
+use crate::models::youtube_snippet::{Item, Snippet};
+use crate::models::myAI_response::SummaryPair;
+use crate::models::run_report::RunReport;
+use crate::models::youtube_transcript::{Root as TranscriptRoot, TranscriptStats};
+use crate::services::dedup_service::DedupState;
+use crate::services::state_service::SeenState;
+use crate::services::discord_service::{EmbedMetadata, MessageSection};
+use crate::services::app::App;
+use crate::services::http_client;
+use crate::utils::{extractive, markdown, metrics, sponsor_filter, text_cleanup};
+use crate::utils::redact::size_label;
 use crate::{
     models::youtube_snippet::SearchResult, services::youtube_service::get_detail_byLink,
-    services::youtube_service::get_youtube_search,
+    services::youtube_service::get_youtube_search_all,
+    services::youtube_service::get_recent_videos, services::youtube_service::get_video_details,
+    services::youtube_service::resolve_channel_id, services::youtube_service::VideoDetails,
+    services::youtube_service::get_playlist_items,
 };
+use chrono::{Datelike, Local};
+use futures::channel::mpsc;
+use futures::future::{self, Future, FutureExt};
+use futures::stream::{self, Stream, StreamExt};
+use std::fmt::Write;
+use std::pin::pin;
 use tokio::fs;
+use tokio::time::Duration;
+use tracing::{debug, info, info_span, warn, Instrument};
+
+/// What a run of `get_lastest_ksForword` ended up doing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// A video was summarized and posted
+    Processed,
+    /// A matching video was found but its transcript was empty
+    EmptyTranscript,
+    /// Matching videos exist, but all of them were already processed
+    #[default]
+    NoNewVideo,
+    /// The search returned no video whose title matched
+    NoMatches,
+    /// The transcript looked like mostly non-speech and summarization was skipped
+    LowQualitySkipped,
+    /// The transcript nearly matched a recently processed video
+    DuplicateSkipped,
+    /// The transcript was shorter than `skip_if_transcript_shorter_than`
+    TooShortSkipped,
+    /// The transcript mentioned none of `require_keywords`
+    MissingKeywordsSkipped,
+    /// Processing exceeded `per_video_budget_secs` and the video was abandoned
+    TimedOut,
+}
+
+/// Result of processing: `Processed` with the video's details, or the reason
+/// nothing was summarized (any other `RunOutcome`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessOutcome {
+    pub outcome: RunOutcome,
+    /// Video that was processed or skipped; `None` when nothing matched
+    pub video_id: Option<String>,
+    pub title: Option<String>,
+    /// Transcript length in chars, as sent to the summarizer
+    pub transcript_chars: usize,
+    /// Length in chars of the summary (all languages combined)
+    pub answer_chars: usize,
+    /// Whether the summary was posted to Discord (false on a dry run)
+    pub posted: bool,
+}
+
+impl ProcessOutcome {
+    /// An outcome for a run that summarized nothing, for the given reason
+    pub fn skipped(outcome: RunOutcome) -> Self {
+        Self {
+            outcome,
+            ..Default::default()
+        }
+    }
+}
+
+/// How a transcript should be summarized, based on its quality
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SummaryPlan {
+    /// Use the normal summary prompt(s)
+    Normal,
+    /// Use the low-quality fallback prompt
+    Fallback,
+    /// Do not summarize
+    Skip,
+}
+
+// Function to get the latest KS Forward video of each configured channel, process its transcript, chat with AI, and send to Discord
+pub async fn get_lastest_ksForword(app: &App) -> Result<ProcessOutcome, Box<dyn std::error::Error>> {
+    let config = app.config();
+    // A fresh install is detected once, before the first channel writes the state file
+    let first_run = match &config.state_path {
+        Some(path) => SeenState::load(path).await?.is_none(),
+        None => false,
+    };
+
+    // A failing channel is reported after the others have had their turn
+    let mut outcome = ProcessOutcome::skipped(RunOutcome::NoMatches);
+    let mut errors = BatchErrors::default();
+    for channel_id in config.polled_channel_ids() {
+        let result = latest_for_channel(app, &channel_id, first_run).await;
+        let Some(result) = errors.check_labelled(format_args!("Channel {}", channel_id), result) else {
+            continue;
+        };
+        match result.outcome {
+            RunOutcome::NoMatches => info!("No matching video on channel {}; continuing.", channel_id),
+            RunOutcome::NoNewVideo => info!("Nothing new on channel {}; continuing.", channel_id),
+            _ => {}
+        }
+        outcome = merge_outcome(outcome, result);
+    }
+    let outcome = errors.finish(outcome)?;
+
+    if matches!(outcome.outcome, RunOutcome::NoNewVideo | RunOutcome::NoMatches) {
+        return report_no_new_video(app, outcome.outcome).await;
+    }
+    Ok(outcome)
+}
 
-// Function to get the latest KS Forward video, process its transcript, chat with AI, and send to Discord
-pub async fn get_lastest_ksForword(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
-    let ks_channel_id = &config.ksforword_channel_id;
+// Combine per-channel results: a processed video wins, then any other result
+// about a video (skips, timeouts), then "nothing new", then "no matches"
+fn merge_outcome(current: ProcessOutcome, result: ProcessOutcome) -> ProcessOutcome {
+    let quiet_rank = |outcome: RunOutcome| match outcome {
+        RunOutcome::NoMatches => 0,
+        RunOutcome::NoNewVideo => 1,
+        _ => 2,
+    };
+    if current.outcome == RunOutcome::Processed || quiet_rank(result.outcome) < quiet_rank(current.outcome) {
+        current
+    } else {
+        result
+    }
+}
 
-    let resYoutube = get_youtube_search(&ks_channel_id).await?;
-    let filtered: Vec<_> = resYoutube
-        .items
+// Find and process the latest matching video(s) of one channel. Returns
+// `NoMatches` or `NoNewVideo` without notifying when nothing was processed.
+async fn latest_for_channel(
+    app: &App,
+    channel_id: &str,
+    first_run: bool,
+) -> Result<ProcessOutcome, Box<dyn std::error::Error>> {
+    let config = app.config();
+    // KSFORWORD_CHANNEL_ID may hold an @handle instead of a raw channel id
+    let channel_id = if channel_id.starts_with('@') {
+        let resolved = resolve_channel_id(config, channel_id).await?;
+        info!("Resolved {} to channel id {}", channel_id, resolved);
+        resolved
+    } else {
+        channel_id.to_string()
+    };
+    let videos = match config.since {
+        // Page back as far as `since` instead of reading one page
+        Some(since) => get_youtube_search_all(config, &channel_id, Some(since), None)
+            .await?
+            .iter()
+            .filter_map(SearchResult::from_item)
+            .collect(),
+        None => {
+            // A full page when several episodes are wanted; search quota cost is the same
+            let max_results = if config.latest_count > 1 { LATEST_SEARCH_RESULTS } else { 0 };
+            app.youtube_search(&channel_id, max_results).await?
+        }
+    };
+    metrics::record(|m| m.channels_scanned += 1);
+    let current_year = Local::now().year();
+    let filtered: Vec<_> = videos
         .iter()
-        .filter(|item| {
-            if let Some(title) = &item.snippet.title {
-                title.starts_with("KS Forward")
-            } else {
-                false
+        .filter(|video| {
+            is_matching_title(config, &video.title, current_year) && is_published_since(video, config.since)
+        })
+        .collect();
+    metrics::record(|m| m.videos_found += filtered.len());
+
+    if let Some(state_path) = &config.state_path {
+        return catch_up(app, state_path, &filtered, first_run).await;
+    }
+
+    let processed_path = seen_path(config);
+    let mut processed = SeenState::load(&processed_path).await?.unwrap_or_default();
+
+    if filtered.is_empty() {
+        return Ok(ProcessOutcome::skipped(RunOutcome::NoMatches));
+    }
+
+    let selected: Vec<&SearchResult> = page_selection(config, &filtered)
+        .into_iter()
+        .filter(|video| {
+            let skip = skip_processed(config, &processed, &video.video_id);
+            if skip {
+                info!("Video {} already processed; skipping.", video.video_id);
             }
+            !skip
         })
         .collect();
 
-    let lastest = filtered.first();
+    let mut outcome = ProcessOutcome::default();
+    let mut errors = BatchErrors::default();
+    let mut results = pin!(process_items(app, selected));
+    while let Some((video, result)) = results.next().await {
+        let Some(result) = errors.check(video, result) else {
+            continue;
+        };
+        if records_seen(config, &result) {
+            processed.mark(&video.video_id);
+            processed.save(&processed_path).await?;
+        }
+        if outcome.outcome != RunOutcome::Processed {
+            outcome = result;
+        }
+    }
+    errors.finish(outcome)
+}
+
+// Whether a handled video goes on the seen list. Skipped videos (empty, short,
+// off-keyword, duplicate transcripts) are recorded like posted ones so they are
+// not fetched again; a failed or timed-out video stays unseen so the next run
+// retries it, and a dry run does not record anything.
+fn records_seen(config: &Config, result: &ProcessOutcome) -> bool {
+    result.outcome != RunOutcome::TimedOut && !config.dry_run
+}
 
-    if let Some(item) = lastest {
-        let video_id = item
-            .id
-            .as_video_id()
-            .unwrap_or_else(|| "No video id found".to_string());
+/// Processed-video list kept under `CACHE_DIR` when no `STATE_PATH` is used
+const PROCESSED_VIDEOS_FILE: &str = "processed_videos.json";
 
-        println!("video id: {}", video_id);
+// The one seen list every command reads and records: `STATE_PATH`, or
+// `CACHE_DIR/processed_videos.json` without it
+fn seen_path(config: &Config) -> String {
+    match &config.state_path {
+        Some(path) => path.clone(),
+        None => std::path::Path::new(&config.cache_dir)
+            .join(PROCESSED_VIDEOS_FILE)
+            .to_string_lossy()
+            .into_owned(),
+    }
+}
 
-        let mapped = SearchResult {
-            video_id: item.id.as_video_id().unwrap_or_default(),
-            link: format!("https://www.youtube.com/watch?v={}", video_id),
-            title: item.snippet.title.clone().unwrap_or_default(),
-            publish_time: item.snippet.publish_time.clone().unwrap_or_default(),
+// Whether a video was already posted and should not be summarized again,
+// unless `FORCE_REPROCESS` is set
+fn skip_processed(config: &Config, processed: &SeenState, video_id: &str) -> bool {
+    !config.force_reprocess && processed.contains(video_id)
+}
+
+/// Search results requested when `latest_count` asks for more than one video
+const LATEST_SEARCH_RESULTS: u8 = 50;
+
+// Matching videos from this search page to process when no state file is used:
+// the newest `latest_count` by publish time, or with `process_all_matching` or
+// `since` every match (newest first, up to the catch-up cap)
+fn page_selection<'a>(config: &Config, filtered: &[&'a SearchResult]) -> Vec<&'a SearchResult> {
+    if !config.process_all_matching && config.since.is_none() {
+        let mut newest = filtered.to_vec();
+        sort_newest_first(&mut newest);
+        return newest.into_iter().take(config.latest_count.max(1)).collect();
+    }
+    select_catch_up(config, filtered, Some(&SeenState::default()))
+}
+
+// Whether a video was published after `since`. With a cutoff set, a video
+// without a parseable publish time is excluded.
+fn is_published_since(video: &SearchResult, since: Option<chrono::DateTime<chrono::Utc>>) -> bool {
+    match since {
+        Some(since) => video.snippet.published().is_some_and(|published| published > since),
+        None => true,
+    }
+}
+
+// Order by publish time, newest first, without trusting the API's `order=date`.
+// Videos with a missing or unparseable timestamp sort last, in search order.
+fn sort_newest_first(videos: &mut [&SearchResult]) {
+    videos.sort_by_key(|video| std::cmp::Reverse(video.snippet.published()));
+}
+
+/// Summarize every available video of a playlist, in playlist order. Titles
+/// are not filtered; private or deleted entries, and with `SINCE` older ones,
+/// are skipped.
+///
+/// Videos on the seen list (`STATE_PATH`, or `CACHE_DIR/processed_videos.json`)
+/// are skipped unless `FORCE_REPROCESS` is set, and each handled video is
+/// recorded. Returns how many videos were processed and how many were skipped.
+pub async fn summarize_playlist(
+    app: &App,
+    playlist_id: &str,
+) -> Result<PlaylistOutcome, Box<dyn std::error::Error>> {
+    let config = app.config();
+    let mut videos = get_playlist_items(config, playlist_id).await?;
+    videos.retain(|video| is_published_since(video, config.since));
+    info!("Playlist {}: {} available video(s)", playlist_id, videos.len());
+    metrics::record(|m| m.videos_found += videos.len());
+
+    let seen_path = seen_path(config);
+    let mut seen = SeenState::load(&seen_path).await?.unwrap_or_default();
+
+    let unseen: Vec<&SearchResult> = videos
+        .iter()
+        .filter(|video| {
+            let skip = skip_processed(config, &seen, &video.video_id);
+            if skip {
+                info!("Video {} already processed; skipping.", video.video_id);
+            }
+            !skip
+        })
+        .collect();
+
+    let mut outcome = PlaylistOutcome::default();
+    let mut errors = BatchErrors::default();
+    let mut results = pin!(process_items(app, unseen));
+    while let Some((video, result)) = results.next().await {
+        let Some(result) = errors.check(video, result) else {
+            continue;
         };
+        match result.outcome {
+            RunOutcome::Processed => outcome.processed += 1,
+            RunOutcome::TimedOut => {}
+            _ => outcome.skipped += 1,
+        }
+        if records_seen(config, &result) {
+            seen.mark(&video.video_id);
+            seen.save(&seen_path).await?;
+        }
+    }
+    errors.finish(outcome)
+}
+
+/// Videos `summarize_playlist` summarized, and those it skipped (empty, short,
+/// off-keyword, duplicate or low-quality transcripts)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlaylistOutcome {
+    pub processed: usize,
+    pub skipped: usize,
+}
+
+/// Combined transcript size (chars) for one comparative AI call. Above it each
+/// transcript is summarized on its own first and the summaries are compared.
+const COMPARE_CONTEXT_CHARS: usize = 60_000;
 
-        println!("Found KS Forward Video: {}", mapped.title);
+/// Search results scanned for matching videos by `compare_recent`
+const COMPARE_SEARCH_RESULTS: usize = 50;
+
+/// Instruction heading a comparative prompt
+const COMPARE_PROMPT: &str = "Compare the following episodes. Briefly summarize each one, \
+then describe recurring themes, what changed between episodes, and notable differences.";
+
+/// One video's text gathered for a comparative summary
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompareInput {
+    pub title: String,
+    pub published: Option<String>,
+    pub text: String,
+}
+
+/// Summarize the last `last` matching videos of a channel into one comparative
+/// post. Returns the posted summary.
+pub async fn compare_recent(
+    app: &App,
+    channel_id: &str,
+    last: usize,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let config = app.config();
+    let items = get_recent_videos(config, channel_id, COMPARE_SEARCH_RESULTS).await?;
+    let current_year = Local::now().year();
+    let mut matching: Vec<&Item> = items
+        .iter()
+        .filter(|item| {
+            item.snippet
+                .title
+                .as_deref()
+                .is_some_and(|title| is_matching_title(config, title, current_year))
+        })
+        .collect();
+    matching.sort_by_key(|item| std::cmp::Reverse(item.snippet.published()));
+    matching.truncate(last);
+    if matching.len() < 2 {
+        return Err(format!("need at least two matching videos to compare, found {}", matching.len()).into());
+    }
+    // Oldest first so the comparison reads chronologically
+    matching.reverse();
 
-        // Get mock transcript and parse
-        let use_mock_data = config.use_mock_data;
-        let transcript_json = if use_mock_data {
+    let mut inputs = Vec::with_capacity(matching.len());
+    for item in matching {
+        let video_id = item.id.as_video_id().unwrap_or_default();
+        let transcript_json = if config.use_mock_data {
             dummy_transcript().await?
         } else {
-            get_youtube_transcript(&mapped.link).await?
+            app.get_transcript(&format!("https://www.youtube.com/watch?v={}", video_id)).await?
         };
-        print!("Transcript fetched.");
+        let text = transcript_text(config, &transcript_json);
+        inputs.push(CompareInput {
+            title: item.snippet.title.clone().unwrap_or_default(),
+            published: format_published(&item.snippet, &config.published_date_format),
+            text: filter_sponsors(config, text),
+        });
+    }
 
-        let full_transcript = parse_transcript_fullscript(transcript_json).await?;
-        println!("Full Transcript length: {}", full_transcript.len());
+    let inputs = fit_compare_budget(inputs, COMPARE_CONTEXT_CHARS, |text| summarize_transcript(app, text)).await?;
+    let summary = summarize_transcript(app, build_compare_prompt(&inputs)).await?;
 
-        if full_transcript != "" && full_transcript.len() > 0 {
-            println!("Transcript successfully retrieved and parsed.");
+    let title = format!("KS Forward: last {} episodes compared", inputs.len());
+    if config.dry_run {
+        info!("🧪 DRY_RUN: not posting to Discord.\n{}", summary);
+    } else {
+        app.send_discord(&title, &summary, &EmbedMetadata::default()).await?;
+    }
+    Ok(summary)
+}
 
-            //chat with AI
-            let ai_response =
-                crate::services::myAI_service::chat_with_ai(config, full_transcript).await?;
-            let ai_answer = ai_response.answer;
+// Map step: when the transcripts together exceed `budget` chars, replace each
+// one with its own summary so the comparison fits in one call
+async fn fit_compare_budget<F, Fut, E>(
+    inputs: Vec<CompareInput>,
+    budget: usize,
+    mut summarize: F,
+) -> Result<Vec<CompareInput>, E>
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = Result<String, E>>,
+{
+    let total: usize = inputs.iter().map(|input| input.text.chars().count()).sum();
+    if total <= budget {
+        return Ok(inputs);
+    }
 
-            // Detailed length logging
-            let byte_len = ai_answer.len();
-            let char_len = ai_answer.chars().count();
-            println!("AI Answer byte length: {}", byte_len);
-            println!("AI Answer char length: {}", char_len);
-            println!(
-                "AI Answer first 200 chars: {}",
-                &ai_answer.chars().take(200).collect::<String>()
-            );
+    info!("Transcripts total {} chars (> {}); summarizing each first.", total, budget);
+    let mut mapped = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let text = summarize(input.text).await?;
+        mapped.push(CompareInput { text, ..input });
+    }
+    Ok(mapped)
+}
 
-            // Check if message is too long for Discord (>5500 chars)
-            const DISCORD_MAX_LENGTH: usize = 5500;
-            println!("Checking if message exceeds Discord limit of {} chars...", DISCORD_MAX_LENGTH);
-
-            let final_message = if char_len > DISCORD_MAX_LENGTH {
-                println!("⚠️  AI response is too long for Discord ({} chars)", char_len);
-                println!("📤 Sending to AI for summarization with 'ks-discord' persona...");
-                let discord_response =
-                    crate::services::myAI_service::chat_with_ai_msg4Discord(config, ai_answer)
-                        .await?;
-                let summary = discord_response.answer;
-                println!("✅ Summarized to {} chars", summary.chars().count());
-                summary
-            } else {
-                println!("✓ AI response length is within Discord limit ({} chars)", char_len);
-                ai_answer
-            };
+// Reduce step prompt: the instruction followed by one delimited block per video
+fn build_compare_prompt(inputs: &[CompareInput]) -> String {
+    let mut prompt = String::from(COMPARE_PROMPT);
+    for (index, input) in inputs.iter().enumerate() {
+        let number = index + 1;
+        let heading = match &input.published {
+            Some(published) => format!("{} ({})", input.title, published),
+            None => input.title.clone(),
+        };
+        prompt.push_str(&format!(
+            "\n\n=== Video {}: {} ===\n{}\n=== End of video {} ===",
+            number,
+            heading,
+            input.text.trim(),
+            number
+        ));
+    }
+    prompt
+}
 
-            // send to discord
-            crate::services::discord_service::send_message(&mapped.title, &final_message).await?;
-            println!("Message sent to Discord.");
-            println!("KS Forward processing completed.");
-        } else {
-            println!("Transcript is empty.");
+// Process every unseen matching video (up to the catch-up cap), recording each
+// handled video in the state file so later runs skip it. `first_run` forces
+// first-run limits when an earlier channel already created the state file.
+async fn catch_up(
+    app: &App,
+    state_path: &str,
+    filtered: &[&SearchResult],
+    first_run: bool,
+) -> Result<ProcessOutcome, Box<dyn std::error::Error>> {
+    let config = app.config();
+    let loaded = SeenState::load(state_path).await?;
+    let first_run = first_run || loaded.is_none();
+    if first_run {
+        info!(
+            "🆕 First run: no state file at {}; processing at most {} video(s).",
+            state_path, config.first_run_limit
+        );
+    }
+
+    let selected = select_catch_up(config, filtered, loaded.as_ref().filter(|_| !first_run));
+    let mut state = loaded.unwrap_or_default();
+    if first_run {
+        // Older backlog on a fresh install is treated as already seen so the
+        // next run does not post it either
+        let backlog: Vec<String> = filtered
+            .iter()
+            .filter(|video| !selected.iter().any(|s| std::ptr::eq(*s, **video)))
+            .map(|video| video.video_id.clone())
+            .collect();
+        info!("🆕 Marking {} older matching video(s) as seen.", backlog.len());
+        for video_id in &backlog {
+            state.mark(video_id);
         }
-    } else {
-        println!("No found data :  KS Forward");
+    }
+    if selected.is_empty() {
+        if !config.dry_run {
+            state.save(state_path).await?;
+        }
+        let quiet = if filtered.is_empty() { RunOutcome::NoMatches } else { RunOutcome::NoNewVideo };
+        return Ok(ProcessOutcome::skipped(quiet));
     }
 
-    Ok(())
+    info!("Catching up on {} unseen video(s).", selected.len());
+    let mut outcome = ProcessOutcome::default();
+    let mut errors = BatchErrors::default();
+    let mut results = pin!(process_items(app, selected));
+    while let Some((video, result)) = results.next().await {
+        let Some(result) = errors.check(video, result) else {
+            continue;
+        };
+        if records_seen(config, &result) {
+            state.mark(&video.video_id);
+            state.save(state_path).await?;
+        }
+        if outcome.outcome != RunOutcome::Processed {
+            outcome = result;
+        }
+    }
+    errors.finish(outcome)
 }
 
-// Function to get summary link from video link
-pub async fn get_summary_link(
+// Unseen matching videos to process this run, in `catch_up_order`; with
+// `FORCE_REPROCESS` seen ones count as unseen. Without a state file (`None`)
+// only the latest `first_run_limit` videos are taken so a fresh install does
+// not flood the channel with the whole backlog.
+fn select_catch_up<'a>(
     config: &Config,
-    video_link: &str,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let detail = get_detail_byLink(video_link).await?;
-    if detail.items.is_empty() {
-        return Err("No video details found for the provided link".into());
+    filtered: &[&'a SearchResult],
+    state: Option<&SeenState>,
+) -> Vec<&'a SearchResult> {
+    let mut unseen: Vec<&SearchResult> = filtered
+        .iter()
+        .copied()
+        .filter(|video| match state {
+            Some(state) => !skip_processed(config, state, &video.video_id),
+            None => true,
+        })
+        .collect();
+    sort_newest_first(&mut unseen);
+
+    let mut selected: Vec<&SearchResult> = match (state, config.catch_up_order) {
+        (None, _) => unseen.into_iter().take(config.first_run_limit).collect(),
+        (Some(_), CatchUpOrder::NewestFirst) => unseen.into_iter().take(config.catch_up_limit).collect(),
+        (Some(_), CatchUpOrder::OldestFirst) => unseen.into_iter().rev().take(config.catch_up_limit).collect(),
+    };
+    if state.is_none() && config.catch_up_order == CatchUpOrder::OldestFirst {
+        selected.reverse();
     }
+    selected
+}
 
-    print!(
-        "Video Title: {}",
-        detail.items[0].snippet.title.clone().unwrap_or_default()
-    );
+/// Result of one video in a batch, paired with the video it came from
+type BatchResult<'a> = (&'a SearchResult, Result<ProcessOutcome, Box<dyn std::error::Error>>);
 
-    let transcript_json = get_youtube_transcript(video_link).await?;
-    print!("Transcript JSON fetched.");
+// Summarize `videos` and post them in the given order. Up to `max_concurrency`
+// videos fetch their transcript and call the AI at once; posting is serialized
+// to keep the channel in order and within Discord's rate limits. A failed
+// video yields its error and does not stop the others.
+fn process_items<'a>(app: &'a App, videos: Vec<&'a SearchResult>) -> impl Stream<Item = BatchResult<'a>> + 'a {
+    prepare_then_post(
+        videos,
+        app.config().max_concurrency.max(1),
+        move |video| async move { (video, prepare_item(app, video).await) },
+        |(video, prepared)| async move {
+            let result = match prepared {
+                Ok(prepared) => post_prepared(prepared).await,
+                Err(e) => Err(e),
+            };
+            (video, result)
+        },
+    )
+}
 
-    let full_transcript = parse_transcript_fullscript(transcript_json).await?;
-    print!("Full transcript parsed.");
-    print!("Transcript length: {}", full_transcript.len());
+// Run `prepare` on up to `concurrency` items at once and `post` on each
+// prepared item one at a time, in input order. The prepares are driven
+// alongside the posts, so a slow post does not hold up the next transcripts.
+fn prepare_then_post<'a, T, R, O, P, PFut, Q, QFut>(
+    items: Vec<T>,
+    concurrency: usize,
+    prepare: P,
+    post: Q,
+) -> impl Stream<Item = O> + 'a
+where
+    T: 'a,
+    R: 'a,
+    O: 'a,
+    P: FnMut(T) -> PFut + 'a,
+    PFut: Future<Output = R> + 'a,
+    Q: FnMut(R) -> QFut + 'a,
+    QFut: Future<Output = O> + 'a,
+{
+    let (ready_tx, ready_rx) = mpsc::unbounded();
+    let prepares = stream::iter(items)
+        .map(prepare)
+        .buffered(concurrency)
+        .for_each(move |prepared| {
+            // The receiver lives as long as this stream, so the send cannot fail
+            let _ = ready_tx.unbounded_send(prepared);
+            future::ready(())
+        });
+    // `prepares` yields nothing; it is merged in only so it is polled with the posts
+    let prepares = prepares.into_stream().filter_map(|()| future::ready(None));
+    stream::select(prepares, ready_rx.then(post))
+}
 
-    let ai_response = crate::services::myAI_service::chat_with_ai(config, full_transcript).await?;
-    let ai_answer = ai_response.answer;
+// Fetch and summarize one video inside a `video` span, tagging its requests
+// with a fresh request id. `per_video_budget_secs` bounds these transcript and AI stages
+// only: a post that has started is never cancelled, since a partly delivered
+// summary reported as `TimedOut` would stay unseen and be posted again by the
+// next run. Each webhook request has its own timeout instead.
+async fn prepare_item(app: &App, video: &SearchResult) -> Result<Prepared, Box<dyn std::error::Error>> {
+    let budget = app.config().per_video_budget_secs.map(Duration::from_secs);
 
-    // Detailed length logging
-    let byte_len = ai_answer.len();
-    let char_len = ai_answer.chars().count();
-    println!("AI Answer byte length: {}", byte_len);
-    println!("AI Answer char length: {}", char_len);
+    let request_id = http_client::new_request_id();
+    let span = info_span!("video", request_id = %request_id);
+    let run = http_client::scope_request_id(request_id, prepare_video(app, video).instrument(span));
+    run_within_budget(budget, &video.video_id, run).await
+}
 
-    // Check if message is too long for Discord (>5500 chars)
-    const DISCORD_MAX_LENGTH: usize = 5500;
-    println!("Checking if message exceeds Discord limit of {} chars...", DISCORD_MAX_LENGTH);
+/// Failures seen while working through a batch of videos (or channels)
+#[derive(Default)]
+struct BatchErrors {
+    succeeded: usize,
+    first_error: Option<Box<dyn std::error::Error>>,
+}
 
-    let final_message = if char_len > DISCORD_MAX_LENGTH {
-        println!("⚠️  AI response is too long for Discord ({} chars)", char_len);
-        println!("📤 Sending to AI for summarization with 'ks-discord' persona...");
-        let discord_response =
-            crate::services::myAI_service::chat_with_ai_msg4Discord(config, ai_answer).await?;
-        let summary = discord_response.answer;
-        println!("✅ Summarized to {} chars", summary.chars().count());
-        summary
+impl BatchErrors {
+    /// The outcome of a video that succeeded; a failure is logged and kept
+    fn check(
+        &mut self,
+        video: &SearchResult,
+        result: Result<ProcessOutcome, Box<dyn std::error::Error>>,
+    ) -> Option<ProcessOutcome> {
+        self.check_labelled(format_args!("Video {}", video.video_id), result)
+    }
+
+    /// `check` for any unit of work, named by `label` in the failure log
+    fn check_labelled<T>(
+        &mut self,
+        label: std::fmt::Arguments<'_>,
+        result: Result<T, Box<dyn std::error::Error>>,
+    ) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.succeeded += 1;
+                Some(value)
+            }
+            Err(e) => {
+                warn!("❌ {} failed: {}", label, e);
+                self.first_error.get_or_insert(e);
+                None
+            }
+        }
+    }
+
+    /// `value`, or the first error when every entry of the batch failed
+    fn finish<T>(self, value: T) -> Result<T, Box<dyn std::error::Error>> {
+        match self.first_error {
+            Some(e) if self.succeeded == 0 => Err(e),
+            _ => Ok(value),
+        }
+    }
+}
+
+// Abandon a video that runs past its budget. The timeout is reported as
+// `RunOutcome::TimedOut` so a multi-video run carries on with the next one.
+async fn run_within_budget<Fut, T>(
+    budget: Option<Duration>,
+    video_id: &str,
+    run: Fut,
+) -> Result<T, Box<dyn std::error::Error>>
+where
+    Fut: std::future::Future<Output = Result<T, Box<dyn std::error::Error>>>,
+    T: From<ProcessOutcome>,
+{
+    let Some(budget) = budget else {
+        return run.await;
+    };
+
+    match tokio::time::timeout(budget, run).await {
+        Ok(result) => result,
+        Err(_) => {
+            let err = AppError::ApiTimeout {
+                seconds: budget.as_secs(),
+            };
+            warn!("⏱️  Abandoning video {}: {}", video_id, err);
+            Ok(ProcessOutcome {
+                video_id: Some(video_id.to_string()),
+                ..ProcessOutcome::skipped(RunOutcome::TimedOut)
+            }
+            .into())
+        }
+    }
+}
+
+// Log that nothing matched and post the optional "no new video" notice
+async fn report_no_new_video(
+    app: &App,
+    outcome: RunOutcome,
+) -> Result<ProcessOutcome, Box<dyn std::error::Error>> {
+    let config = app.config();
+    info!("No found data :  KS Forward");
+
+    if let Some((title, message)) = no_new_video_notice(config, outcome).filter(|_| !config.dry_run) {
+        app.send_discord(title, message, &EmbedMetadata::default()).await?;
+        info!("No-new-video notification sent to Discord.");
+    }
+
+    Ok(ProcessOutcome::skipped(outcome))
+}
+
+// Whether a video title should be processed. With `require_year_in_title`, the
+// title must also mention `current_year` (Gregorian or Thai Buddhist Era).
+fn is_matching_title(config: &Config, title: &str, current_year: i32) -> bool {
+    if !title.starts_with("KS Forward") {
+        return false;
+    }
+    if !config.require_year_in_title {
+        return true;
+    }
+
+    let buddhist_year = current_year + 543;
+    title.contains(&current_year.to_string()) || title.contains(&buddhist_year.to_string())
+}
+
+/// A video after its transcript and AI stages: either finished (skipped,
+/// empty, timed out) or summarized and waiting to be posted
+enum Prepared {
+    Done(ProcessOutcome),
+    Ready(Box<PreparedSummary>),
+}
+
+impl From<ProcessOutcome> for Prepared {
+    fn from(outcome: ProcessOutcome) -> Self {
+        Prepared::Done(outcome)
+    }
+}
+
+/// Everything `post_summary` needs to post and record one summarized video
+struct PreparedSummary {
+    /// The run's app, configured with this video's persona
+    app: App,
+    mapped: SearchResult,
+    summaries: Vec<LanguageSummary>,
+    metadata: EmbedMetadata,
+    full_transcript: String,
+    /// Correlation id of the video's earlier requests, reused for its post
+    request_id: Option<String>,
+    /// The span the video was prepared in, re-entered for its post
+    span: tracing::Span,
+}
+
+fn skipped_video(mapped: &SearchResult, outcome: RunOutcome) -> ProcessOutcome {
+    ProcessOutcome {
+        video_id: Some(mapped.video_id.clone()),
+        title: Some(mapped.title.clone()),
+        ..ProcessOutcome::skipped(outcome)
+    }
+}
+
+// Fetch and summarize one video without posting it. Nothing here writes shared
+// state, so several videos can be prepared at once.
+async fn prepare_video(app: &App, video: &SearchResult) -> Result<Prepared, Box<dyn std::error::Error>> {
+    let config = app.config();
+    let mapped = video.clone();
+    debug!("video id: {}", mapped.video_id);
+    let skipped = |outcome: RunOutcome| Prepared::Done(skipped_video(&mapped, outcome));
+
+    info!("Found KS Forward Video: {}", mapped.title);
+
+    // Summaries for this video use the persona matching its topic
+    let persona = config
+        .persona_for(&mapped.title, mapped.snippet.description.as_deref().unwrap_or_default())
+        .to_string();
+    let app = app.with_config(Config {
+        ai_persona: persona,
+        ..config.clone()
+    });
+    let config = app.config();
+
+    // Get mock transcript and parse
+    let use_mock_data = config.use_mock_data;
+    let transcript_json = if use_mock_data {
+        dummy_transcript().await?
     } else {
-        println!("✓ AI response length is within Discord limit ({} chars)", char_len);
-        ai_answer.clone()
+        app.get_transcript(&mapped.link).await?
     };
+    info!("Transcript fetched.");
+    metrics::record(|m| m.transcripts_fetched += 1);
 
-    //send to discord
-    crate::services::discord_service::send_message(
-        &detail.items[0].snippet.title.clone().unwrap_or_default(),
-        &final_message,
-    )
-    .await?;
+    if config.strict_transcript {
+        transcript_json.validate()?;
+        info!("Transcript timing validated.");
+    }
 
-    Ok(final_message)
+    let stats = transcript_json.stats();
+    // Length and view counts are best effort; a failed lookup leaves them out
+    let details = if (config.min_transcript_coverage > 0.0 || config.show_video_stats) && !use_mock_data {
+        get_video_details(config, &mapped.video_id).await.unwrap_or_else(|e| {
+            warn!("⚠️  Could not look up the video details ({}); continuing without them.", e);
+            VideoDetails::default()
+        })
+    } else {
+        VideoDetails::default()
+    };
+    let partial_note = if config.min_transcript_coverage > 0.0 {
+        partial_transcript_note(config, &transcript_json, details.duration_secs)
+    } else {
+        None
+    };
+    let full_transcript = transcript_text(config, &transcript_json);
+    let full_transcript = filter_sponsors(config, full_transcript);
+    debug!("Full transcript size: {}", size_label(&full_transcript));
+
+    if !full_transcript.is_empty() {
+        info!("Transcript successfully retrieved and parsed.");
+
+        if let Some(outcome) = transcript_gate(config, &full_transcript) {
+            return Ok(skipped(outcome));
+        }
+
+        if let Some(threshold) = config.dedup_threshold {
+            let state = DedupState::load(&config.dedup_state_path).await?;
+            if is_duplicate(&state, &mapped.video_id, &full_transcript, threshold) {
+                return Ok(skipped(RunOutcome::DuplicateSkipped));
+            }
+        }
+
+        let summaries = match summary_plan(config, &stats) {
+            SummaryPlan::Skip => {
+                warn!(
+                    "⚠️  Transcript looks like mostly non-speech ({:.0}% markers); skipping summary.",
+                    stats.marker_ratio * 100.0
+                );
+                return Ok(skipped(RunOutcome::LowQualitySkipped));
+            }
+            _ if config.summarizer == Summarizer::Extractive => {
+                info!("📝 Using the extractive summarizer; skipping AI.");
+                vec![LanguageSummary {
+                    language: None,
+                    text: extractive_summary(config, &full_transcript),
+                }]
+            }
+            SummaryPlan::Fallback => {
+                warn!("⚠️  Transcript looks like mostly non-speech; using fallback prompt.");
+                let prompt = format!("{}\n\n{}", config.low_quality_prompt, full_transcript);
+                vec![LanguageSummary {
+                    language: None,
+                    text: summarize_transcript(&app, prompt).await?,
+                }]
+            }
+            SummaryPlan::Normal if config.summary_languages.is_empty() && config.summary_tldr => {
+                vec![LanguageSummary {
+                    language: None,
+                    text: tldr_message(config, &summarize_with_tldr(&app, &full_transcript).await?),
+                }]
+            }
+            SummaryPlan::Normal if config.summary_languages.is_empty() => vec![LanguageSummary {
+                language: None,
+                text: summarize_transcript(&app, full_transcript.clone()).await?,
+            }],
+            SummaryPlan::Normal => {
+                summarize_in_languages(&config.summary_languages, &full_transcript, |prompt| {
+                    summarize_transcript(&app, prompt)
+                })
+                .await?
+            }
+        };
+
+        let summaries = match &partial_note {
+            Some(note) => summaries
+                .into_iter()
+                .map(|summary| LanguageSummary {
+                    text: tag_partial(config, note, &summary.text),
+                    ..summary
+                })
+                .collect(),
+            None => summaries,
+        };
+
+        // send to discord
+        let statistics = details
+            .statistics
+            .filter(|_| config.show_video_stats)
+            .and_then(|stats| stats.footer_label());
+        let metadata = EmbedMetadata {
+            video_url: Some(mapped.link.clone()),
+            statistics,
+            ..embed_metadata(config, &mapped.snippet)
+        };
+        Ok(Prepared::Ready(Box::new(PreparedSummary {
+            app,
+            summaries,
+            metadata,
+            full_transcript,
+            request_id: http_client::current_request_id(),
+            span: tracing::Span::current(),
+            mapped,
+        })))
+    } else {
+        info!("Transcript is empty.");
+        Ok(skipped(RunOutcome::EmptyTranscript))
+    }
 }
 
-// Function to parse transcript JSON into full transcript string
-pub async fn parse_transcript_fullscript(
-    transcript_json: TranscriptRoot,
-) -> Result<String, Box<dyn std::error::Error>> {
-    // The API returns content as an array of objects with text field
-    // Join all text segments together
-    let full_transcript = transcript_json.content
+// Post a prepared video under the request id and span it was prepared with,
+// so its Discord logs carry the same id. The post is outside the video's
+// budget (see `prepare_item`).
+async fn post_prepared(prepared: Prepared) -> Result<ProcessOutcome, Box<dyn std::error::Error>> {
+    let prepared = match prepared {
+        Prepared::Done(outcome) => return Ok(outcome),
+        Prepared::Ready(prepared) => prepared,
+    };
+    let span = prepared.span.clone();
+    match prepared.request_id.clone() {
+        Some(request_id) => http_client::scope_request_id(request_id, post_summary(*prepared)).instrument(span).await,
+        None => post_summary(*prepared).instrument(span).await,
+    }
+}
+
+// Post a summarized video to Discord, then write its report and record its
+// fingerprint. Runs one video at a time, so the dedup state read here already
+// holds every video posted earlier in the batch.
+async fn post_summary(prepared: PreparedSummary) -> Result<ProcessOutcome, Box<dyn std::error::Error>> {
+    let PreparedSummary {
+        app,
+        mapped,
+        summaries,
+        metadata,
+        full_transcript,
+        ..
+    } = prepared;
+    let config = app.config();
+    let full_transcript_chars = full_transcript.chars().count();
+
+    let mut dedup_state = match config.dedup_threshold {
+        Some(threshold) => {
+            let state = DedupState::load(&config.dedup_state_path).await?;
+            // A near-duplicate prepared alongside this video may have been posted meanwhile
+            if is_duplicate(&state, &mapped.video_id, &full_transcript, threshold) {
+                return Ok(skipped_video(&mapped, RunOutcome::DuplicateSkipped));
+            }
+            Some(state)
+        }
+        None => None,
+    };
+
+    if config.dry_run {
+        info!("🧪 DRY_RUN: not posting to Discord.\n{}", combined_summary(&summaries));
+    } else {
+        deliver_summaries(&app, &mapped.title, &summaries, &metadata).await?;
+        metrics::record(|m| m.discord_posts += 1);
+        info!("Message sent to Discord.");
+    }
+
+    let report = RunReport {
+        video_id: mapped.video_id.clone(),
+        title: mapped.title.clone(),
+        link: mapped.link.clone(),
+        publish_time: mapped.publish_time.clone(),
+        summary: combined_summary(&summaries),
+        transcript_chars: full_transcript_chars,
+        estimated_tokens: crate::services::myAI_service::estimate_tokens(&full_transcript),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    archive_report(config, &report);
+    write_report(config, &report).await;
+
+    // A dry run leaves no trace that would make the next real run skip this video
+    if let Some(state) = dedup_state.as_mut().filter(|_| !config.dry_run) {
+        state.record(&mapped.video_id, &full_transcript);
+        if let Err(e) = state.save(&config.dedup_state_path).await {
+            warn!("Failed to save dedup state: {}", e);
+        }
+    }
+    info!("KS Forward processing completed.");
+    Ok(ProcessOutcome {
+        transcript_chars: full_transcript_chars,
+        answer_chars: report.summary.chars().count(),
+        posted: !config.dry_run,
+        ..skipped_video(&mapped, RunOutcome::Processed)
+    })
+}
+
+// Whether the transcript nearly matches a recently processed video's
+fn is_duplicate(state: &DedupState, video_id: &str, transcript: &str, threshold: f64) -> bool {
+    match state.find_similar(video_id, transcript, threshold) {
+        Some((earlier, score)) => {
+            warn!(
+                "⚠️  Transcript is {:.0}% similar to recent video {}; skipping as duplicate.",
+                score * 100.0,
+                earlier.video_id
+            );
+            true
+        }
+        None => false,
+    }
+}
+
+// Check how much of the video the transcript covers. Below
+// `min_transcript_coverage` a warning is logged, and with the `tag` action the
+// returned note is prefixed to the summary.
+fn partial_transcript_note(
+    config: &Config,
+    transcript: &TranscriptRoot,
+    video_secs: Option<u64>,
+) -> Option<String> {
+    let coverage = transcript.coverage(video_secs? as f64)?;
+    if coverage >= config.min_transcript_coverage {
+        return None;
+    }
+
+    warn!(
+        "⚠️  Transcript covers only {:.0}% of the video; it may be partial.",
+        coverage * 100.0
+    );
+    match config.partial_transcript_action {
+        PartialTranscriptAction::Warn => None,
+        PartialTranscriptAction::Tag => Some(format!(
+            "⚠️ Partial transcript: covers about {:.0}% of the video",
+            coverage * 100.0
+        )),
+    }
+}
+
+// Skip teasers and off-topic videos before spending AI quota: a transcript
+// shorter than `skip_if_transcript_shorter_than`, or one mentioning none of
+// `require_keywords` (case-insensitive), returns the skip outcome.
+fn transcript_gate(config: &Config, transcript: &str) -> Option<RunOutcome> {
+    let chars = transcript.chars().count();
+    if chars < config.skip_if_transcript_shorter_than {
+        info!(
+            "Transcript has only {} chars (minimum {}); skipping.",
+            chars, config.skip_if_transcript_shorter_than
+        );
+        return Some(RunOutcome::TooShortSkipped);
+    }
+
+    if !config.require_keywords.is_empty() {
+        let lower = transcript.to_lowercase();
+        let matched = config
+            .require_keywords
+            .iter()
+            .any(|keyword| lower.contains(&keyword.to_lowercase()));
+        if !matched {
+            info!(
+                "Transcript mentions none of the required keywords ({}); skipping.",
+                config.require_keywords.join(", ")
+            );
+            return Some(RunOutcome::MissingKeywordsSkipped);
+        }
+    }
+    None
+}
+
+// Join per-language summaries into one text for archiving
+fn combined_summary(summaries: &[LanguageSummary]) -> String {
+    summaries
         .iter()
-        .map(|c| c.text.clone())
+        .map(|summary| match &summary.language {
+            Some(language) => format!("[{}]\n{}", language_label(language), summary.text),
+            None => summary.text.clone(),
+        })
         .collect::<Vec<_>>()
-        .join(" ");
-    Ok(full_transcript)
+        .join("\n\n")
 }
 
-// Mock function for testing transcript parsing
-pub async fn dummy_transcript() -> Result<TranscriptRoot, Box<dyn std::error::Error>> {
-    let path = "src/mock_data/example_transcript.json";
-    let data = fs::read_to_string(path).await?;
-    let transcript: TranscriptRoot = serde_json::from_str(&data)?;
-    Ok(transcript)
+// Store the report in the SQLite archive when DB_PATH is configured.
+// Archive failures are logged but never fail a run that already posted.
+fn archive_report(config: &Config, report: &RunReport) {
+    let Some(path) = &config.db_path else {
+        return;
+    };
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::services::db_service;
+
+        match db_service::open(path).and_then(|conn| db_service::store_summary(&conn, report)) {
+            Ok(()) => info!("Summary archived to {}", path),
+            Err(e) => warn!("Failed to archive summary to {}: {}", path, e),
+        }
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    warn!(
+        "DB_PATH={} is set but this build lacks the `sqlite` feature; not archiving {}",
+        path, report.video_id
+    );
+}
+
+// Write the report as JSON when REPORT_PATH is configured. Like archiving,
+// a failed write is logged but does not fail the run.
+async fn write_report(config: &Config, report: &RunReport) {
+    let Some(path) = &config.report_path else {
+        return;
+    };
+
+    let result = match serde_json::to_string_pretty(report) {
+        Ok(json) => fs::write(path, json).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    match result {
+        Ok(()) => info!("Run report written to {}", path),
+        Err(e) => warn!("Failed to write run report to {}: {}", path, e),
+    }
+}
+
+// Prefix a summary with the partial-transcript note, keeping the tagged text
+// within `AI_MAX_ANSWER_CHARS`
+fn tag_partial(config: &Config, note: &str, summary: &str) -> String {
+    cap_answer(config, format!("{}\n\n{}", note, summary))
+}
+
+// Pick the summary path from the transcript's speech rate and non-speech marker share
+fn summary_plan(config: &Config, stats: &TranscriptStats) -> SummaryPlan {
+    let slow_speech = config.low_quality_min_wpm > 0.0
+        && stats
+            .words_per_minute
+            .is_some_and(|wpm| wpm < config.low_quality_min_wpm);
+    let marker_heavy =
+        config.low_quality_marker_ratio > 0.0 && stats.marker_ratio > config.low_quality_marker_ratio;
+
+    if !(slow_speech || marker_heavy) {
+        return SummaryPlan::Normal;
+    }
+
+    match config.low_quality_action {
+        LowQualityAction::Describe => SummaryPlan::Fallback,
+        LowQualityAction::Skip => SummaryPlan::Skip,
+    }
+}
+
+// Heartbeat message to post when nothing new was found, if enabled
+fn no_new_video_notice(config: &Config, outcome: RunOutcome) -> Option<(&'static str, &'static str)> {
+    if config.notify_on_no_new_video && matches!(outcome, RunOutcome::NoNewVideo | RunOutcome::NoMatches) {
+        Some(("KS Forward", "No new episodes today"))
+    } else {
+        None
+    }
+}
+
+// Function to get summary link from video link
+pub async fn get_summary_link(
+    app: &App,
+    video_link: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let config = app.config();
+    let detail = get_detail_byLink(config, video_link).await?;
+    if detail.items.is_empty() {
+        return Err("No video details found for the provided link".into());
+    }
+
+    info!(
+        "Video Title: {}",
+        detail.items[0].snippet.title.clone().unwrap_or_default()
+    );
+
+    let transcript_json = app.get_transcript(video_link).await?;
+    info!("Transcript JSON fetched.");
+
+    let full_transcript = transcript_text(config, &transcript_json);
+    let full_transcript = filter_sponsors(config, full_transcript);
+    info!("Full transcript parsed.");
+    debug!("Transcript size: {}", size_label(&full_transcript));
+
+    let final_message = if config.summarizer == Summarizer::Extractive {
+        extractive_summary(config, &full_transcript)
+    } else {
+        summarize_transcript(app, full_transcript).await?
+    };
+
+    //send to discord
+    // The detail lookup already asked for `statistics`
+    let statistics = detail.items[0]
+        .statistics
+        .as_ref()
+        .filter(|_| config.show_video_stats)
+        .and_then(|stats| stats.footer_label());
+    // Link the canonical watch URL rather than the (possibly shortened) input
+    let watch_url = SearchResult::from_item(&detail.items[0])
+        .map(|result| result.link)
+        .unwrap_or_else(|| video_link.to_string());
+    let metadata = EmbedMetadata {
+        video_url: Some(watch_url),
+        statistics,
+        ..embed_metadata(config, &detail.items[0].snippet)
+    };
+    if config.dry_run {
+        info!("🧪 DRY_RUN: not posting to Discord.\n{}", final_message);
+    } else {
+        app.send_discord(
+            &detail.items[0].snippet.title.clone().unwrap_or_default(),
+            &final_message,
+            &metadata,
+        )
+        .await?;
+    }
+
+    Ok(final_message)
+}
+
+/// A summary produced for one requested output language
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageSummary {
+    /// Language the summary was requested in; `None` for the default prompt
+    pub language: Option<String>,
+    pub text: String,
+}
+
+// Summarize a transcript with the AI, re-summarizing if the answer is too long for Discord
+async fn summarize_transcript(
+    app: &App,
+    content: String,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let config = app.config();
+    let ai_response = app.chat(content).await?;
+    crate::services::myAI_service::log_events(config, &ai_response);
+    let ai_answer = ai_response.answer;
+
+    // Detailed length logging
+    let char_len = ai_answer.chars().count();
+    debug!("AI Answer size: {}", size_label(&ai_answer));
+    info!(
+        "AI Answer first 200 chars: {}",
+        &ai_answer.chars().take(200).collect::<String>()
+    );
+
+    // Check if message is too long for Discord (>5500 chars)
+    const DISCORD_MAX_LENGTH: usize = 5500;
+    info!("Checking if message exceeds Discord limit of {} chars...", DISCORD_MAX_LENGTH);
+
+    if char_len > DISCORD_MAX_LENGTH {
+        warn!("⚠️  AI response is too long for Discord ({} chars)", char_len);
+        info!("📤 Sending to AI for summarization with 'ks-discord' persona...");
+        let discord_response = app.chat_for_discord(ai_answer).await?;
+        let summary = discord_response.answer;
+        info!("✅ Summarized to {} chars", summary.chars().count());
+        Ok(finalize_summary(config, summary))
+    } else {
+        info!("✓ AI response length is within Discord limit ({} chars)", char_len);
+        Ok(finalize_summary(config, ai_answer))
+    }
+}
+
+/// Instruction asking the AI for a TL;DR and the full summary as one JSON object
+const SUMMARY_PAIR_PROMPT: &str = "Respond with only a JSON object of the form \
+{\"tldr\": \"<one or two sentence TL;DR>\", \"summary\": \"<full summary>\"}.";
+
+/// Summarize a transcript into a TL;DR and a full summary with a single AI call.
+/// The parts are left uncapped; `tldr_message` caps the post they make up.
+async fn summarize_with_tldr(
+    app: &App,
+    transcript: &str,
+) -> Result<SummaryPair, Box<dyn std::error::Error>> {
+    let config = app.config();
+    let prompt = format!("{}\n\n{}", SUMMARY_PAIR_PROMPT, transcript);
+    let ai_response = app.chat(prompt).await?;
+    crate::services::myAI_service::log_events(config, &ai_response);
+    let pair = SummaryPair::from_answer(&ai_response.answer);
+
+    Ok(SummaryPair {
+        tldr: transform_summary(config, pair.tldr),
+        full: transform_summary(config, pair.full),
+    })
+}
+
+// One post body from a summary pair: the TL;DR line above the full summary,
+// capped as a whole to `AI_MAX_ANSWER_CHARS`
+fn tldr_message(config: &Config, pair: &SummaryPair) -> String {
+    cap_answer(config, format!("**TL;DR:** {}\n\n{}", pair.tldr, pair.full))
+}
+
+// Drop sponsor reads from the transcript when the filter is enabled
+fn filter_sponsors(config: &Config, transcript: String) -> String {
+    if !config.sponsor_filter {
+        return transcript;
+    }
+    let (cleaned, removed) = sponsor_filter::strip_sponsor_segments(&transcript, &config.sponsor_phrases);
+    if removed > 0 {
+        info!("🧹 Removed {} sponsor sentence(s) from the transcript.", removed);
+    }
+    cleaned
+}
+
+// Summarize offline by picking the transcript's top sentences
+fn extractive_summary(config: &Config, transcript: &str) -> String {
+    finalize_summary(config, extractive::summarize(transcript, config.extractive_sentences))
+}
+
+// Apply the configured text transforms to a summary before it is delivered,
+// then cap its length
+fn finalize_summary(config: &Config, summary: String) -> String {
+    cap_answer(config, transform_summary(config, summary))
+}
+
+// Tidy a summary and convert its markdown for Discord, as configured
+fn transform_summary(config: &Config, summary: String) -> String {
+    let summary = if config.tidy_summaries {
+        text_cleanup::tidy(&summary)
+    } else {
+        summary
+    };
+
+    if config.discord_markdown_compat {
+        markdown::to_discord_markdown(&summary)
+    } else {
+        summary
+    }
+}
+
+/// Appended to answers cut by `AI_MAX_ANSWER_CHARS`
+const TRUNCATED_MARKER: &str = "…[truncated]";
+
+// Cut an answer longer than `ai_max_answer_chars` on a char boundary, ending it
+// with `TRUNCATED_MARKER` so the result including the marker stays within the cap.
+// A cap too small for the marker keeps just the prefix.
+fn cap_answer(config: &Config, answer: String) -> String {
+    let Some(max_chars) = config.ai_max_answer_chars else {
+        return answer;
+    };
+    let total = answer.chars().count();
+    if total <= max_chars {
+        return answer;
+    }
+
+    warn!(
+        "⚠️  AI answer is {} chars, truncating to AI_MAX_ANSWER_CHARS={}",
+        total, max_chars
+    );
+    let marker_chars = TRUNCATED_MARKER.chars().count();
+    if max_chars <= marker_chars {
+        return answer.chars().take(max_chars).collect();
+    }
+    let kept: String = answer.chars().take(max_chars - marker_chars).collect();
+    format!("{}{}", kept.trim_end(), TRUNCATED_MARKER)
+}
+
+// Produce one summary per language by prefixing the transcript with a language instruction
+async fn summarize_in_languages<F, Fut, E>(
+    languages: &[String],
+    transcript: &str,
+    mut summarize: F,
+) -> Result<Vec<LanguageSummary>, E>
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = Result<String, E>>,
+{
+    let mut summaries = Vec::with_capacity(languages.len());
+
+    for language in languages {
+        info!("Summarizing in {}", language_label(language));
+        let prompt = format!(
+            "Write the summary in {}.\n\n{}",
+            language_label(language),
+            transcript
+        );
+        summaries.push(LanguageSummary {
+            language: Some(language.clone()),
+            text: summarize(prompt).await?,
+        });
+    }
+
+    Ok(summaries)
+}
+
+// Human-readable name for common language codes; other values are used as given
+fn language_label(language: &str) -> String {
+    match language.to_lowercase().as_str() {
+        "th" => "Thai".to_string(),
+        "en" => "English".to_string(),
+        "ja" => "Japanese".to_string(),
+        "zh" => "Chinese".to_string(),
+        _ => language.to_string(),
+    }
+}
+
+// Post summaries: a single summary as-is, multiple languages grouped or one message each
+async fn deliver_summaries(
+    app: &App,
+    title: &str,
+    summaries: &[LanguageSummary],
+    metadata: &EmbedMetadata,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match summaries {
+        [single] if single.language.is_none() => app.send_discord(title, &single.text, metadata).await,
+        _ if app.config().summary_languages_separate => {
+            for summary in summaries {
+                let label = language_label(summary.language.as_deref().unwrap_or_default());
+                let language_title = format!("{} ({})", title, label);
+                app.send_discord(&language_title, &summary.text, metadata).await?;
+            }
+            Ok(())
+        }
+        _ => {
+            let sections: Vec<MessageSection> = summaries
+                .iter()
+                .map(|summary| MessageSection {
+                    heading: language_label(summary.language.as_deref().unwrap_or_default()),
+                    body: summary.text.clone(),
+                })
+                .collect();
+            app.send_discord_sections(title, &sections, metadata).await
+        }
+    }
+}
+
+// Build the Discord embed extras for a video according to the configured flags
+fn embed_metadata(config: &Config, snippet: &Snippet) -> EmbedMetadata {
+    // The thumbnail is shown large when enabled, otherwise in the embed's corner
+    let thumbnail = snippet.thumbnails.as_ref().and_then(|t| t.best_url());
+    let (image_url, thumbnail_url) = if config.embed_large_image {
+        (thumbnail, None)
+    } else {
+        (None, thumbnail)
+    };
+
+    let channel_title = if config.show_channel_in_footer {
+        snippet.channel_title.clone()
+    } else {
+        None
+    };
+
+    EmbedMetadata {
+        image_url,
+        thumbnail_url,
+        published: format_published(snippet, &config.published_date_format),
+        channel: snippet.channel_title.clone(),
+        channel_title,
+        show_detected_language: config.show_detected_language,
+        ..Default::default()
+    }
+}
+
+// Human-friendly publish date in local time, or None when the snippet has no parseable time
+fn format_published(snippet: &Snippet, format: &str) -> Option<String> {
+    let format = if format.trim().is_empty() {
+        DEFAULT_PUBLISHED_DATE_FORMAT
+    } else {
+        format
+    };
+    let published = snippet.published()?.with_timezone(&chrono::Local);
+    let mut out = String::new();
+    // An invalid format string surfaces as a fmt error rather than a panic
+    write!(out, "{}", published.format(format)).ok()?;
+    Some(out)
+}
+
+// Function to parse transcript JSON into full transcript string
+pub fn parse_transcript_fullscript(transcript_json: &TranscriptRoot, join: TranscriptJoin) -> String {
+    // The API returns content as an array of objects with text field
+    // Join all text segments together
+    let texts: Vec<&str> = transcript_json.content.iter().map(|c| c.text.as_str()).collect();
+    let full_transcript = match join {
+        TranscriptJoin::Space => texts.join(" "),
+        TranscriptJoin::Newline => texts.join("\n"),
+        TranscriptJoin::Sentence => {
+            let mut joined = String::new();
+            for text in texts {
+                if !joined.is_empty() {
+                    let ends_sentence = joined.trim_end().ends_with(['.', '!', '?', '。']);
+                    joined.push(if ends_sentence { '\n' } else { ' ' });
+                }
+                joined.push_str(text);
+            }
+            joined
+        }
+    };
+    full_transcript
+}
+
+/// Segment durations above this many units mean the transcript is timed in milliseconds
+const MILLISECOND_DURATION_THRESHOLD: f64 = 100.0;
+
+// Render a transcript as one "[mm:ss] text" line per segment. Offsets may be
+// in seconds or milliseconds depending on the API; milliseconds are detected
+// from segment durations (no caption lasts 100 seconds) or offsets beyond a day.
+pub fn parse_transcript_timestamped(root: &TranscriptRoot) -> String {
+    let in_millis = root.content.iter().any(|c| {
+        c.duration >= MILLISECOND_DURATION_THRESHOLD || c.offset >= 86_400.0
+    });
+    let divisor = if in_millis { 1000.0 } else { 1.0 };
+
+    root.content
+        .iter()
+        .map(|c| {
+            let secs = (c.offset.max(0.0) / divisor) as u64;
+            // Segment text may contain line breaks; keep one segment per line
+            let text = c.text.split_whitespace().collect::<Vec<_>>().join(" ");
+            format!("[{:02}:{:02}] {}", secs / 60, secs % 60, text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Transcript text handed to the summarizer, timestamped when configured
+fn transcript_text(config: &Config, root: &TranscriptRoot) -> String {
+    if config.transcript_with_timestamps {
+        parse_transcript_timestamped(root)
+    } else {
+        parse_transcript_fullscript(root, config.transcript_join)
+    }
+}
+
+// Mock function for testing transcript parsing
+pub async fn dummy_transcript() -> Result<TranscriptRoot, Box<dyn std::error::Error>> {
+    let path = "src/mock_data/example_transcript.json";
+    let data = fs::read_to_string(path).await?;
+    let transcript: TranscriptRoot = serde_json::from_str(&data)?;
+    Ok(transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::youtube_snippet::{High, Id, Thumbnails};
+    use crate::models::youtube_transcript::Content;
+
+    // A video as the search maps it from an API item with this snippet
+    fn video_with(video_id: &str, snippet: Snippet) -> SearchResult {
+        let item = Item {
+            id: Id::StringId(video_id.to_string()),
+            snippet,
+            ..Default::default()
+        };
+        SearchResult::from_item(&item).unwrap()
+    }
+
+    fn video(video_id: &str) -> SearchResult {
+        video_with(video_id, Snippet::default())
+    }
+
+    fn dated_video(video_id: &str, publish_time: &str) -> SearchResult {
+        let snippet = Snippet {
+            publish_time: Some(publish_time.to_string()),
+            ..Default::default()
+        };
+        video_with(video_id, snippet)
+    }
+
+    /// A fresh temp directory, removed on drop so a failed assert leaves nothing behind
+    struct TempDir(std::path::PathBuf);
+
+    impl TempDir {
+        fn new(label: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("ks-{}-{}", label, uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        /// Path of `name` inside the directory
+        fn file(&self, name: &str) -> String {
+            self.0.join(name).to_string_lossy().into_owned()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    // Config for a run against `server`: YouTube, the transcript API, the AI
+    // backend and one Discord webhook all point at it, and the cache lives in
+    // the returned temp dir
+    fn mock_server_config(server: &wiremock::MockServer, label: &str) -> (Config, TempDir) {
+        use crate::models::discord::DiscordTarget;
+
+        let dir = TempDir::new(label);
+        let config = Config {
+            youtube_api_key: "test-key-123".to_string(),
+            youtube_api_base: server.uri(),
+            supabase_api_key: "sd-key".to_string(),
+            supadata_base_url: server.uri(),
+            supadata_transcript_path: "/v1/transcript".to_string(),
+            my_ai_api_url: format!("{}/chat", server.uri()),
+            cache_dir: dir.file("cache"),
+            discord_targets: vec![DiscordTarget::from_url(format!("{}/api/webhooks/1/token", server.uri()))],
+            ..Default::default()
+        };
+        (config, dir)
+    }
+
+    #[test]
+    fn test_catch_up_order() {
+        // Search results are not guaranteed to be sorted
+        let items = [
+            dated_video("b", "2025-01-02T01:00:00Z"),
+            dated_video("c", "2025-01-03T01:00:00Z"),
+            dated_video("a", "2025-01-01T01:00:00Z"),
+        ];
+        let filtered: Vec<&SearchResult> = items.iter().collect();
+        let state = SeenState::default();
+        let ids = |selected: Vec<&SearchResult>| -> Vec<String> {
+            selected.iter().map(|video| video.video_id.clone()).collect()
+        };
+
+        let newest = Config {
+            catch_up_limit: 3,
+            ..Config::default()
+        };
+        assert_eq!(ids(select_catch_up(&newest, &filtered, Some(&state))), vec!["c", "b", "a"]);
+
+        let oldest = Config {
+            catch_up_limit: 2,
+            catch_up_order: CatchUpOrder::OldestFirst,
+            ..Config::default()
+        };
+        assert_eq!(ids(select_catch_up(&oldest, &filtered, Some(&state))), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_first_run_processes_only_first_run_limit() {
+        let config = Config {
+            catch_up_limit: 3,
+            first_run_limit: 1,
+            ..Config::default()
+        };
+        let items = [video("c"), video("b"), video("a")];
+        let filtered: Vec<&SearchResult> = items.iter().collect();
+
+        let selected = select_catch_up(&config, &filtered, None);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].video_id, "c");
+    }
+
+    #[test]
+    fn test_catch_up_skips_seen_and_respects_cap() {
+        let config = Config {
+            catch_up_limit: 2,
+            first_run_limit: 1,
+            ..Config::default()
+        };
+        let items = [video("d"), video("c"), video("b"), video("a")];
+        let filtered: Vec<&SearchResult> = items.iter().collect();
+        let state = SeenState {
+            seen: vec!["c".to_string()],
+        };
+
+        let selected: Vec<String> = select_catch_up(&config, &filtered, Some(&state))
+            .iter()
+            .map(|video| video.video_id.clone())
+            .collect();
+        assert_eq!(selected, vec!["d".to_string(), "b".to_string()]);
+
+        let forced = Config {
+            force_reprocess: true,
+            ..config
+        };
+        let selected: Vec<String> = select_catch_up(&forced, &filtered, Some(&state))
+            .iter()
+            .map(|video| video.video_id.clone())
+            .collect();
+        assert_eq!(selected, vec!["d".to_string(), "c".to_string()]);
+    }
+
+    fn snippet_with_thumbnail() -> Snippet {
+        Snippet {
+            thumbnails: Some(Thumbnails {
+                high: Some(High {
+                    url: Some("https://i.ytimg.com/vi/abc/hqdefault.jpg".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_embed_metadata_fields_from_snippet() {
+        let snippet = Snippet {
+            channel_title: Some("KS Forward Channel".to_string()),
+            publish_time: Some("2025-01-15T23:30:00Z".to_string()),
+            ..Default::default()
+        };
+        let metadata = embed_metadata(&Config::default(), &snippet);
+
+        let expected = chrono::DateTime::parse_from_rfc3339("2025-01-15T23:30:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Local)
+            .format(DEFAULT_PUBLISHED_DATE_FORMAT)
+            .to_string();
+        assert_eq!(metadata.published, Some(expected));
+        assert_eq!(metadata.channel.as_deref(), Some("KS Forward Channel"));
+
+        let bare = embed_metadata(&Config::default(), &Snippet::default());
+        assert!(bare.published.is_none() && bare.channel.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_summarize_in_two_languages() {
+        let languages = vec!["th".to_string(), "en".to_string()];
+        let mut prompts = Vec::new();
+
+        let summaries = summarize_in_languages(&languages, "transcript text", |prompt| {
+            prompts.push(prompt.clone());
+            let language = if prompt.contains("Thai") { "th" } else { "en" };
+            async move { Ok::<_, String>(format!("summary-{}", language)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[0].starts_with("Write the summary in Thai."));
+        assert!(prompts[1].starts_with("Write the summary in English."));
+        assert!(prompts.iter().all(|p| p.ends_with("transcript text")));
+
+        assert_eq!(
+            summaries,
+            vec![
+                LanguageSummary {
+                    language: Some("th".to_string()),
+                    text: "summary-th".to_string(),
+                },
+                LanguageSummary {
+                    language: Some("en".to_string()),
+                    text: "summary-en".to_string(),
+                },
+            ]
+        );
+    }
+
+    fn music_heavy_transcript() -> TranscriptRoot {
+        let content = (0..10)
+            .map(|i| Content {
+                lang: "en".to_string(),
+                text: if i < 8 { "[Music]".to_string() } else { "thanks for watching".to_string() },
+                offset: i as f64 * 10_000.0,
+                duration: 10_000.0,
+            })
+            .collect();
+        TranscriptRoot {
+            content,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_summary_plan_uses_fallback_for_music_heavy_transcript() {
+        let stats = music_heavy_transcript().stats();
+        let config = Config {
+            low_quality_marker_ratio: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(summary_plan(&config, &stats), SummaryPlan::Fallback);
+
+        let skip = Config {
+            low_quality_action: LowQualityAction::Skip,
+            ..config.clone()
+        };
+        assert_eq!(summary_plan(&skip, &stats), SummaryPlan::Skip);
+
+        let disabled = Config {
+            low_quality_marker_ratio: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(summary_plan(&disabled, &stats), SummaryPlan::Normal);
+    }
+
+    #[test]
+    fn test_summary_plan_checks_words_per_minute() {
+        let stats = TranscriptStats {
+            words_per_minute: Some(12.0),
+            ..Default::default()
+        };
+        let config = Config {
+            low_quality_min_wpm: 40.0,
+            ..Default::default()
+        };
+        assert_eq!(summary_plan(&config, &stats), SummaryPlan::Fallback);
+
+        let fast = TranscriptStats {
+            words_per_minute: Some(150.0),
+            ..Default::default()
+        };
+        assert_eq!(summary_plan(&config, &fast), SummaryPlan::Normal);
+    }
+
+    #[test]
+    fn test_combined_summary_labels_languages() {
+        let summaries = vec![
+            LanguageSummary {
+                language: Some("th".to_string()),
+                text: "ไทย".to_string(),
+            },
+            LanguageSummary {
+                language: Some("en".to_string()),
+                text: "English".to_string(),
+            },
+        ];
+        assert_eq!(combined_summary(&summaries), "[Thai]\nไทย\n\n[English]\nEnglish");
+
+        let single = vec![LanguageSummary {
+            language: None,
+            text: "only".to_string(),
+        }];
+        assert_eq!(combined_summary(&single), "only");
+    }
+
+    #[test]
+    fn test_year_guard_skips_old_titles() {
+        let config = Config {
+            require_year_in_title: true,
+            ..Default::default()
+        };
+
+        assert!(is_matching_title(&config, "KS Forward 15 Jan 2025", 2025));
+        assert!(is_matching_title(&config, "KS Forward 15 ม.ค. 2568", 2025));
+        assert!(!is_matching_title(&config, "KS Forward 12 Dec 2024", 2025));
+        assert!(!is_matching_title(&config, "KS Forward", 2025));
+        assert!(!is_matching_title(&config, "Weekly 2025", 2025));
+    }
+
+    #[test]
+    fn test_year_guard_disabled_by_default() {
+        let config = Config::default();
+        assert!(is_matching_title(&config, "KS Forward 12 Dec 2024", 2025));
+    }
+
+    #[test]
+    fn test_merge_outcome_prefers_processed_then_skips_then_nothing_new() {
+        let quiet = |outcome| ProcessOutcome::skipped(outcome);
+        let merged = [RunOutcome::NoMatches, RunOutcome::NoNewVideo, RunOutcome::NoMatches]
+            .into_iter()
+            .fold(quiet(RunOutcome::NoMatches), |acc, o| merge_outcome(acc, quiet(o)));
+        assert_eq!(merged.outcome, RunOutcome::NoNewVideo);
+
+        let merged = merge_outcome(quiet(RunOutcome::NoNewVideo), quiet(RunOutcome::LowQualitySkipped));
+        assert_eq!(merged.outcome, RunOutcome::LowQualitySkipped);
+        let merged = merge_outcome(merged, quiet(RunOutcome::NoNewVideo));
+        assert_eq!(merged.outcome, RunOutcome::LowQualitySkipped);
+
+        let posted = ProcessOutcome {
+            posted: true,
+            ..quiet(RunOutcome::Processed)
+        };
+        let merged = merge_outcome(posted.clone(), quiet(RunOutcome::DuplicateSkipped));
+        assert_eq!(merged, posted);
+        assert_eq!(merge_outcome(quiet(RunOutcome::TimedOut), posted.clone()), posted);
+    }
+
+    #[test]
+    fn test_no_new_video_notice_requires_flag_and_outcome() {
+        let disabled = Config::default();
+        assert!(no_new_video_notice(&disabled, RunOutcome::NoNewVideo).is_none());
+
+        let enabled = Config {
+            notify_on_no_new_video: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            no_new_video_notice(&enabled, RunOutcome::NoNewVideo),
+            Some(("KS Forward", "No new episodes today"))
+        );
+        assert!(no_new_video_notice(&enabled, RunOutcome::NoMatches).is_some());
+        assert!(no_new_video_notice(&enabled, RunOutcome::Processed).is_none());
+        assert!(no_new_video_notice(&enabled, RunOutcome::EmptyTranscript).is_none());
+    }
+
+    #[test]
+    fn test_finalize_summary_markdown_compat_flag() {
+        let summary = "## Highlights".to_string();
+        assert_eq!(finalize_summary(&Config::default(), summary.clone()), "## Highlights");
+
+        let enabled = Config {
+            discord_markdown_compat: true,
+            ..Default::default()
+        };
+        assert_eq!(finalize_summary(&enabled, summary), "**Highlights**");
+    }
+
+    #[test]
+    fn test_cap_answer_truncates_on_char_boundary() {
+        let config = Config {
+            ai_max_answer_chars: Some(20),
+            ..Default::default()
+        };
+        // Thai chars are three bytes each; a byte-based cut would split one
+        let answer = "ตลาดหุ้นไทยปรับตัวขึ้นต่อเนื่องในสัปดาห์นี้".to_string();
+        let capped = cap_answer(&config, answer.clone());
+        assert!(capped.ends_with(TRUNCATED_MARKER));
+        assert_eq!(capped.chars().count(), 20);
+        assert!(answer.starts_with(capped.trim_end_matches(TRUNCATED_MARKER)));
+
+        let short = "สั้น".to_string();
+        assert_eq!(cap_answer(&config, short.clone()), short);
+        assert_eq!(cap_answer(&Config::default(), answer.clone()), answer);
+
+        // No room for the marker: the prefix alone, still within the cap
+        let tiny = Config {
+            ai_max_answer_chars: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(cap_answer(&tiny, answer.clone()), "ตลาดห");
+    }
+
+    #[test]
+    fn test_embed_metadata_image_gated_by_flag() {
+        let snippet = snippet_with_thumbnail();
+
+        let disabled = embed_metadata(&Config::default(), &snippet);
+        assert!(disabled.image_url.is_none());
+        assert_eq!(
+            disabled.thumbnail_url.as_deref(),
+            Some("https://i.ytimg.com/vi/abc/hqdefault.jpg")
+        );
+
+        let enabled = Config {
+            embed_large_image: true,
+            ..Default::default()
+        };
+        let enabled = embed_metadata(&enabled, &snippet);
+        assert_eq!(enabled.image_url.as_deref(), Some("https://i.ytimg.com/vi/abc/hqdefault.jpg"));
+        assert!(enabled.thumbnail_url.is_none());
+
+        // No thumbnails in the snippet: neither image is set
+        let bare = embed_metadata(&Config::default(), &Snippet::default());
+        assert!(bare.image_url.is_none() && bare.thumbnail_url.is_none());
+    }
+
+    #[test]
+    fn test_partial_transcript_is_tagged() {
+        // 12 minutes of a 60-minute video
+        let transcript = TranscriptRoot {
+            content: vec![
+                Content { text: "a".to_string(), offset: 0.0, duration: 60_000.0, ..Default::default() },
+                Content { text: "b".to_string(), offset: 660_000.0, duration: 60_000.0, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        let tag = Config {
+            min_transcript_coverage: 0.8,
+            partial_transcript_action: PartialTranscriptAction::Tag,
+            ..Config::default()
+        };
+        let note = partial_transcript_note(&tag, &transcript, Some(3600)).unwrap();
+        assert!(note.contains("20%"));
+
+        // The note counts against the answer cap
+        let capped = Config {
+            ai_max_answer_chars: Some(80),
+            ..tag.clone()
+        };
+        let summary = finalize_summary(&capped, "สรุปข่าวตลาดหุ้น ".repeat(10));
+        let tagged = tag_partial(&capped, &note, &summary);
+        assert!(tagged.starts_with(&note));
+        assert!(tagged.chars().count() <= 80);
+
+        let warn = Config {
+            min_transcript_coverage: 0.8,
+            ..Config::default()
+        };
+        assert!(partial_transcript_note(&warn, &transcript, Some(3600)).is_none());
+        assert!(partial_transcript_note(&tag, &transcript, Some(900)).is_none());
+        assert!(partial_transcript_note(&tag, &transcript, None).is_none());
+    }
+
+    #[test]
+    fn test_transcript_gates() {
+        let transcript = "Coming up next week: the Fed decision and what it means for Thai BONDS.";
+
+        assert_eq!(transcript_gate(&Config::default(), transcript), None);
+
+        let min_length = Config {
+            skip_if_transcript_shorter_than: 100,
+            ..Config::default()
+        };
+        assert_eq!(transcript_gate(&min_length, transcript), Some(RunOutcome::TooShortSkipped));
+        let min_length = Config {
+            skip_if_transcript_shorter_than: transcript.chars().count(),
+            ..Config::default()
+        };
+        assert_eq!(transcript_gate(&min_length, transcript), None);
+
+        let keywords = |words: &[&str]| Config {
+            require_keywords: words.iter().map(|w| w.to_string()).collect(),
+            ..Config::default()
+        };
+        assert_eq!(transcript_gate(&keywords(&["inflation", "bonds"]), transcript), None);
+        assert_eq!(transcript_gate(&keywords(&["FED"]), transcript), None);
+        assert_eq!(
+            transcript_gate(&keywords(&["inflation", "gold"]), transcript),
+            Some(RunOutcome::MissingKeywordsSkipped)
+        );
+    }
+
+    #[test]
+    fn test_transcript_join_modes() {
+        let root = TranscriptRoot {
+            content: vec![
+                Content { text: "Hello there.".to_string(), ..Default::default() },
+                Content { text: "How are".to_string(), ..Default::default() },
+                Content { text: "you?".to_string(), ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        let join = |mode| parse_transcript_fullscript(&root, mode);
+
+        assert_eq!(join(TranscriptJoin::Space), "Hello there. How are you?");
+        assert_eq!(join(TranscriptJoin::Newline), "Hello there.\nHow are\nyou?");
+        assert_eq!(join(TranscriptJoin::Sentence), "Hello there.\nHow are you?");
+        assert_eq!(parse_transcript_fullscript(&TranscriptRoot::default(), TranscriptJoin::Space), "");
+    }
+
+    #[test]
+    fn test_transcript_timestamps() {
+        let segment = |text: &str, offset: f64, duration: f64| Content {
+            text: text.to_string(),
+            offset,
+            duration,
+            ..Default::default()
+        };
+
+        // Milliseconds, including an offset under 1000
+        let millis = TranscriptRoot {
+            content: vec![
+                segment("Good morning", 640.0, 2400.0),
+                segment("market\nupdate", 65_000.0, 3100.0),
+                segment("Bye", 3_725_000.0, 1500.0),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_transcript_timestamped(&millis),
+            "[00:00] Good morning\n[01:05] market update\n[62:05] Bye"
+        );
+
+        // Seconds
+        let seconds = TranscriptRoot {
+            content: vec![segment("Hello", 0.5, 2.4), segment("World", 75.2, 3.0)],
+            ..Default::default()
+        };
+        assert_eq!(parse_transcript_timestamped(&seconds), "[00:00] Hello\n[01:15] World");
+
+        let config = Config {
+            transcript_with_timestamps: true,
+            ..Default::default()
+        };
+        assert_eq!(transcript_text(&config, &seconds), "[00:00] Hello\n[01:15] World");
+        assert_eq!(transcript_text(&Config::default(), &seconds), "Hello World");
+    }
+
+    #[test]
+    fn test_embed_metadata_channel_gated_by_flag() {
+        let snippet = Snippet {
+            channel_title: Some("KS Forward Channel".to_string()),
+            ..Default::default()
+        };
+        assert!(embed_metadata(&Config::default(), &snippet).channel_title.is_none());
+
+        let enabled = Config {
+            show_channel_in_footer: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            embed_metadata(&enabled, &snippet).channel_title.as_deref(),
+            Some("KS Forward Channel")
+        );
+    }
+
+    #[test]
+    fn test_embed_metadata_formats_publish_date() {
+        let snippet = dated_video("a", "2025-01-15T01:00:00Z").snippet;
+        // An unset format falls back to the default "15 Jan 2025" style
+        assert_eq!(
+            embed_metadata(&Config::default(), &snippet).published.as_deref(),
+            Some("15 Jan 2025")
+        );
+
+        let iso = Config {
+            published_date_format: "%Y-%m-%d".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(embed_metadata(&iso, &snippet).published.as_deref(), Some("2025-01-15"));
+        assert!(embed_metadata(&iso, &Snippet::default()).published.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_writes_report_without_posting() {
+        use wiremock::matchers::any;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let (config, dir) = mock_server_config(&server, "report");
+        let report_path = dir.file("report.json");
+        let dedup_path = dir.file("dedup.json");
+        let config = Config {
+            dry_run: true,
+            dedup_threshold: Some(0.9),
+            dedup_state_path: dedup_path.clone(),
+            use_mock_data: true,
+            summarizer: Summarizer::Extractive,
+            extractive_sentences: 3,
+            report_path: Some(report_path.clone()),
+            ..config
+        };
+        let item = video_with(
+            "abc123",
+            Snippet {
+                title: Some("KS Forward 2025".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let app = App::new(config);
+        let results: Vec<_> = process_items(&app, vec![&item]).collect().await;
+        let outcome = results.into_iter().next().unwrap().1.unwrap();
+        assert_eq!(outcome.outcome, RunOutcome::Processed);
+        assert_eq!(outcome.video_id.as_deref(), Some("abc123"));
+        assert!(outcome.transcript_chars > 0 && outcome.answer_chars > 0);
+        assert!(!outcome.posted);
+
+        let written: RunReport = serde_json::from_str(&fs::read_to_string(&report_path).await.unwrap()).unwrap();
+        assert_eq!(written.video_id, "abc123");
+        assert!(!written.summary.is_empty());
+        assert!(!std::path::Path::new(&dedup_path).exists(), "a dry run must not record dedup state");
+        // Dropping the server verifies the `expect(0)` webhook mock
+    }
+
+    #[test]
+    fn test_newest_first_ignores_search_order() {
+        let items = [
+            dated_video("undated", "not a timestamp"),
+            dated_video("tuesday", "2025-01-14T01:00:00Z"),
+            video("missing"),
+            dated_video("thursday", "2025-01-16T08:00:00+07:00"),
+            dated_video("wednesday", "2025-01-15T01:00:00Z"),
+        ];
+        let mut sorted: Vec<&SearchResult> = items.iter().collect();
+        sort_newest_first(&mut sorted);
+        let ids: Vec<String> = sorted.iter().map(|video| video.video_id.clone()).collect();
+        assert_eq!(ids, ["thursday", "wednesday", "tuesday", "undated", "missing"]);
+
+        let filtered: Vec<&SearchResult> = items.iter().collect();
+        let newest = page_selection(&Config::default(), &filtered);
+        assert_eq!(newest[0].video_id, "thursday");
+    }
+
+    #[test]
+    fn test_page_selection_processes_all_matching_when_enabled() {
+        let items = [
+            dated_video("morning", "2025-01-15T01:00:00Z"),
+            dated_video("evening", "2025-01-15T11:00:00Z"),
+        ];
+        let filtered: Vec<&SearchResult> = items.iter().collect();
+        let ids = |selected: Vec<&SearchResult>| -> Vec<String> {
+            selected.iter().map(|video| video.video_id.clone()).collect()
+        };
+
+        // Default: only the newest match
+        assert_eq!(ids(page_selection(&Config::default(), &filtered)), ["evening"]);
+
+        let latest_two = Config {
+            latest_count: 2,
+            ..Default::default()
+        };
+        assert_eq!(ids(page_selection(&latest_two, &filtered)), ["evening", "morning"]);
+
+        let all = Config {
+            process_all_matching: true,
+            catch_up_limit: 3,
+            ..Default::default()
+        };
+        assert_eq!(ids(page_selection(&all, &filtered)), ["evening", "morning"]);
+
+        let capped = Config {
+            catch_up_limit: 1,
+            ..all
+        };
+        assert_eq!(ids(page_selection(&capped, &filtered)), ["evening"]);
+    }
+
+    #[test]
+    fn test_since_filters_by_publish_time() {
+        let items = [
+            dated_video("old", "2023-12-31T23:59:59Z"),
+            dated_video("new", "2024-01-02T01:00:00Z"),
+            dated_video("newer", "2024-01-03T01:00:00+07:00"),
+            dated_video("undated", "not a timestamp"),
+        ];
+        let since = crate::config::parse_since("2024-01-01").ok();
+        let kept: Vec<&SearchResult> = items.iter().filter(|video| is_published_since(video, since)).collect();
+        let ids: Vec<String> = kept.iter().map(|video| video.video_id.clone()).collect();
+        assert_eq!(ids, ["new", "newer"]);
+        assert!(items.iter().all(|video| is_published_since(video, None)));
+
+        // Every match since the cutoff is selected, not just the newest
+        let config = Config {
+            since,
+            catch_up_limit: 3,
+            ..Default::default()
+        };
+        assert_eq!(page_selection(&config, &kept).len(), 2);
+    }
+
+    #[test]
+    fn test_tldr_message_puts_tldr_first() {
+        let pair = SummaryPair {
+            tldr: "Banks lead the SET.".to_string(),
+            full: "Full notes here.".to_string(),
+        };
+        assert_eq!(
+            tldr_message(&Config::default(), &pair),
+            "**TL;DR:** Banks lead the SET.\n\nFull notes here."
+        );
+    }
+
+    #[test]
+    fn test_tldr_message_stays_within_answer_cap() {
+        let config = Config {
+            ai_max_answer_chars: Some(60),
+            ..Default::default()
+        };
+        let pair = SummaryPair {
+            tldr: "ตลาดหุ้นไทยปรับตัวขึ้น ".repeat(3),
+            full: "ตลาดหุ้นไทยปรับตัวขึ้นต่อเนื่องในสัปดาห์นี้ ".repeat(3),
+        };
+        let message = tldr_message(&config, &pair);
+        assert!(message.starts_with("**TL;DR:** "));
+        assert!(message.ends_with(TRUNCATED_MARKER));
+        assert!(message.chars().count() <= 60);
+    }
+
+    #[test]
+    fn test_skip_processed_unless_forced() {
+        let mut processed = SeenState::default();
+        processed.mark("abc123");
+
+        assert!(skip_processed(&Config::default(), &processed, "abc123"));
+        assert!(!skip_processed(&Config::default(), &processed, "new456"));
+
+        let forced = Config {
+            force_reprocess: true,
+            ..Default::default()
+        };
+        assert!(!skip_processed(&forced, &processed, "abc123"));
+    }
+
+    #[test]
+    fn test_compare_prompt_delimits_each_video() {
+        let inputs = [
+            CompareInput {
+                title: "KS Forward EP1".to_string(),
+                published: Some("13 Jan 2025".to_string()),
+                text: "Banks led the market.".to_string(),
+            },
+            CompareInput {
+                title: "KS Forward EP2".to_string(),
+                published: None,
+                text: "  Energy shares rebounded.  ".to_string(),
+            },
+        ];
+
+        let prompt = build_compare_prompt(&inputs);
+        assert!(prompt.starts_with(COMPARE_PROMPT));
+        assert!(prompt.contains(
+            "=== Video 1: KS Forward EP1 (13 Jan 2025) ===\nBanks led the market.\n=== End of video 1 ==="
+        ));
+        assert!(prompt.contains(
+            "=== Video 2: KS Forward EP2 ===\nEnergy shares rebounded.\n=== End of video 2 ==="
+        ));
+        assert!(prompt.find("Video 1:").unwrap() < prompt.find("Video 2:").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_compare_budget_maps_only_when_over() {
+        let inputs = vec![
+            CompareInput {
+                title: "A".to_string(),
+                published: None,
+                text: "x".repeat(30),
+            },
+            CompareInput {
+                title: "B".to_string(),
+                published: None,
+                text: "y".repeat(30),
+            },
+        ];
+        let summarize = |text: String| async move { Ok::<_, String>(format!("summary of {} chars", text.len())) };
+
+        let kept = fit_compare_budget(inputs.clone(), 100, summarize).await.unwrap();
+        assert_eq!(kept, inputs);
+
+        let mapped = fit_compare_budget(inputs, 50, summarize).await.unwrap();
+        assert!(mapped.iter().all(|input| input.text == "summary of 30 chars"));
+        assert_eq!(mapped[1].title, "B");
+    }
+
+    #[tokio::test]
+    async fn test_summarize_playlist_posts_each_video_once() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/playlistItems"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_playlist_items.json")),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        // Later pages: one more video
+        Mock::given(method("GET"))
+            .and(path("/playlistItems"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"kind":"k","etag":"e","pageInfo":{"totalResults":4,"resultsPerPage":3},"items":[
+                    {"kind":"youtube#playlistItem","etag":"p4","id":"UExfNA",
+                     "snippet":{"title":"Market wrap","videoOwnerChannelTitle":"KS Forward",
+                                "resourceId":{"kind":"youtube#video","videoId":"k3Xy9QpLm2A"}}}]}"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/token"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let (config, _dir) = mock_server_config(&server, "playlist");
+        let config = Config {
+            use_mock_data: true,
+            summarizer: Summarizer::Extractive,
+            extractive_sentences: 3,
+            ..config
+        };
+
+        let processed_two = PlaylistOutcome {
+            processed: 2,
+            skipped: 0,
+        };
+        let app = App::new(config);
+        assert_eq!(summarize_playlist(&app, "PL123").await.unwrap(), processed_two);
+        let posts = |requests: Vec<wiremock::Request>| requests.iter().filter(|r| r.method.as_str() == "POST").count();
+        assert_eq!(posts(server.received_requests().await.unwrap()), 2);
+
+        // Both videos are on the processed list now; the private and deleted ones never were
+        assert_eq!(summarize_playlist(&app, "PL123").await.unwrap(), PlaylistOutcome::default());
+        assert_eq!(posts(server.received_requests().await.unwrap()), 2);
+
+        // Skipped videos are reported apart from processed ones. The first page
+        // mock is used up, so the playlist is now just the one later video.
+        let too_short = Config {
+            force_reprocess: true,
+            skip_if_transcript_shorter_than: usize::MAX,
+            ..app.config().clone()
+        };
+        let skipped_one = PlaylistOutcome {
+            processed: 0,
+            skipped: 1,
+        };
+        assert_eq!(summarize_playlist(&App::new(too_short), "PL123").await.unwrap(), skipped_one);
+        assert_eq!(posts(server.received_requests().await.unwrap()), 2);
+    }
+
+    #[tokio::test]
+    async fn test_batch_posts_in_order_and_isolates_failures() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let transcript = |video_id: &str, delay_ms: u64| {
+            Mock::given(method("GET"))
+                .and(path("/v1/transcript"))
+                .and(query_param("url", format!("https://www.youtube.com/watch?v={}", video_id)))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_string(include_str!("../mock_data/example_transcript.json"))
+                        .set_delay(Duration::from_millis(delay_ms)),
+                )
+        };
+        // The first video is the slowest, yet must still be posted first
+        transcript("slow1", 200).mount(&server).await;
+        transcript("fast2", 0).mount(&server).await;
+        Mock::given(method("GET"))
+            .and(path("/v1/transcript"))
+            .and(query_param("url", "https://www.youtube.com/watch?v=gone3"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_string(include_str!("../mock_data/transcript_unavailable.json")),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/token"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let (config, _dir) = mock_server_config(&server, "batch");
+        let config = Config {
+            summarizer: Summarizer::Extractive,
+            extractive_sentences: 3,
+            max_concurrency: 3,
+            ..config
+        };
+        let items: Vec<SearchResult> = ["slow1", "fast2", "gone3"]
+            .into_iter()
+            .map(|video_id| {
+                let snippet = Snippet {
+                    title: Some(format!("KS Forward {}", video_id)),
+                    ..Default::default()
+                };
+                video_with(video_id, snippet)
+            })
+            .collect();
+
+        let app = App::new(config);
+        let results: Vec<_> = process_items(&app, items.iter().collect()).collect().await;
+
+        let mut errors = BatchErrors::default();
+        let outcomes: Vec<_> = results
+            .into_iter()
+            .map(|(item, result)| errors.check(item, result).map(|outcome| outcome.outcome))
+            .collect();
+        assert_eq!(outcomes, [Some(RunOutcome::Processed), Some(RunOutcome::Processed), None]);
+        // One failed video does not fail a batch where others succeeded
+        assert_eq!(errors.finish(()).ok(), Some(()));
+
+        let titles: Vec<String> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|request| request.method.as_str() == "POST")
+            .map(|request| {
+                let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+                body["embeds"][0]["title"].as_str().unwrap_or_default().to_string()
+            })
+            .collect();
+        assert_eq!(titles, ["KS Forward slow1", "KS Forward fast2"]);
+
+        let mut all_failed = BatchErrors::default();
+        assert!(all_failed.check(&items[2], Err("boom".into())).is_none());
+        assert_eq!(all_failed.finish(()).unwrap_err().to_string(), "boom");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_since_run_leaves_state_file_unchanged() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"kind":"k","etag":"e","pageInfo":{"totalResults":1,"resultsPerPage":50},"items":[
+                    {"kind":"youtube#searchResult","etag":"a1",
+                     "id":{"kind":"youtube#video","videoId":"JB5FbXxSZ3o"},
+                     "snippet":{"publishedAt":"2025-01-15T01:00:00Z","title":"KS Forward 15 Jan 2025"}}]}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let (config, dir) = mock_server_config(&server, "backfill");
+        let state_path = dir.file("state.json");
+        let before = "{\n  \"seen\": [\n    \"older_video\"\n  ]\n}";
+        fs::write(&state_path, before).await.unwrap();
+
+        let config = Config {
+            ksforword_channel_ids: vec!["UCxxxxxxxxxxxxxxxxxx".to_string()],
+            use_mock_data: true,
+            summarizer: Summarizer::Extractive,
+            dry_run: true,
+            since: Some(crate::config::parse_since("2025-01-01").unwrap()),
+            state_path: Some(state_path.clone()),
+            ..config
+        };
+
+        let outcome = get_lastest_ksForword(&App::new(config)).await.unwrap();
+        assert_eq!(outcome.outcome, RunOutcome::Processed);
+        assert_eq!(fs::read_to_string(&state_path).await.unwrap(), before);
+    }
+
+    #[tokio::test]
+    async fn test_skipped_video_is_not_processed_again() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_search.json")))
+            .mount(&server)
+            .await;
+
+        let (config, _dir) = mock_server_config(&server, "skipped");
+        let config = Config {
+            ksforword_channel_ids: vec!["UCxxxxxxxxxxxxxxxxxx".to_string()],
+            use_mock_data: true,
+            skip_if_transcript_shorter_than: usize::MAX,
+            ..config
+        };
+
+        let app = App::new(config);
+        let outcome = get_lastest_ksForword(&app).await.unwrap();
+        assert_eq!(outcome.outcome, RunOutcome::TooShortSkipped);
+        // The skipped video is on the seen list, so the next run finds nothing new
+        let outcome = get_lastest_ksForword(&app).await.unwrap();
+        assert_eq!(outcome.outcome, RunOutcome::NoNewVideo);
+    }
+
+    #[tokio::test]
+    async fn test_failing_channel_does_not_stop_the_others() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("channelId", "UCbrokenxxxxxxxxxxxx"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("channelId", "UCxxxxxxxxxxxxxxxxxx"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_search.json")))
+            .mount(&server)
+            .await;
+
+        let (config, _dir) = mock_server_config(&server, "channels");
+        let config = Config {
+            ksforword_channel_ids: vec!["UCbrokenxxxxxxxxxxxx".to_string(), "UCxxxxxxxxxxxxxxxxxx".to_string()],
+            use_mock_data: true,
+            summarizer: Summarizer::Extractive,
+            dry_run: true,
+            ..config
+        };
+
+        // The first channel fails, the second is still processed
+        let app = App::new(config);
+        let outcome = get_lastest_ksForword(&app).await.unwrap();
+        assert_eq!(outcome.outcome, RunOutcome::Processed);
+        assert_eq!(outcome.video_id.as_deref(), Some("JB5FbXxSZ3o"));
+
+        // With every channel failing the run fails
+        let broken = Config {
+            ksforword_channel_ids: vec!["UCbrokenxxxxxxxxxxxx".to_string()],
+            ..app.config().clone()
+        };
+        assert!(get_lastest_ksForword(&App::new(broken)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_video_lookup_does_not_fail_the_video() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/transcript"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_transcript.json")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/videos"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/token"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let (config, _dir) = mock_server_config(&server, "lookup");
+        let config = Config {
+            min_transcript_coverage: 0.8,
+            show_video_stats: true,
+            summarizer: Summarizer::Extractive,
+            extractive_sentences: 3,
+            max_concurrency: 1,
+            ..config
+        };
+        let item = video("JB5FbXxSZ3o");
+
+        let app = App::new(config);
+        let results: Vec<_> = process_items(&app, vec![&item]).collect().await;
+        let outcome = results.into_iter().next().unwrap().1.unwrap();
+        assert_eq!(outcome.outcome, RunOutcome::Processed);
+        // Length and statistics come from one lookup
+        let lookups = server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|request| request.url.path() == "/videos")
+            .count();
+        assert_eq!(lookups, 1);
+    }
+
+    #[tokio::test]
+    async fn test_one_request_id_across_a_videos_calls_and_logs() {
+        use std::sync::{Arc, Mutex};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        // Collects the formatted log lines of this test's thread
+        #[derive(Clone, Default)]
+        struct Logs(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Logs {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/transcript"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_transcript.json")))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"answer":"สรุปตลาด","session_id":"s1"}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/token"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let (config, _dir) = mock_server_config(&server, "request-id");
+        let item = video("JB5FbXxSZ3o");
+
+        let logs = Logs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .with_ansi(false)
+            .finish();
+        let app = App::new(config);
+        let results: Vec<_> = {
+            let _guard = tracing::subscriber::set_default(subscriber);
+            process_items(&app, vec![&item]).collect().await
+        };
+        assert_eq!(results.into_iter().next().unwrap().1.unwrap().outcome, RunOutcome::Processed);
+
+        // Transcript, AI and Discord requests all carry the same id
+        let received = server.received_requests().await.unwrap();
+        let ids: std::collections::HashSet<&str> = received
+            .iter()
+            .map(|request| request.headers.get(http_client::REQUEST_ID_HEADER).unwrap().to_str().unwrap())
+            .collect();
+        assert!(received.len() >= 3, "{} request(s)", received.len());
+        assert_eq!(ids.len(), 1);
+        let request_id = ids.into_iter().next().unwrap();
+
+        // ...and so do the Discord logs, which run in the video's span
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let delivery = logs.lines().find(|line| line.contains("Delivering to webhook")).unwrap();
+        assert!(delivery.contains(&format!("request_id={}", request_id)), "{}", delivery);
+    }
+
+    #[tokio::test]
+    async fn test_prepares_run_concurrently_and_during_posts() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::sync::Notify;
+
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let last_prepared = Notify::new();
+        let prepare = |n: u64| {
+            let (in_flight, peak, last_prepared) = (&in_flight, &peak, &last_prepared);
+            async move {
+                peak.fetch_max(in_flight.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10 * n)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if n == 3 {
+                    last_prepared.notify_one();
+                }
+                n
+            }
+        };
+        // The first post only finishes once the last video is prepared, which
+        // needs the prepares to keep going while it waits
+        let post = |n: u64| {
+            let last_prepared = &last_prepared;
+            async move {
+                if n == 1 {
+                    let waited = tokio::time::timeout(Duration::from_secs(5), last_prepared.notified()).await;
+                    assert!(waited.is_ok(), "prepares stalled during a post");
+                }
+                n
+            }
+        };
+
+        let posted: Vec<u64> = prepare_then_post(vec![1, 2, 3], 2, prepare, post).collect().await;
+        assert_eq!(posted, [1, 2, 3]);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_per_video_budget_skips_slow_video_and_continues() {
+        let budget = Some(Duration::from_millis(50));
+        let slow = async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            Ok(ProcessOutcome::skipped(RunOutcome::Processed))
+        };
+        let fast = async { Ok(ProcessOutcome::skipped(RunOutcome::Processed)) };
+
+        let mut outcomes = Vec::new();
+        outcomes.push(run_within_budget(budget, "slow", slow).await.unwrap());
+        outcomes.push(run_within_budget(budget, "fast", fast).await.unwrap());
+        assert_eq!(outcomes[0].outcome, RunOutcome::TimedOut);
+        assert_eq!(outcomes[0].video_id.as_deref(), Some("slow"));
+        assert_eq!(outcomes[1].outcome, RunOutcome::Processed);
+
+        // Without a budget the slow stage is awaited in full
+        let unbounded = async { Ok(ProcessOutcome::skipped(RunOutcome::EmptyTranscript)) };
+        assert_eq!(
+            run_within_budget(None, "any", unbounded).await.unwrap().outcome,
+            RunOutcome::EmptyTranscript
+        );
+    }
 }
@@ -1,7 +1,17 @@
 pub mod http_client;
+#[cfg(feature = "demo")]
 pub mod todo_service;
 pub mod youtube_service;
 pub mod supabase_service;
+#[allow(non_snake_case)]
 pub mod ksForword_service;
+#[allow(non_snake_case)]
 pub mod myAI_service;
-pub mod discord_service;
\ No newline at end of file
+pub mod discord_service;
+pub mod dedup_service;
+pub mod state_service;
+pub mod cache_service;
+pub mod health_service;
+pub mod app;
+#[cfg(feature = "sqlite")]
+pub mod db_service;
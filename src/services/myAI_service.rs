@@ -1,34 +1,282 @@
-use std::fs::Permissions;
+use tokio::time::Duration;
 
-use reqwest::Client;
-use tokio::time::{sleep, Duration};
+use crate::{
+    config::{AiBackend, Config},
+    error::AppError,
+    models::{myAI_response::Root, openai_response::ChatCompletion},
+    services::http_client,
+    utils::{
+        metrics,
+        redact::size_label,
+        retry::{retry, RetryPolicy},
+    },
+};
 
-use crate::{config::Config, models::myAI_response::Root};
+use serde_json::{json, Value};
+use tracing::{debug, error, info, warn};
 
-use serde_json::json;
+/// Upper bound on the combined size of few-shot examples, in chars
+const MAX_FEW_SHOT_CHARS: usize = 20_000;
 
-/// Legacy method - sends simple text content to AI service
-/// Deprecated: Use `chat_with_ai_v2` instead
-pub async fn chat_with_ai(
+/// Build the chat messages: few-shot examples as alternating user/assistant
+/// turns, followed by the real user message. Examples that would push the total
+/// past `MAX_FEW_SHOT_CHARS` are left out.
+fn build_messages(config: &Config, content: &str) -> Vec<Value> {
+    let mut messages = Vec::new();
+    let mut used = 0usize;
+
+    if let Some(prompt) = &config.ai_system_prompt {
+        messages.push(json!({ "role": "system", "content": prompt }));
+    }
+
+    for example in &config.few_shot_examples {
+        let size = example.input.chars().count() + example.output.chars().count();
+        if used + size > MAX_FEW_SHOT_CHARS {
+            tracing::warn!(
+                "Skipping few-shot examples beyond {} chars to stay within context",
+                MAX_FEW_SHOT_CHARS
+            );
+            break;
+        }
+        used += size;
+        messages.push(json!({ "role": "user", "content": example.input }));
+        messages.push(json!({ "role": "assistant", "content": example.output }));
+    }
+
+    messages.push(json!({ "role": "user", "content": content }));
+    messages
+}
+
+/// Estimates above this are logged as a warning before the call
+const WARN_ESTIMATED_TOKENS: usize = 25_000;
+
+/// Rough token count for `content`, without a tokenizer.
+///
+/// ASCII letter/digit runs count one token per four characters (at least one),
+/// each punctuation mark counts as its own token, and other scripts (e.g. Thai,
+/// which has no spaces between words) count one token per two characters.
+pub fn estimate_tokens(content: &str) -> usize {
+    fn flush(run: &mut usize, per_token: usize, total: &mut usize) {
+        if *run > 0 {
+            *total += run.div_ceil(per_token);
+            *run = 0;
+        }
+    }
+
+    let mut total = 0usize;
+    let mut ascii_run = 0usize;
+    let mut other_run = 0usize;
+
+    for c in content.chars() {
+        if c.is_ascii_alphanumeric() {
+            flush(&mut other_run, 2, &mut total);
+            ascii_run += 1;
+        } else if c.is_alphanumeric() || (!c.is_ascii() && !c.is_whitespace() && !c.is_ascii_punctuation()) {
+            flush(&mut ascii_run, 4, &mut total);
+            other_run += 1;
+        } else {
+            flush(&mut ascii_run, 4, &mut total);
+            flush(&mut other_run, 2, &mut total);
+            if !c.is_whitespace() {
+                total += 1;
+            }
+        }
+    }
+    flush(&mut ascii_run, 4, &mut total);
+    flush(&mut other_run, 2, &mut total);
+    total
+}
+
+/// Warn when a request's token estimate is large, and refuse it when it
+/// exceeds `max_estimated_tokens`
+fn check_token_budget(config: &Config, estimate: usize) -> Result<(), Box<dyn std::error::Error>> {
+    debug!("Estimated request tokens: {}", estimate);
+    if let Some(max) = config.max_estimated_tokens {
+        if estimate > max {
+            return Err(crate::error::AppError::ai_service(format!(
+                "estimated {} tokens exceeds MAX_ESTIMATED_TOKENS={}",
+                estimate, max
+            ))
+            .into());
+        }
+    }
+    if estimate > WARN_ESTIMATED_TOKENS {
+        tracing::warn!("Large AI request: about {} estimated tokens", estimate);
+    }
+    Ok(())
+}
+
+/// Add the optional generation settings (`temperature`, `max_tokens`) to a request body
+fn apply_generation_options(config: &Config, body: &mut Value) {
+    if let Some(temperature) = config.ai_temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(max_tokens) = config.ai_max_tokens {
+        body["max_tokens"] = json!(max_tokens);
+    }
+}
+
+/// Per-attempt timeout for `chat_with_client` and `chat_with_ai_v2`, so a hung backend cannot block the run
+const AI_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Attempts made by each AI call on network errors and 5xx/429 responses
+const AI_MAX_ATTEMPTS: usize = 3;
+
+/// Build the legacy chat POST with its headers and request timeout
+pub(crate) fn ai_request(client: &reqwest::Client, url: &str, api_key: &str, body: &Value) -> reqwest::RequestBuilder {
+    http_client::with_request_id(client.post(url))
+        .header("accept", "application/json")
+        .header("content-type", "application/json")
+        .header("X-API-Key", api_key)
+        .timeout(AI_REQUEST_TIMEOUT)
+        .json(body)
+}
+
+/// Build an OpenAI-compatible `/chat/completions` POST with bearer auth
+pub(crate) fn openai_request(client: &reqwest::Client, url: &str, api_key: &str, body: &Value) -> reqwest::RequestBuilder {
+    http_client::with_request_id(client.post(url))
+        .header("accept", "application/json")
+        .bearer_auth(api_key)
+        .timeout(AI_REQUEST_TIMEOUT)
+        .json(body)
+}
+
+/// Standard `/chat/completions` body: the configured model, the messages and
+/// the optional generation settings
+pub(crate) fn openai_body(config: &Config, messages: Vec<Value>) -> Value {
+    let mut body = json!({
+        "model": config.ai_model.as_deref().unwrap_or_default(),
+        "messages": messages
+    });
+    apply_generation_options(config, &mut body);
+    body
+}
+
+/// Retry 5xx, 429 and network failures: `AI_MAX_ATTEMPTS` attempts, about 2s
+/// then 4s apart, with room for every attempt to run to `AI_REQUEST_TIMEOUT`
+fn ai_retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: AI_MAX_ATTEMPTS,
+        base_delay: Duration::from_secs(2),
+        max_elapsed: AI_REQUEST_TIMEOUT * AI_MAX_ATTEMPTS as u32,
+        ..RetryPolicy::default()
+    }
+}
+
+/// Send the request `build` makes under `ai_retry_policy` and return the body
+/// of the first successful response. A failure left once retries run out (or a
+/// non-retryable status such as 401) comes back as `AppError::AIService`.
+async fn send_with_retry(
+    config: &Config,
+    url: &str,
+    body: &Value,
+    build: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<String, AppError> {
+    let build = &build;
+    retry(&ai_retry_policy(), AppError::is_retryable, |attempt| async move {
+        debug!("Sending AI request (attempt {}/{})", attempt, AI_MAX_ATTEMPTS);
+        http_client::trace_request(config, "POST", url, Some(&body.to_string()));
+        let resp = http_client::timed_send("ai chat", build().send()).await?;
+
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let text = resp.text().await.unwrap_or_default();
+        http_client::trace_response(config, url, status, &headers, &text);
+        if status.is_success() {
+            return Ok(text);
+        }
+        if status.as_u16() == 401 {
+            error!("❌ Authentication failed - check MY_AI_API_KEY");
+        }
+        warn!("AI API {} returned {} with body: {}", url, status, text);
+        Err(AppError::ApiError {
+            url: url.to_string(),
+            status: status.as_u16(),
+        })
+    })
+    .await
+    .map_err(|err| AppError::ai_service(format!("request failed: {}", err)))
+}
+
+/// Parse a successful custom-backend response. Plain text is taken as the
+/// answer, and JSON followed by stray characters is cut down to its outer object.
+fn parse_answer(config: &Config, url: &str, text: &str) -> Result<Root, AppError> {
+    let trimmed = text.trim();
+    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+        // Server returned plain text instead of JSON
+        error!("=== Server returned plain text instead of JSON ===");
+        error!("URL: {}", url);
+        error!("Response length: {} chars", text.chars().count());
+        debug!(
+            "Response preview (first 200 chars): {}",
+            &text.chars().take(200).collect::<String>()
+        );
+        error!("==============================================");
+        return Ok(Root {
+            answer: text.to_string(),
+            events: vec![],
+            session_id: "unknown".to_string(),
+            context_used: false,
+        });
+    }
+
+    let parse_err = match Root::from_api_json(text, &config.ai_answer_path) {
+        Ok(root) => return Ok(root),
+        Err(parse_err) => parse_err,
+    };
+
+    // Try to extract JSON object from the response
+    let extracted = match (text.find('{'), text.rfind('}')) {
+        (Some(start), Some(end)) if start < end => Root::from_api_json(&text[start..=end], &config.ai_answer_path).ok(),
+        _ => None,
+    };
+    if let Some(root) = extracted {
+        warn!("⚠️  Extracted JSON from response with trailing characters");
+        return Ok(root);
+    }
+
+    error!("=== JSON Parse Error ===");
+    error!("URL: {}", url);
+    error!("Response body length: {} bytes", text.len());
+    debug!("Full response body:\n{}", text);
+    error!("Parse error: {}", parse_err);
+    error!("========================");
+    Err(AppError::ai_service(format!("failed to parse AI response from {}: {}", url, parse_err)))
+}
+
+/// Log the response's reasoning events at debug level when `AI_DEBUG_EVENTS` is set
+pub fn log_events(config: &Config, response: &Root) {
+    if !config.ai_debug_events {
+        return;
+    }
+    debug!("AI returned {} event(s)", response.events.len());
+    for event in &response.events {
+        debug!("  {}", event.describe());
+    }
+}
+
+/// Legacy method - sends simple text content to AI service on `client`
+pub(crate) async fn chat_with_client(
     config: &Config,
+    client: &reqwest::Client,
     content: String,
 ) -> Result<Root, Box<dyn std::error::Error>> {
-    println!("Sending to myAI API (legacy format)");
+    info!("Sending to myAI API (legacy format)");
+    metrics::record(|m| m.ai_calls += 1);
     let myAI_url = &config.my_ai_api_url;
     let api_key = &config.my_ai_api_key;
-    println!("myAI_url: {}", myAI_url);
-    println!("API Key length: {} chars", api_key.len());
-    println!("API Key preview: {}...{}", &api_key[..8.min(api_key.len())], &api_key[api_key.len().saturating_sub(8)..]);
+    debug!("myAI_url: {}", myAI_url);
+    debug!("API Key length: {} chars", api_key.chars().count());
 
     // Log content size to help debug
     let content_len = content.chars().count();
-    println!("Request content length: {} chars", content_len);
+    debug!("Request content length: {} chars", content_len);
     if content_len > 10000 {
-        println!(
+        warn!(
             "WARNING: Content is very long ({} chars), this may cause issues",
             content_len
         );
-        println!(
+        debug!(
             "Content preview (first 200 chars): {}",
             &content.chars().take(200).collect::<String>()
         );
@@ -37,7 +285,7 @@ pub async fn chat_with_ai(
     // Truncate content if it's too long
     const MAX_CONTENT_LENGTH: usize = 100000;
     let processed_content = if content_len > MAX_CONTENT_LENGTH {
-        println!(
+        info!(
             "Truncating content from {} to {} chars",
             content_len, MAX_CONTENT_LENGTH
         );
@@ -46,140 +294,44 @@ pub async fn chat_with_ai(
         content
     };
 
-    let client = Client::new();
-    let body = json!({
-        "persona": "ks-summary",
-        "user_id": "ks-summary",
-        "messages": [
-            {
-                "role": "user",
-                "content": processed_content
-            }
-        ]
+    let messages = build_messages(config, &processed_content);
+    let estimate = messages
+        .iter()
+        .map(|message| estimate_tokens(message["content"].as_str().unwrap_or_default()))
+        .sum();
+    check_token_budget(config, estimate)?;
+
+    if config.ai_backend == AiBackend::OpenAi {
+        return chat_with_ai_openai(config, client, messages).await;
+    }
+
+    let mut body = json!({
+        "persona": config.ai_persona,
+        "user_id": config.ai_user_id,
+        "messages": messages
     });
+    apply_generation_options(config, &mut body);
 
-    // Retry policy
-    let max_retries = 3usize;
-    let mut attempt = 0usize;
-
-    loop {
-        attempt += 1;
-        let resp_result = client
-            .post(myAI_url)
-            .header("accept", "application/json")
-            .header("content-type", "application/json")
-            .header("X-API-Key", api_key) // ✅ Added X-API-Key header
-            .json(&body)
-            .send()
-            .await;
+    let text = send_with_retry(config, myAI_url, &body, || ai_request(client, myAI_url, api_key, &body)).await?;
+    Ok(parse_answer(config, myAI_url, &text)?)
+}
 
-        match resp_result {
-            Ok(resp) => {
-                let status = resp.status();
-                let url = resp.url().clone();
-                let headers = resp.headers().clone();
-                let text = resp.text().await.unwrap_or_default();
-                if status.is_success() {
-                    // Check if response is actually JSON
-                    let trimmed = text.trim();
-                    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
-                        // Server returned plain text instead of JSON
-                        eprintln!("=== Server returned plain text instead of JSON ===");
-                        eprintln!("URL: {}", url);
-                        eprintln!("Response length: {} chars", text.len());
-                        eprintln!(
-                            "Response preview (first 200 chars): {}",
-                            &text.chars().take(200).collect::<String>()
-                        );
-                        eprintln!("==============================================");
-
-                        // Create a mock Root response with the plain text as the answer
-                        let fallback_response = Root {
-                            answer: text.clone(),
-                            events: vec![],
-                            session_id: "unknown".to_string(),
-                            context_used: false,
-                        };
-                        return Ok(fallback_response);
-                    }
+/// Send chat messages to an OpenAI-compatible backend (`AI_BACKEND=openai`) and
+/// return `choices[0].message.content` as the answer. Retries like `chat_with_client`.
+async fn chat_with_ai_openai(
+    config: &Config,
+    client: &reqwest::Client,
+    messages: Vec<Value>,
+) -> Result<Root, Box<dyn std::error::Error>> {
+    info!("Sending to OpenAI-compatible chat completions API");
+    let url = &config.my_ai_api_url;
+    let body = openai_body(config, messages);
 
-                    // Parse JSON - handle cases where API returns extra characters after JSON
-                    let parsed: Result<Root, _> = serde_json::from_str(&text);
-                    match parsed {
-                        Ok(root) => return Ok(root),
-                        Err(parse_err) => {
-                            // Try to extract JSON object from the response
-                            let fallback_result = if let Some(start) = text.find('{') {
-                                if let Some(end) = text.rfind('}') {
-                                    let json_str = &text[start..=end];
-                                    serde_json::from_str::<Root>(json_str).ok()
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            };
-
-                            if let Some(root) = fallback_result {
-                                eprintln!(
-                                    "=== JSON extracted from response with trailing characters ==="
-                                );
-                                return Ok(root);
-                            }
-
-                            // Enhanced error logging
-                            eprintln!("=== JSON Parse Error ===");
-                            eprintln!("Status: {}", status);
-                            eprintln!("URL: {}", url);
-                            eprintln!("Response body length: {} bytes", text.len());
-                            eprintln!(
-                                "Response body preview (first 200 chars): {}",
-                                &text.chars().take(200).collect::<String>()
-                            );
-                            eprintln!("Full response body:\n{}", text);
-                            eprintln!("Parse error: {}", parse_err);
-                            eprintln!("========================");
-                            return Err(format!(
-                                "Failed to parse myAI response JSON: {}\nurl: {}\nheaders: {:?}\nbody: {}",
-                                parse_err, url, headers, text
-                            )
-                            .into());
-                        }
-                    }
-                } else if status.is_server_error() && attempt < max_retries {
-                    // 5xx — retry after backoff
-                    eprintln!(
-                        "myAI API returned server error ({}). attempt {}/{}. url: {}\nheaders: {:?}\nbody: {}",
-                        status, attempt, max_retries, url, headers, text
-                    );
-                    let backoff = Duration::from_millis(500 * (attempt as u64));
-                    sleep(backoff).await;
-                    continue;
-                } else {
-                    // Client error (4xx) or server error after retries
-                    return Err(format!(
-                        "myAI API request failed with status {}. url: {}\nheaders: {:?}\nbody: {}",
-                        status, url, headers, text
-                    )
-                    .into());
-                }
-            }
-            Err(err) => {
-                // Network/transport error; may be transient
-                if attempt < max_retries {
-                    eprintln!(
-                        "myAI request error (attempt {}/{}): {}. retrying...",
-                        attempt, max_retries, err
-                    );
-                    let backoff = Duration::from_millis(500 * (attempt as u64));
-                    sleep(backoff).await;
-                    continue;
-                } else {
-                    return Err(format!("myAI request failed: {}", err).into());
-                }
-            }
-        }
-    }
+    let text = send_with_retry(config, url, &body, || {
+        openai_request(client, url, &config.my_ai_api_key, &body)
+    })
+    .await?;
+    Ok(ChatCompletion::from_api_json(&text)?.into_root()?)
 }
 
 /// New V2 API method - uses updated API format with X-API-Key header and structured content
@@ -198,20 +350,22 @@ pub async fn chat_with_ai(
 /// ```
 pub async fn chat_with_ai_v2(
     config: &Config,
+    client: &reqwest::Client,
     persona: &str,
     content: &str,
 ) -> Result<Root, Box<dyn std::error::Error>> {
-    println!("Sending to myAI API v2");
+    info!("Sending to myAI API v2");
+    metrics::record(|m| m.ai_calls += 1);
     let myAI_url = &config.my_ai_api_url;
     let api_key = &config.my_ai_api_key;
-    println!("myAI_url: {}", myAI_url);
-    println!("persona: {}", persona);
+    debug!("myAI_url: {}", myAI_url);
+    info!("persona: {}", persona);
 
     // Log content size
     let content_len = content.chars().count();
-    println!("Request content length: {} chars", content_len);
+    debug!("Request content length: {} chars", content_len);
     if content_len > 10000 {
-        println!(
+        warn!(
             "WARNING: Content is very long ({} chars), this may cause issues",
             content_len
         );
@@ -220,7 +374,7 @@ pub async fn chat_with_ai_v2(
     // Truncate content if needed
     const MAX_CONTENT_LENGTH: usize = 100000;
     let processed_content = if content_len > MAX_CONTENT_LENGTH {
-        println!(
+        info!(
             "Truncating content from {} to {} chars",
             content_len, MAX_CONTENT_LENGTH
         );
@@ -229,10 +383,16 @@ pub async fn chat_with_ai_v2(
         content.to_string()
     };
 
-    let client = Client::new();
+    check_token_budget(config, estimate_tokens(&processed_content))?;
+
+    if config.ai_backend == AiBackend::OpenAi {
+        // OpenAI has no personas; the content goes as a plain user message
+        let messages = vec![json!({ "role": "user", "content": processed_content })];
+        return chat_with_ai_openai(config, client, messages).await;
+    }
 
     // New API format with structured content
-    let body = json!({
+    let mut body = json!({
         "persona": persona,
         "messages": [
             {
@@ -246,145 +406,218 @@ pub async fn chat_with_ai_v2(
             }
         ]
     });
+    apply_generation_options(config, &mut body);
 
-    println!(
+    info!(
         "Request body: {}",
         serde_json::to_string_pretty(&body).unwrap_or_default()
     );
 
-    // Retry policy
-    let max_retries = 3usize;
-    let mut attempt = 0usize;
-
-    loop {
-        attempt += 1;
-        println!("Sending request (attempt {}/{})...", attempt, max_retries);
-        println!("API Key: {}", api_key);
-        let resp_result = client
-            .post(myAI_url)
-            .header("accept", "application/json")
-            .header("content-type", "application/json")
-            .header("X-API-Key", api_key)
-            .json(&body)
-            .send()
-            .await;
-
-        match resp_result {
-            Ok(resp) => {
-                let status = resp.status();
-                let url = resp.url().clone();
-                let headers = resp.headers().clone();
-                let text = resp.text().await.unwrap_or_default();
-
-                println!("Response status: {}", status);
-                println!("Response length: {} chars", text.len());
-
-                if status.is_success() {
-                    // Check if response is JSON
-                    let trimmed = text.trim();
-                    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
-                        eprintln!("=== Server returned non-JSON response ===");
-                        eprintln!("URL: {}", url);
-                        eprintln!(
-                            "Response preview: {}",
-                            &text.chars().take(200).collect::<String>()
-                        );
-
-                        // Fallback response
-                        let fallback_response = Root {
-                            answer: text.clone(),
-                            events: vec![],
-                            session_id: "unknown".to_string(),
-                            context_used: false,
-                        };
-                        return Ok(fallback_response);
-                    }
-
-                    // Parse JSON
-                    let parsed: Result<Root, _> = serde_json::from_str(&text);
-                    match parsed {
-                        Ok(root) => {
-                            println!("✅ Successfully parsed AI response");
-                            println!("Session ID: {}", root.session_id);
-                            return Ok(root);
-                        }
-                        Err(parse_err) => {
-                            // Try to extract JSON from response with trailing characters
-                            let fallback_result = if let Some(start) = text.find('{') {
-                                if let Some(end) = text.rfind('}') {
-                                    let json_str = &text[start..=end];
-                                    serde_json::from_str::<Root>(json_str).ok()
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            };
-
-                            if let Some(root) = fallback_result {
-                                eprintln!(
-                                    "⚠️  Extracted JSON from response with trailing characters"
-                                );
-                                return Ok(root);
-                            }
-
-                            eprintln!("=== JSON Parse Error ===");
-                            eprintln!("Status: {}", status);
-                            eprintln!("Parse error: {}", parse_err);
-                            eprintln!(
-                                "Response preview: {}",
-                                &text.chars().take(500).collect::<String>()
-                            );
-                            return Err(
-                                format!("Failed to parse AI response: {}", parse_err).into()
-                            );
-                        }
-                    }
-                } else if status.is_server_error() && attempt < max_retries {
-                    eprintln!(
-                        "⚠️  Server error ({}) - retrying (attempt {}/{})",
-                        status, attempt, max_retries
-                    );
-                    let backoff = Duration::from_millis(500 * (attempt as u64));
-                    sleep(backoff).await;
-                    continue;
-                } else if status.as_u16() == 401 {
-                    eprintln!("❌ Authentication failed - check MY_AI_API_KEY");
-                    return Err(format!(
-                        "Authentication failed with status {}. Please check MY_AI_API_KEY.",
-                        status
-                    )
-                    .into());
-                } else {
-                    return Err(format!(
-                        "AI API request failed with status {}. body: {}",
-                        status, text
-                    )
-                    .into());
-                }
-            }
-            Err(err) => {
-                if attempt < max_retries {
-                    eprintln!(
-                        "⚠️  Request error (attempt {}/{}): {}",
-                        attempt, max_retries, err
-                    );
-                    let backoff = Duration::from_millis(500 * (attempt as u64));
-                    sleep(backoff).await;
-                    continue;
-                } else {
-                    return Err(format!("AI request failed: {}", err).into());
-                }
-            }
-        }
-    }
+    let text = send_with_retry(config, myAI_url, &body, || ai_request(client, myAI_url, api_key, &body)).await?;
+    debug!("Response size: {}", size_label(&text));
+    let root = parse_answer(config, myAI_url, &text)?;
+    info!("✅ Successfully parsed AI response");
+    debug!("Session ID: {}", root.session_id);
+    Ok(root)
 }
 
 /// Discord-specific method - formats AI response for Discord
 /// Uses "ks-discord" persona for optimized Discord message formatting
 pub async fn chat_with_ai_msg4Discord(
     config: &Config,
+    client: &reqwest::Client,
     content: String,
 ) -> Result<Root, Box<dyn std::error::Error>> {
-    chat_with_ai_v2(config, "ks-discord", &content).await
+    chat_with_ai_v2(config, client, "ks-discord", &content).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FewShotExample;
+
+    #[test]
+    fn test_generation_options_only_when_configured() {
+        let mut body = json!({ "persona": "ks-summary" });
+        apply_generation_options(&Config::default(), &mut body);
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("max_tokens").is_none());
+
+        let config = Config {
+            ai_temperature: Some(0.5),
+            ai_max_tokens: Some(800),
+            ..Config::default()
+        };
+        apply_generation_options(&config, &mut body);
+        let serialized = body.to_string();
+        assert!(serialized.contains("\"temperature\":0.5"));
+        assert!(serialized.contains("\"max_tokens\":800"));
+    }
+
+    #[test]
+    fn test_few_shot_examples_are_prior_turns() {
+        let config = Config {
+            few_shot_examples: vec![FewShotExample {
+                input: "example transcript".to_string(),
+                output: "example summary".to_string(),
+            }],
+            ..Config::default()
+        };
+        let body = json!({ "messages": build_messages(&config, "real transcript") });
+        let messages = body["messages"].as_array().unwrap();
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["role"], "user");
+        assert_eq!(messages[0]["content"], "example transcript");
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["content"], "example summary");
+        assert_eq!(messages[2]["content"], "real transcript");
+    }
+
+    #[test]
+    fn test_ai_request_has_timeout() {
+        let client = reqwest::Client::new();
+        let request = ai_request(&client, "http://localhost:8000/chat", "key", &json!({}))
+            .build()
+            .unwrap();
+        assert_eq!(request.timeout(), Some(&AI_REQUEST_TIMEOUT));
+        assert_eq!(request.headers()["X-API-Key"], "key");
+    }
+
+    #[test]
+    fn test_system_prompt_comes_first() {
+        assert_eq!(build_messages(&Config::default(), "real transcript").len(), 1);
+
+        let config = Config {
+            ai_system_prompt: Some("Summarize in Thai bullet points".to_string()),
+            ..Config::default()
+        };
+        let messages = build_messages(&config, "real transcript");
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["role"], "system");
+        assert_eq!(messages[0]["content"], "Summarize in Thai bullet points");
+        assert_eq!(messages[1]["role"], "user");
+        assert_eq!(messages[1]["content"], "real transcript");
+    }
+
+    #[test]
+    fn test_few_shot_examples_are_capped() {
+        let big = "x".repeat(MAX_FEW_SHOT_CHARS);
+        let config = Config {
+            few_shot_examples: vec![
+                FewShotExample { input: "a".to_string(), output: "b".to_string() },
+                FewShotExample { input: big, output: "c".to_string() },
+            ],
+            ..Config::default()
+        };
+        assert_eq!(build_messages(&config, "real").len(), 3);
+    }
+
+    #[test]
+    fn test_estimate_tokens_for_known_text() {
+        // 9 words, several longer than 4 chars, plus 2 punctuation marks
+        let english = "The quick brown fox jumps over the lazy dog.";
+        let estimate = estimate_tokens(english);
+        assert!((9..=14).contains(&estimate), "estimate {}", estimate);
+
+        // Thai has no spaces between words; roughly one token per two chars
+        let thai = "ตลาดหุ้นไทยปรับตัวขึ้น";
+        let thai_estimate = estimate_tokens(thai);
+        let chars = thai.chars().count();
+        assert!(thai_estimate >= chars / 3 && thai_estimate <= chars, "estimate {}", thai_estimate);
+
+        assert_eq!(estimate_tokens("   "), 0);
+    }
+
+    #[test]
+    fn test_token_budget_blocks_when_configured() {
+        assert!(check_token_budget(&Config::default(), 1_000_000).is_ok());
+
+        let config = Config {
+            max_estimated_tokens: Some(100),
+            ..Config::default()
+        };
+        assert!(check_token_budget(&config, 100).is_ok());
+        let err = check_token_budget(&config, 101).unwrap_err();
+        assert!(err.to_string().contains("MAX_ESTIMATED_TOKENS=100"));
+    }
+
+    #[tokio::test]
+    async fn test_openai_backend_parses_choice_content() {
+        use wiremock::matchers::{body_partial_json, header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("authorization", "Bearer sk-test"))
+            .and(body_partial_json(json!({
+                "model": "gpt-4o-mini",
+                "messages": [{ "role": "user", "content": "summarize this" }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"id":"chatcmpl-1","choices":[{"index":0,"message":{"role":"assistant","content":"the summary"},"finish_reason":"stop"}]}"#,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = Config::builder()
+            .my_ai_api_url(format!("{}/v1/chat/completions", server.uri()))
+            .my_ai_api_key("sk-test")
+            .ai_backend(AiBackend::OpenAi)
+            .ai_model(Some("gpt-4o-mini".to_string()))
+            .build();
+        let root = chat_with_client(&config, &reqwest::Client::new(), "summarize this".to_string())
+            .await
+            .unwrap();
+        assert_eq!(root.answer, "the summary");
+        assert_eq!(root.session_id, "chatcmpl-1");
+    }
+
+    #[tokio::test]
+    async fn test_v2_parse_failure_is_ai_service_error() {
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("X-API-Key", "key"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"answer": 42"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = Config::builder()
+            .my_ai_api_url(format!("{}/chat", server.uri()))
+            .my_ai_api_key("key")
+            .build();
+        let err = chat_with_ai_v2(&config, &reqwest::Client::new(), "ks-discord", "summarize this").await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<AppError>(), Some(AppError::AIService(_))), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_openai_auth_failure_is_ai_service_error_without_retry() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = Config::builder()
+            .my_ai_api_url(format!("{}/v1/chat/completions", server.uri()))
+            .ai_backend(AiBackend::OpenAi)
+            .build();
+        let err = chat_with_client(&config, &reqwest::Client::new(), "summarize this".to_string())
+            .await
+            .unwrap_err();
+        assert!(matches!(err.downcast_ref::<AppError>(), Some(AppError::AIService(_))), "{}", err);
+        assert!(err.to_string().contains("401"));
+    }
+
 }
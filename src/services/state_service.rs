@@ -0,0 +1,77 @@
+//! Persistent record of videos that have already been handled
+//!
+//! The state file drives incremental catch-up: each run processes matching
+//! videos that are not in the file yet. A missing file means a first run.
+use crate::error::{AppError, Result};
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::fs;
+
+/// How many video ids are remembered; older ones have long left the search page
+const MAX_SEEN: usize = 500;
+
+/// Video ids already handled, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SeenState {
+    pub seen: Vec<String>,
+}
+
+impl SeenState {
+    /// Load the state file; `None` when it does not exist yet (first run)
+    pub async fn load(path: &str) -> Result<Option<Self>> {
+        if !Path::new(path).exists() {
+            return Ok(None);
+        }
+        let raw = fs::read_to_string(path).await?;
+        serde_json::from_str(&raw)
+            .map(Some)
+            .map_err(|e| AppError::cache(format!("state file '{}': {}", path, e)))
+    }
+
+    /// Write the state file, creating its directory if needed
+    pub async fn save(&self, path: &str) -> Result<()> {
+        let raw = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::cache(format!("state file '{}': {}", path, e)))?;
+        if let Some(dir) = Path::new(path).parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        fs::write(path, raw).await?;
+        Ok(())
+    }
+
+    /// Whether a video has already been handled
+    pub fn contains(&self, video_id: &str) -> bool {
+        self.seen.iter().any(|id| id == video_id)
+    }
+
+    /// Remember a handled video, dropping the oldest beyond `MAX_SEEN`
+    pub fn mark(&mut self, video_id: &str) {
+        if self.contains(video_id) {
+            return;
+        }
+        self.seen.push(video_id.to_string());
+        if self.seen.len() > MAX_SEEN {
+            let excess = self.seen.len() - MAX_SEEN;
+            self.seen.drain(..excess);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark_is_idempotent_and_bounded() {
+        let mut state = SeenState::default();
+        state.mark("a");
+        state.mark("a");
+        assert_eq!(state.seen, vec!["a".to_string()]);
+
+        for i in 0..MAX_SEEN {
+            state.mark(&format!("v{}", i));
+        }
+        assert_eq!(state.seen.len(), MAX_SEEN);
+        assert!(!state.contains("a"));
+    }
+}
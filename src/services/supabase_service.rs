@@ -1,131 +1,359 @@
-use crate::config::{self};
+use crate::config::Config;
+use crate::error::{AppError, Result};
 
 use crate::models::youtube_transcript::Root;
+use crate::services::cache_service::{get_cached_transcript, put_cached_transcript};
+use crate::services::http_client;
+use crate::services::youtube_service::parse_video_id;
 use reqwest::Client;
+use crate::utils::retry::{retry, Jitter, RetryPolicy};
 use std::time::Duration;
-use tokio::time::sleep;
-use std::path::Path;
-use tokio::fs;
-
-// Helper function to extract video ID from YouTube URL
-fn extract_video_id_from_url(url: &str) -> Option<String> {
-    // Handle various YouTube URL formats:
-    // - https://www.youtube.com/watch?v=VIDEO_ID
-    // - https://youtu.be/VIDEO_ID
-    // - https://www.youtube.com/embed/VIDEO_ID
-    
-    if let Some(pos) = url.find("v=") {
-        let start = pos + 2;
-        let end = url[start..].find('&').map(|p| start + p).unwrap_or(url.len());
-        return Some(url[start..end].to_string());
+use tracing::{debug, info, warn};
+
+/// Refuse a transcript whose size exceeds `max_transcript_bytes`, before it is parsed
+pub(crate) fn check_transcript_size(config: &Config, bytes: usize) -> Result<()> {
+    match config.max_transcript_bytes {
+        Some(max) if bytes > max => Err(AppError::TranscriptTooLarge { bytes, max }),
+        _ => Ok(()),
     }
-    
-    if let Some(pos) = url.find("youtu.be/") {
-        let start = pos + 9;
-        let end = url[start..].find('?').map(|p| start + p).unwrap_or(url.len());
-        return Some(url[start..end].to_string());
+}
+
+/// Body fragments the transcript API uses when a video has no transcript to give
+const UNAVAILABLE_MARKERS: &[&str] = &[
+    "transcript-unavailable",
+    "transcripts are disabled",
+    "transcript is disabled",
+    "no transcript",
+];
+
+/// Whether a response says the video has no transcript (disabled or never
+/// generated), which no amount of retrying will change. Supadata answers this
+/// with a non-200 status (206 or 404) and an error body.
+fn is_transcript_unavailable(status: reqwest::StatusCode, body: &str) -> bool {
+    if status == reqwest::StatusCode::OK {
+        return false;
     }
-    
-    None
+    let body = body.to_lowercase();
+    UNAVAILABLE_MARKERS.iter().any(|marker| body.contains(marker))
 }
 
-pub async fn get_youtube_transcript(url: &str) -> Result<Root, Box<dyn std::error::Error>> {
-    let supabase_url = "https://api.supadata.ai/v1/transcript";
+/// True when `transcript` is in `lang`. Only a transcript that does not state
+/// its language is judged by whether the API lists `lang` as available.
+fn serves_lang(transcript: &Root, lang: &str) -> bool {
+    match &transcript.lang {
+        Some(served) => served.eq_ignore_ascii_case(lang),
+        None => lists_lang(transcript, lang),
+    }
+}
 
+/// Whether the API lists `lang` among the video's transcript languages
+fn lists_lang(transcript: &Root, lang: &str) -> bool {
+    transcript.available_langs.iter().any(|l| l.eq_ignore_ascii_case(lang))
+}
+
+/// Fetch a transcript from the endpoint configured in `config`
+/// (`SUPADATA_BASE_URL` + `SUPADATA_TRANSCRIPT_PATH`)
+pub async fn get_youtube_transcript(config: &Config, client: &Client, url: &str) -> Result<Root> {
+    fetch_transcript(config, client, url, None).await
+}
+
+/// Fetch the transcript in `lang`, falling back to the default transcript when
+/// the video has no track in that language. A track listed as available but
+/// not served is kept as it came, in its own language, without a re-fetch.
+pub async fn get_youtube_transcript_lang(
+    config: &Config,
+    client: &Client,
+    url: &str,
+    lang: &str,
+) -> Result<Root> {
+    match fetch_transcript(config, client, url, Some(lang)).await {
+        Ok(transcript) if serves_lang(&transcript, lang) => Ok(transcript),
+        Ok(transcript) if lists_lang(&transcript, lang) => {
+            warn!(
+                "Asked for a '{}' transcript but got '{}'; using it as is",
+                lang,
+                transcript.lang.as_deref().unwrap_or_default()
+            );
+            Ok(transcript)
+        }
+        Ok(transcript) => {
+            info!(
+                "Transcript language '{}' not available (available: {}); using default transcript",
+                lang,
+                transcript.available_langs.join(", ")
+            );
+            get_youtube_transcript(config, client, url).await
+        }
+        Err(AppError::TranscriptNotFound { .. }) => {
+            info!("No '{}' transcript; falling back to default transcript", lang);
+            get_youtube_transcript(config, client, url).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn fetch_transcript(
+    config: &Config,
+    client: &Client,
+    url: &str,
+    lang: Option<&str>,
+) -> Result<Root> {
     if url.trim().is_empty() {
-        return Err("youtube url is empty".into());
+        return Err(AppError::youtube("youtube url is empty"));
     }
 
-    // Extract video ID for cache filename
-    let video_id = extract_video_id_from_url(url)
-        .ok_or("Failed to extract video ID from URL")?;
-    
-    // Create cache directory if it doesn't exist
-    let cache_dir = "transcript_cache";
-    fs::create_dir_all(cache_dir).await?;
-    
-    let cache_file = format!("{}/{}.json", cache_dir, video_id);
-    
-    // Check if cached file exists
-    if Path::new(&cache_file).exists() {
-        println!("Loading transcript from cache: {}", cache_file);
-        let cached_data = fs::read_to_string(&cache_file).await?;
-        let transcript: Root = serde_json::from_str(&cached_data)?;
+    // Extract video ID for the cache key
+    let video_id = parse_video_id(url)
+        .ok_or_else(|| AppError::youtube(format!("failed to extract video ID from '{}'", url)))?;
+    let cache_key = match lang {
+        Some(lang) => format!("{}.{}", video_id, lang),
+        None => video_id.clone(),
+    };
+
+    let transcript_url = config.transcript_endpoint();
+    let supabase_url = transcript_url.as_str();
+
+    if let Some(transcript) = get_cached_transcript(config, &cache_key).await? {
         return Ok(transcript);
     }
     
-    println!("Cache miss - fetching transcript from API for video: {}", video_id);
+    info!("Cache miss - fetching transcript from API for video: {}", video_id);
 
-    let supabase_key = config::Config::from_env()?.supabase_api_key;
+    let supabase_key = config.supabase_api_key.clone();
     if supabase_key.trim().is_empty() {
-        return Err("SUPABASE_API_KEY is empty; set the secret/env before running".into());
+        return Err(AppError::config("SUPABASE_API_KEY is empty; set the secret/env before running"));
     }
 
-    let query_params = [("url", url)];
+    let mut query_params = vec![("url", url)];
+    if let Some(lang) = lang {
+        query_params.push(("lang", lang));
+    }
 
-    let client = Client::new();
-    let max_retries = 3usize;
-    let mut last_error = String::new();
+    // Retry network errors, timeouts, 429s and 5xx responses; auth and other
+    // client errors, and a missing or oversized transcript, are final
+    let policy = RetryPolicy {
+        max_attempts: config.transcript_max_retries.max(1),
+        base_delay: Duration::from_secs(config.transcript_backoff_secs),
+        max_delay: Duration::MAX,
+        max_elapsed: Duration::MAX,
+        jitter: Jitter::Symmetric,
+    };
+    let should_retry = |e: &AppError| e.is_retryable();
+    let (video_id, cache_key, query_params, supabase_key) = (&video_id, &cache_key, &query_params, &supabase_key);
 
-    for attempt in 1..=max_retries {
-        println!(
+    retry(&policy, should_retry, |attempt| async move {
+        info!(
             "Calling transcript API (attempt {}/{}): {}?url={}",
-            attempt, max_retries, supabase_url, url
+            attempt, policy.max_attempts, supabase_url, url
         );
 
-        let response = client
-            .get(supabase_url)
-            .header("x-api-key", &supabase_key)
-            .query(&query_params)
-            .timeout(Duration::from_secs(30))
-            .send()
+        http_client::trace_request(config, "GET", &format!("{}?url={}", supabase_url, url), None);
+        let request = http_client::with_request_id(client.get(supabase_url))
+            .header("x-api-key", supabase_key)
+            .query(query_params)
+            .timeout(Duration::from_secs(30));
+        let resp = http_client::timed_send("transcript fetch", request.send()).await?;
+
+        let status = resp.status();
+        // Refuse early when the server announces an oversized body
+        if let Some(length) = resp.content_length() {
+            check_transcript_size(config, length as usize)?;
+        }
+        let headers = resp.headers().clone();
+        let body = resp.text().await.unwrap_or_default();
+        http_client::trace_response(config, supabase_url, status, &headers, &body);
+
+        // Log the raw response for debugging
+        debug!("=== Transcript API Response ===");
+        debug!("Status: {}", status);
+        debug!("Body size: {} bytes", body.len());
+        debug!("Body preview (first 500 chars): {}", &body.chars().take(500).collect::<String>());
+        //println!("Full body: {}", body);
+        debug!("================================");
+
+        if is_transcript_unavailable(status, &body) {
+            info!("No transcript available for video {} ({})", video_id, status);
+            return Err(AppError::TranscriptNotFound { video_id: video_id.clone() });
+        }
+
+        if !status.is_success() {
+            warn!("Transcript API {} returned {} with body: {}", supabase_url, status, body);
+            return Err(AppError::ApiError {
+                url: supabase_url.to_string(),
+                status: status.as_u16(),
+            });
+        }
+
+        check_transcript_size(config, body.len())?;
+        let transcript = Root::from_api_json(&body)?;
+
+        if let Err(e) = put_cached_transcript(config, cache_key, &transcript).await {
+            warn!("Failed to save transcript to cache: {}", e);
+        }
+
+        Ok(transcript)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_transcript_fetched_from_configured_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/transcript"))
+            .and(header("x-api-key", "sd-key"))
+            .and(query_param("url", "https://youtu.be/dQw4w9WgXcQ"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_transcript.json")))
+            .expect(1)
+            .mount(&server)
             .await;
 
-        match response {
-            Ok(resp) => {
-                let status = resp.status();
-                let body = resp.text().await.unwrap_or_default();
-
-                // Log the raw response for debugging
-                println!("=== Transcript API Response ===");
-                println!("Status: {}", status);
-                println!("Body length: {} chars", body.len());
-                println!("Body preview (first 500 chars): {}", &body.chars().take(500).collect::<String>());
-                //println!("Full body: {}", body);
-                println!("================================");
-
-                if status.is_success() {
-                    let transcript: Root = serde_json::from_str(&body)?;
-                    
-                    // Save to cache file
-                    if let Err(e) = fs::write(&cache_file, &body).await {
-                        eprintln!("Warning: Failed to save transcript to cache: {}", e);
-                    } else {
-                        println!("Transcript saved to cache: {}", cache_file);
-                    }
-                    
-                    return Ok(transcript);
-                } else {
-                    last_error = format!(
-                        "Transcript API {} returned {} with body: {}",
-                        supabase_url, status, body
-                    );
-                }
-            }
-            Err(err) => {
-                last_error = format!("Transcript API request error: {}", err);
+        let config = Config::builder()
+            .supabase_api_key("sd-key")
+            .supadata_base_url(server.uri())
+            .cache_dir(
+                std::env::temp_dir()
+                    .join(format!("ks-transcripts-{}", uuid::Uuid::new_v4()))
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+            .build();
+
+        let transcript = get_youtube_transcript(&config, http_client::client(), "https://youtu.be/dQw4w9WgXcQ").await.unwrap();
+        assert_eq!(transcript.lang.as_deref(), Some("th"));
+        assert!(!transcript.content.is_empty());
+
+        // The second request is served from the cache; `expect(1)` checks on drop
+        let cached = get_youtube_transcript(&config, http_client::client(), "https://youtu.be/dQw4w9WgXcQ").await.unwrap();
+        assert_eq!(cached, transcript);
+        let _ = tokio::fs::remove_dir_all(&config.cache_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_malformed_transcript_is_a_json_parse_error() {
+        let server = MockServer::start().await;
+        // `offset` changed type: a schema change the parser should pinpoint
+        Mock::given(method("GET"))
+            .and(path("/v1/transcript"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "{\"lang\":\"th\",\n \"content\":[{\"text\":\"hi\",\"offset\":\"0s\",\"duration\":1}]}",
+            ))
+            .mount(&server)
+            .await;
+
+        let config = Config::builder()
+            .supabase_api_key("sd-key")
+            .supadata_base_url(server.uri())
+            .cache_dir(
+                std::env::temp_dir()
+                    .join(format!("ks-transcripts-{}", uuid::Uuid::new_v4()))
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+            .build();
+
+        let err = get_youtube_transcript(&config, http_client::client(), "https://youtu.be/dQw4w9WgXcQ").await.unwrap_err();
+        match err {
+            AppError::JsonParse { location, message } => {
+                assert_eq!(location, "line 2, column 38");
+                assert!(message.contains("invalid type"), "{}", message);
             }
+            other => panic!("expected JsonParse, got {:?}", other),
         }
+        assert!(!std::path::Path::new(&config.cache_dir).join("dQw4w9WgXcQ.json").exists());
+    }
 
-        if attempt < max_retries {
-            let backoff = Duration::from_secs(2) * attempt as u32;
-            println!(
-                "Retrying transcript API after {:?} due to error: {}",
-                backoff, last_error
-            );
-            sleep(backoff).await;
-        }
+    #[tokio::test]
+    async fn test_lang_request_errors_are_not_masked_by_the_fallback() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/transcript"))
+            .and(query_param("lang", "en"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("invalid api key"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = Config::builder()
+            .supabase_api_key("sd-key")
+            .supadata_base_url(server.uri())
+            .cache_dir(
+                std::env::temp_dir()
+                    .join(format!("ks-transcripts-{}", uuid::Uuid::new_v4()))
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+            .build();
+
+        // Only the `lang=en` request is made: a 401 is not retried, and the
+        // default transcript is not tried
+        let err = get_youtube_transcript_lang(&config, http_client::client(), "https://youtu.be/dQw4w9WgXcQ", "en")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::ApiError { status: 401, .. }), "{:?}", err);
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_transcript_size_guard() {
+        let segment = r#"{"lang":"th","text":"word word word word","offset":0,"duration":1000},"#;
+        let body = format!(r#"{{"content":[{}]}}"#, segment.repeat(20_000));
+
+        let config = Config {
+            max_transcript_bytes: Some(1_000_000),
+            ..Config::default()
+        };
+        let err = check_transcript_size(&config, body.len()).unwrap_err();
+        assert!(matches!(err, AppError::TranscriptTooLarge { max: 1_000_000, .. }));
+
+        assert!(check_transcript_size(&config, 1_000_000).is_ok());
+        assert!(check_transcript_size(&Config::default(), body.len()).is_ok());
+    }
+
+    #[test]
+    fn test_transcript_unavailable_detection() {
+        use reqwest::StatusCode;
+
+        let disabled = include_str!("../mock_data/transcript_unavailable.json");
+        assert!(is_transcript_unavailable(StatusCode::PARTIAL_CONTENT, disabled));
+        assert!(is_transcript_unavailable(StatusCode::NOT_FOUND, disabled));
+        assert!(is_transcript_unavailable(
+            StatusCode::BAD_REQUEST,
+            r#"{"error":"Transcripts are disabled for this video"}"#
+        ));
+
+        // Transient failures and real transcripts are not mistaken for it
+        assert!(!is_transcript_unavailable(StatusCode::SERVICE_UNAVAILABLE, "upstream timeout"));
+        assert!(!is_transcript_unavailable(StatusCode::NOT_FOUND, "Not Found"));
+        assert!(!is_transcript_unavailable(StatusCode::OK, "no transcript of the show tonight"));
     }
 
-    Err(last_error.into())
+    #[test]
+    fn test_serves_lang() {
+        let transcript = Root {
+            lang: Some("th".to_string()),
+            available_langs: vec!["th".to_string(), "en".to_string()],
+            content: Vec::new(),
+        };
+        assert!(serves_lang(&transcript, "TH"));
+        // An English track is available, but this transcript is Thai
+        assert!(!serves_lang(&transcript, "en"));
+        assert!(lists_lang(&transcript, "EN"));
+        assert!(!serves_lang(&transcript, "ja"));
+        assert!(!lists_lang(&transcript, "ja"));
+
+        // Without a stated language, the available list decides
+        let unlabelled = Root {
+            lang: None,
+            ..transcript
+        };
+        assert!(serves_lang(&unlabelled, "en"));
+        assert!(!serves_lang(&Root::default(), "en"));
+    }
 }
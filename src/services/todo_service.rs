@@ -1,9 +1,69 @@
+//! Outbound connectivity self-test against the public jsonplaceholder API,
+//! which needs no API key
+use crate::error::{AppError, Result};
 use crate::models::todo::Todo;
+use crate::services::http_client;
 
-pub async fn get_todo(id: u32) -> Result<Todo, reqwest::Error> {
-    let url = format!("https://jsonplaceholder.typicode.com/todos/{}", id);
+/// Public test API with fixed sample data
+pub(crate) const TODO_BASE_URL: &str = "https://jsonplaceholder.typicode.com";
 
-    let res = reqwest::get(&url).await?.json::<Todo>().await?;
+/// Title jsonplaceholder always returns for todo 1
+const KNOWN_TODO_TITLE: &str = "delectus aut autem";
 
-    Ok(res)
+async fn get_todo_at(base_url: &str, id: u32) -> Result<Todo> {
+    let url = format!("{}/todos/{}", base_url.trim_end_matches('/'), id);
+    let response = http_client::client().get(&url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(AppError::ApiError {
+            url,
+            status: status.as_u16(),
+        });
+    }
+    Ok(response.json::<Todo>().await?)
+}
+
+/// Confirm outbound HTTPS works, independent of any API key, by fetching a
+/// todo with a known title from `base_url`
+pub(crate) async fn selftest_network_at(base_url: &str) -> Result<()> {
+    let todo = get_todo_at(base_url, 1).await?;
+    if todo.title != KNOWN_TODO_TITLE {
+        return Err(AppError::InvalidResponse(format!(
+            "network self-test expected todo 1 titled '{}', got '{}'",
+            KNOWN_TODO_TITLE, todo.title
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_selftest_checks_known_title() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/todos/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"userId": 1, "id": 1, "title": "delectus aut autem", "completed": false}"#,
+            ))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/todos/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"userId": 1, "id": 1, "title": "captive portal", "completed": false}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        selftest_network_at(&server.uri()).await.unwrap();
+        let err = selftest_network_at(&server.uri()).await.unwrap_err();
+        assert_eq!(err.category(), "parse");
+        assert!(err.to_string().contains("captive portal"));
+    }
 }
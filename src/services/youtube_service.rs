@@ -1,89 +1,800 @@
-use crate::config::{self};
+use crate::config::Config;
 
-use crate::models::youtube_snippet::Root;
-use reqwest::Client;
+use crate::error::{AppError, Result};
+use crate::models::youtube_snippet::{ChannelList, Item, Root, SearchResult, Statistics};
+use crate::services::http_client;
+use crate::utils::retry::{retry, Jitter, RetryPolicy};
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use tokio::time::Duration;
+use tracing::debug;
 
-pub async fn get_youtube_search(channel_id: &str) -> Result<Root, Box<dyn std::error::Error>> {
-    let url = "https://www.googleapis.com/youtube/v3/search";
+/// Data API paths, appended to `youtube_api_base`
+pub(crate) const SEARCH_PATH: &str = "/search";
+const CHANNELS_PATH: &str = "/channels";
+const VIDEOS_PATH: &str = "/videos";
+const PLAYLIST_ITEMS_PATH: &str = "/playlistItems";
 
-    let key = config::Config::from_env()?.youtube_api_key;
+/// Results per `search_latest` call when the caller passes 0
+const DEFAULT_SEARCH_RESULTS: usize = 5;
+
+/// Page size for paginated searches (the API maximum)
+const SEARCH_PAGE_SIZE: usize = 50;
+
+/// Safety cap on pages fetched by `get_youtube_search_all`
+const MAX_SEARCH_PAGES: usize = 20;
+
+/// Search a channel's latest completed videos. `max_results` is clamped to the
+/// API maximum of 50; 0 means the default of 5.
+pub(crate) async fn search_latest(
+    config: &Config,
+    client: &Client,
+    channel_id: &str,
+    max_results: u8,
+) -> Result<Root> {
+    let max_results = clamp_max_results(max_results);
+    let url = config.youtube_endpoint(SEARCH_PATH);
+    search_with_retry(&api_retry_policy(), config, client, &url, channel_id, max_results).await
+}
+
+// `search_channel` for completed videos, retried while the error is retryable
+async fn search_with_retry(
+    policy: &RetryPolicy,
+    config: &Config,
+    client: &Client,
+    url: &str,
+    channel_id: &str,
+    max_results: usize,
+) -> Result<Root> {
+    retry(policy, AppError::is_retryable, |_| {
+        search_channel(config, client, url, channel_id, max_results, true)
+    })
+    .await
+}
+
+// Retries for transient YouTube API failures (5xx, 429, network): 3 attempts, 2s then 4s
+fn api_retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::from_secs(2),
+        jitter: Jitter::None,
+        ..RetryPolicy::default()
+    }
+}
+
+// Page size for `search_latest`: 0 → default, above the API maximum → maximum
+fn clamp_max_results(max_results: u8) -> usize {
+    match max_results {
+        0 => DEFAULT_SEARCH_RESULTS,
+        n => usize::from(n).min(SEARCH_PAGE_SIZE),
+    }
+}
+
+/// Each video result of a search mapped to a `SearchResult`, dropping results
+/// without a video id
+pub(crate) fn map_search_results(root: &Root) -> Vec<SearchResult> {
+    root.items.iter().filter_map(SearchResult::from_item).collect()
+}
+
+// Search a channel's latest videos; `completed_only` keeps finished live streams only
+async fn search_channel(
+    config: &Config,
+    client: &Client,
+    url: &str,
+    channel_id: &str,
+    max_results: usize,
+    completed_only: bool,
+) -> Result<Root> {
+    let key = config.youtube_api_key.clone();
     if key.trim().is_empty() {
-        return Err("YOUTUBE_API_KEY is empty; set the secret/env before running".into());
+        return Err(AppError::config("YOUTUBE_API_KEY is empty; set the secret/env before running"));
     }
     if channel_id.trim().is_empty() {
-        return Err("channel_id is empty; set KSFORWORD_CHANNEL_ID before running".into());
+        return Err(AppError::config("channel_id is empty; set KSFORWORD_CHANNEL_ID before running"));
     }
-    let query_params = [
+    let max_results = max_results.to_string();
+    let mut query_params = vec![
         ("part", "snippet"),
         ("channelId", channel_id),
-        ("maxResults", "5"),
+        ("maxResults", max_results.as_str()),
         ("order", "date"),
         ("type", "video"),
         ("key", &key),
-        ("eventType", "completed"),
     ];
+    if completed_only {
+        query_params.push(("eventType", "completed"));
+    }
 
-    let client = Client::new();
-    let res = client
-        .get(url)
-        .query(&query_params)
-        .send()
-        .await?
-        .error_for_status()? // ถ้า status code != 2xx จะ return error
-        .json::<Root>()
-        .await?;
+    let request = http_client::with_request_id(client.get(url)).query(&query_params);
+    send_and_parse(config, client, request).await
+}
 
-    Ok(res)
+/// Resolve a channel `@handle` (the `@` is optional) to its channel id
+pub async fn resolve_channel_id(config: &Config, handle: &str) -> Result<String> {
+    let url = config.youtube_endpoint(CHANNELS_PATH);
+    resolve_channel_id_at(config, http_client::client(), &url, handle).await
 }
 
-pub async fn get_detail_byLink(url: &str) -> Result<Root, Box<dyn std::error::Error>> {
-    let video_id = extract_video_id(url).await?;
-    let key = config::Config::from_env()?.youtube_api_key;
+async fn resolve_channel_id_at(
+    config: &Config,
+    client: &Client,
+    url: &str,
+    handle: &str,
+) -> Result<String> {
+    let handle = handle.trim().trim_start_matches('@');
+    if handle.is_empty() {
+        return Err(AppError::youtube("channel handle is empty"));
+    }
+    let key = config.youtube_api_key.clone();
     if key.trim().is_empty() {
-        return Err("YOUTUBE_API_KEY is empty; set the secret/env before running".into());
+        return Err(AppError::config("YOUTUBE_API_KEY is empty; set the secret/env before running"));
     }
-    if video_id.trim().is_empty() {
-        return Err("video_id is empty; cannot extract from the provided link".into());
+
+    let query_params = [("part", "id"), ("forHandle", handle), ("key", &key)];
+    let request = http_client::with_request_id(client.get(url)).query(&query_params);
+    let channels: ChannelList = send_and_parse(config, client, request).await?;
+    channels
+        .items
+        .into_iter()
+        .next()
+        .map(|channel| channel.id)
+        .ok_or_else(|| AppError::youtube(format!("no channel found for handle @{}", handle)))
+}
+
+/// Latest videos of a channel (live or not), newest first, up to one search page
+pub async fn get_recent_videos(config: &Config, channel_id: &str, max_results: usize) -> Result<Vec<Item>> {
+    let client = http_client::client();
+    let max_results = max_results.clamp(1, SEARCH_PAGE_SIZE);
+    let url = config.youtube_endpoint(SEARCH_PATH);
+    let root = search_channel(config, client, &url, channel_id, max_results, false).await?;
+    Ok(root.items)
+}
+
+/// Result of probing one configured channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelStatus {
+    /// The search returned at least one video
+    Reachable,
+    /// The search succeeded but the channel has no videos
+    Empty,
+    /// The search failed; `category` comes from `AppError::category`
+    Error { category: &'static str, message: String },
+}
+
+/// Probe every configured channel with a one-result search
+pub async fn check_channels(config: &Config) -> Vec<(String, ChannelStatus)> {
+    check_channels_at(config, &config.youtube_endpoint(SEARCH_PATH)).await
+}
+
+pub(crate) async fn check_channels_at(config: &Config, url: &str) -> Vec<(String, ChannelStatus)> {
+    let client = http_client::client();
+    let mut results = Vec::new();
+    for channel_id in config.channel_ids() {
+        let status = match search_channel(config, client, url, &channel_id, 1, false).await {
+            Ok(root) if root.items.is_empty() => ChannelStatus::Empty,
+            Ok(_) => ChannelStatus::Reachable,
+            Err(e) => ChannelStatus::Error {
+                category: e.category(),
+                message: e.to_string(),
+            },
+        };
+        results.push((channel_id, status));
     }
+    results
+}
 
-    println!("Extracted video ID: {}", video_id);
+/// Render channel probe results as a plain-text table
+pub fn format_channel_report(results: &[(String, ChannelStatus)]) -> String {
+    let width = results
+        .iter()
+        .map(|(id, _)| id.len())
+        .chain(std::iter::once("CHANNEL".len()))
+        .max()
+        .unwrap_or_default();
 
-    let api_url = "https://www.googleapis.com/youtube/v3/videos";
-    let query_params = [
-        ("part", "snippet"),
-        ("id", video_id.as_str()),
-        ("key", &key),
+    let mut lines = vec![format!("{:<width$}  STATUS", "CHANNEL")];
+    for (channel_id, status) in results {
+        let status = match status {
+            ChannelStatus::Reachable => "reachable".to_string(),
+            ChannelStatus::Empty => "empty".to_string(),
+            ChannelStatus::Error { category, message } => format!("error ({}): {}", category, message),
+        };
+        lines.push(format!("{:<width$}  {}", channel_id, status));
+    }
+    lines.join("\n")
+}
+
+/// Fetch a channel's videos published after `published_after`, following
+/// `nextPageToken` until `max_total` items are collected, no token remains, or
+/// `MAX_SEARCH_PAGES` pages have been read. `None` collects every page.
+pub async fn get_youtube_search_all(
+    config: &Config,
+    channel_id: &str,
+    published_after: Option<DateTime<Utc>>,
+    max_total: Option<usize>,
+) -> Result<Vec<Item>> {
+    let url = config.youtube_endpoint(SEARCH_PATH);
+    search_all_at(config, &url, channel_id, published_after, max_total).await
+}
+
+async fn search_all_at(
+    config: &Config,
+    url: &str,
+    channel_id: &str,
+    published_after: Option<DateTime<Utc>>,
+    max_total: Option<usize>,
+) -> Result<Vec<Item>> {
+    let key = config.youtube_api_key.as_str();
+    if key.trim().is_empty() {
+        return Err(AppError::config("YOUTUBE_API_KEY is empty; set the secret/env before running"));
+    }
+    if channel_id.trim().is_empty() {
+        return Err(AppError::config("channel_id is empty; set KSFORWORD_CHANNEL_ID before running"));
+    }
+
+    let client = http_client::client();
+    let max_total = max_total.unwrap_or(usize::MAX);
+    let mut items = Vec::new();
+    let mut page_token: Option<String> = None;
+
+    for page in 1..=MAX_SEARCH_PAGES {
+        // Ask only for what is still needed, never more than the API's 50 per page
+        let page_size = (max_total - items.len()).min(SEARCH_PAGE_SIZE);
+        let query_params = search_all_params(channel_id, key, published_after, page_token.as_deref(), page_size);
+        let request = http_client::with_request_id(client.get(url)).query(&query_params);
+        let root: Root = send_and_parse(config, client, request).await?;
+        debug!("Search page {}: {} item(s)", page, root.items.len());
+
+        items.extend(root.items);
+        page_token = root.next_page_token;
+        if page_token.is_none() || items.len() >= max_total {
+            break;
+        }
+    }
+
+    items.truncate(max_total);
+    Ok(items)
+}
+
+// Query parameters for one page of a paginated channel search
+fn search_all_params(
+    channel_id: &str,
+    key: &str,
+    published_after: Option<DateTime<Utc>>,
+    page_token: Option<&str>,
+    page_size: usize,
+) -> Vec<(&'static str, String)> {
+    let mut params = vec![
+        ("part", "snippet".to_string()),
+        ("channelId", channel_id.to_string()),
+        ("maxResults", page_size.to_string()),
+        ("order", "date".to_string()),
+        ("type", "video".to_string()),
+        ("key", key.to_string()),
     ];
+    if let Some(after) = published_after {
+        params.push(("publishedAfter", after.to_rfc3339_opts(SecondsFormat::Secs, true)));
+    }
+    if let Some(token) = page_token {
+        params.push(("pageToken", token.to_string()));
+    }
+    params
+}
+
+/// Every available video of a playlist, in playlist order, mapped to
+/// `SearchResult`s. Private and deleted entries are skipped.
+pub async fn get_playlist_items(config: &Config, playlist_id: &str) -> Result<Vec<SearchResult>> {
+    let videos = get_playlist_videos(config, playlist_id).await?;
+    Ok(videos.iter().filter_map(SearchResult::from_item).collect())
+}
+
+/// Every available video of a playlist as video items (see
+/// `Item::from_playlist_item`), following `nextPageToken` for up to
+/// `MAX_SEARCH_PAGES` pages
+async fn get_playlist_videos(config: &Config, playlist_id: &str) -> Result<Vec<Item>> {
+    let key = config.youtube_api_key.as_str();
+    if key.trim().is_empty() {
+        return Err(AppError::config("YOUTUBE_API_KEY is empty; set the secret/env before running"));
+    }
+    if playlist_id.trim().is_empty() {
+        return Err(AppError::config("playlist_id is empty; pass one or set PLAYLIST_ID"));
+    }
+
+    let client = http_client::client();
+    let url = config.youtube_endpoint(PLAYLIST_ITEMS_PATH);
+    let page_size = SEARCH_PAGE_SIZE.to_string();
+    let mut videos = Vec::new();
+    let mut skipped = 0;
+    let mut page_token: Option<String> = None;
+
+    for page in 1..=MAX_SEARCH_PAGES {
+        let mut query_params = vec![
+            ("part", "snippet,contentDetails"),
+            ("playlistId", playlist_id),
+            ("maxResults", page_size.as_str()),
+            ("key", key),
+        ];
+        if let Some(token) = page_token.as_deref() {
+            query_params.push(("pageToken", token));
+        }
+        let request = http_client::with_request_id(client.get(&url)).query(&query_params);
+        let root: Root = send_and_parse(config, client, request).await?;
+        debug!("Playlist page {}: {} item(s)", page, root.items.len());
+
+        for item in root.items {
+            match Item::from_playlist_item(item) {
+                Some(video) => videos.push(video),
+                None => skipped += 1,
+            }
+        }
+        page_token = root.next_page_token;
+        if page_token.is_none() {
+            break;
+        }
+    }
+
+    if skipped > 0 {
+        debug!("Skipped {} private or deleted item(s) in playlist {}", skipped, playlist_id);
+    }
+    Ok(videos)
+}
+
+#[allow(non_snake_case)]
+pub async fn get_detail_byLink(config: &Config, url: &str) -> Result<Root> {
+    let video_id = extract_video_id(url).await?;
+    if video_id.trim().is_empty() {
+        return Err(AppError::youtube("video_id is empty; cannot extract from the provided link"));
+    }
+
+    debug!("Extracted video ID: {}", video_id);
+    get_videos(config, &video_id, "snippet,statistics").await
+}
+
+// Fetch the given `part`s of one video from the `/videos` endpoint
+async fn get_videos(config: &Config, video_id: &str, part: &str) -> Result<Root> {
+    let key = config.youtube_api_key.as_str();
+    if key.trim().is_empty() {
+        return Err(AppError::config("YOUTUBE_API_KEY is empty; set the secret/env before running"));
+    }
+
+    let query_params = [("part", part), ("id", video_id), ("key", key)];
+    let client = http_client::client();
+    let request = http_client::with_request_id(client.get(config.youtube_endpoint(VIDEOS_PATH))).query(&query_params);
+    send_and_parse(config, client, request).await
+}
+
+// Send a YouTube Data API request and parse the JSON body, tracing it when enabled
+async fn send_and_parse<T: DeserializeOwned>(
+    config: &Config,
+    client: &Client,
+    request: RequestBuilder,
+) -> Result<T> {
+    let request = request.build()?;
+    let url = request.url().to_string();
+    http_client::trace_request(config, "GET", &url, None);
+    // Label by endpoint, e.g. "youtube search" or "youtube videos"
+    let endpoint = request
+        .url()
+        .path_segments()
+        .and_then(|mut s| s.next_back())
+        .unwrap_or_default()
+        .to_string();
+    let call = format!("youtube {}", endpoint);
+
+    let resp = http_client::timed_send(&call, client.execute(request)).await?;
+    let status = resp.status();
+    let headers = resp.headers().clone();
+    let body = resp.text().await?;
+    http_client::trace_response(config, &url, status, &headers, &body);
+
+    // ถ้า status code != 2xx จะ return error
+    if !status.is_success() {
+        return Err(AppError::ApiError {
+            // The query string carries the API key
+            url: url.split('?').next().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+        });
+    }
 
-    let client = Client::new();
-    let res = client
-        .get(api_url)
-        .query(&query_params)
-        .send()
-        .await?
-        .error_for_status()? // ถ้า status code != 2xx จะ return error
-        .json::<Root>()
-        .await?;
+    serde_json::from_str::<T>(&body).map_err(|e| AppError::JsonParse {
+        location: format!("YouTube {} response", endpoint),
+        message: e.to_string(),
+    })
+}
+
+/// A video's length and view/like counts
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VideoDetails {
+    pub duration_secs: Option<u64>,
+    pub statistics: Option<Statistics>,
+}
+
+/// Fetch a video's `contentDetails` and `statistics` in one request, retrying
+/// transient failures
+pub async fn get_video_details(config: &Config, video_id: &str) -> Result<VideoDetails> {
+    let root = retry(&api_retry_policy(), AppError::is_retryable, |_| {
+        get_videos(config, video_id, "contentDetails,statistics")
+    })
+    .await?;
+
+    let item = root.items.into_iter().next();
+    Ok(VideoDetails {
+        duration_secs: item
+            .as_ref()
+            .and_then(|item| item.content_details.as_ref())
+            .and_then(|details| details.duration_secs()),
+        statistics: item.and_then(|item| item.statistics),
+    })
+}
+
+pub async fn extract_video_id(url: &str) -> Result<String> {
+    let id = parse_video_id(url).ok_or_else(|| AppError::youtube("Could not extract video ID from URL"))?;
+    if !is_valid_video_id(&id) {
+        return Err(AppError::youtube("invalid video id"));
+    }
+    Ok(id)
+}
 
-    Ok(res)
+/// YouTube video ids are exactly 11 characters from `[A-Za-z0-9_-]`
+fn is_valid_video_id(id: &str) -> bool {
+    id.len() == 11 && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
 }
 
-pub async fn extract_video_id(url: &str) -> Result<String, Box<dyn std::error::Error>> {
+/// Video id from a watch (`?v=`, also on `m.youtube.com` and with `&list=`),
+/// `youtu.be/`, `/shorts/` or `/embed/` link
+pub fn parse_video_id(url: &str) -> Option<String> {
     let url = url.trim();
-    if url.contains("youtube.com/watch?v=") {
-        let parts: Vec<&str> = url.split("v=").collect();
-        if parts.len() > 1 {
-            let id_part = parts[1];
-            let id = id_part.split('&').next().unwrap_or("");
-            return Ok(id.to_string());
+    let id = if let Some((_, query)) = url.split_once("youtube.com/watch?") {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("v="))?
+    } else {
+        ["youtu.be/", "youtube.com/shorts/", "youtube.com/embed/"]
+            .iter()
+            .find_map(|marker| url.split_once(marker).map(|(_, rest)| rest))?
+    };
+    let id = id.split(['?', '&', '#', '/']).next().unwrap_or_default();
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_extract_video_id_url_forms() {
+        for url in [
+            "https://www.youtube.com/watch?v=JB5FbXxSZ3o",
+            "https://www.youtube.com/watch?v=JB5FbXxSZ3o&list=PL1234567890",
+            "https://www.youtube.com/watch?feature=share&v=JB5FbXxSZ3o",
+            "https://m.youtube.com/watch?v=JB5FbXxSZ3o&t=42s",
+            "https://youtu.be/JB5FbXxSZ3o?si=abc",
+            "https://www.youtube.com/shorts/JB5FbXxSZ3o?feature=share",
+            "https://www.youtube.com/embed/JB5FbXxSZ3o?start=10",
+            "  https://youtube.com/shorts/JB5FbXxSZ3o  ",
+        ] {
+            assert_eq!(extract_video_id(url).await.unwrap(), "JB5FbXxSZ3o", "{}", url);
+        }
+
+        for url in ["", "not a link", "https://www.youtube.com/watch?v=", "https://www.youtube.com/shorts/"] {
+            assert!(extract_video_id(url).await.is_err(), "{}", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_video_id_validates_format() {
+        assert_eq!(extract_video_id("https://youtu.be/a-B_c1D2e3F").await.unwrap(), "a-B_c1D2e3F");
+
+        for url in [
+            "https://www.youtube.com/watch?v=JB5Fb",
+            "https://www.youtube.com/watch?v=JB5Fb$xSZ3o",
+            "https://youtu.be/JB5FbXxSZ3o4",
+        ] {
+            let err = extract_video_id(url).await.unwrap_err();
+            assert_eq!(err.to_string(), "YouTube API error: invalid video id", "{}", url);
         }
-    } else if url.contains("youtu.be/") {
-        let parts: Vec<&str> = url.split("youtu.be/").collect();
-        if parts.len() > 1 {
-            let id_part = parts[1];
-            let id = id_part.split('?').next().unwrap_or("");
-            return Ok(id.to_string());
+    }
+
+    #[test]
+    fn test_search_all_params_include_published_after() {
+        let since = Utc.with_ymd_and_hms(2025, 1, 15, 0, 0, 0).unwrap();
+        let params = search_all_params("UC123", "key", Some(since), Some("NEXT"), SEARCH_PAGE_SIZE);
+
+        assert!(params.contains(&("publishedAfter", "2025-01-15T00:00:00Z".to_string())));
+        assert!(params.contains(&("pageToken", "NEXT".to_string())));
+        assert!(params.contains(&("maxResults", "50".to_string())));
+
+        let params = search_all_params("UC123", "key", None, None, SEARCH_PAGE_SIZE);
+        assert!(params.iter().all(|(name, _)| *name != "publishedAfter" && *name != "pageToken"));
+    }
+
+    fn search_body(items: &str) -> String {
+        format!(
+            r#"{{"kind":"youtube#searchListResponse","etag":"e","pageInfo":{{"totalResults":1,"resultsPerPage":1}},"items":{}}}"#,
+            items
+        )
+    }
+
+    fn search_item(video_id: &str) -> String {
+        format!(
+            r#"{{"kind":"youtube#searchResult","etag":"e","id":{{"kind":"youtube#video","videoId":"{}"}},"snippet":{{"title":"{}"}}}}"#,
+            video_id, video_id
+        )
+    }
+
+    #[tokio::test]
+    async fn test_videos_use_configured_api_base() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/youtube/v3/videos"))
+            .and(query_param("id", "dQw4w9WgXcQ"))
+            .and(query_param("part", "snippet,statistics"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_search.json")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = Config::builder()
+            .youtube_api_key("test-key-123")
+            .youtube_api_base(format!("{}/youtube/v3/", server.uri()))
+            .build();
+        let root = get_videos(&config, "dQw4w9WgXcQ", "snippet,statistics").await.unwrap();
+        let expected: Root = serde_json::from_str(include_str!("../mock_data/example_search.json")).unwrap();
+        assert_eq!(root, expected);
+    }
+
+    #[tokio::test]
+    async fn test_search_all_follows_next_page_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("pageToken", "PAGE2"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(search_body(&format!(
+                "[{},{}]",
+                search_item("page2_a"),
+                search_item("page2_b")
+            ))))
+            .mount(&server)
+            .await;
+        let first_page = format!(
+            r#"{{"kind":"youtube#searchListResponse","etag":"e","nextPageToken":"PAGE2","pageInfo":{{"totalResults":4,"resultsPerPage":2}},"items":[{},{}]}}"#,
+            search_item("page1_a"),
+            search_item("page1_b")
+        );
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(first_page))
+            .mount(&server)
+            .await;
+
+        let config = Config {
+            youtube_api_key: "test-key-123".to_string(),
+            ..Default::default()
+        };
+        let url = format!("{}/search", server.uri());
+        let ids = |items: Vec<Item>| -> Vec<String> {
+            items
+                .iter()
+                .filter_map(|item| item.id.as_video_id())
+                .collect()
+        };
+
+        let items = search_all_at(&config, &url, "UC123", None, None).await.unwrap();
+        assert_eq!(ids(items), ["page1_a", "page1_b", "page2_a", "page2_b"]);
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(received.len(), 2);
+        let second_query = received[1].url.query().unwrap_or_default();
+        assert!(second_query.contains("pageToken=PAGE2"), "{}", second_query);
+
+        // Stops once max_total is reached, asking only for the remaining count
+        let items = search_all_at(&config, &url, "UC123", None, Some(3)).await.unwrap();
+        assert_eq!(ids(items), ["page1_a", "page1_b", "page2_a"]);
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(received.len(), 4);
+        assert!(received[2].url.query().unwrap_or_default().contains("maxResults=3"));
+        assert!(received[3].url.query().unwrap_or_default().contains("maxResults=1"));
+
+        let items = search_all_at(&config, &url, "UC123", None, Some(2)).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(server.received_requests().await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_playlist_items_paginate_and_skip_unavailable() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/playlistItems"))
+            .and(query_param("pageToken", "EAAaBlBUOkNBSQ"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"kind":"youtube#playlistItemListResponse","etag":"e","pageInfo":{"totalResults":4,"resultsPerPage":3},"items":[
+                    {"kind":"youtube#playlistItem","etag":"p4","id":"UExfNA",
+                     "snippet":{"publishedAt":"2025-02-02T00:00:00Z","title":"KS Forward 16 Jan 2025","videoOwnerChannelTitle":"KS Forward",
+                                "resourceId":{"kind":"youtube#video","videoId":"k3Xy9QpLm2A"}}}]}"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/playlistItems"))
+            .and(query_param("playlistId", "PL123"))
+            .and(query_param("part", "snippet,contentDetails"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string(include_str!("../mock_data/example_playlist_items.json")),
+            )
+            .mount(&server)
+            .await;
+
+        let config = Config::builder()
+            .youtube_api_key("test-key-123")
+            .youtube_api_base(server.uri())
+            .build();
+        let results = get_playlist_items(&config, "PL123").await.unwrap();
+
+        let ids: Vec<&str> = results.iter().map(|r| r.video_id.as_str()).collect();
+        assert_eq!(ids, ["JB5FbXxSZ3o", "k3Xy9QpLm2A"]);
+        // Without contentDetails the time it was added to the playlist is used
+        assert_eq!(results[1].publish_time, "2025-02-02T00:00:00Z");
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+        let err = get_playlist_items(&config, " ").await.unwrap_err();
+        assert_eq!(err.category(), "config");
+    }
+
+    #[tokio::test]
+    async fn test_check_channels_reports_each_channel() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("channelId", "UC_reachable"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(search_body(
+                r#"[{"kind":"youtube#searchResult","etag":"e1","id":{"kind":"youtube#video","videoId":"abc"},"snippet":{"title":"Latest"}}]"#,
+            )))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("channelId", "UC_empty"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(search_body("[]")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("channelId", "UC_missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let config = Config {
+            youtube_api_key: "test-key-123".to_string(),
+            ksforword_channel_id: "UC_reachable".to_string(),
+            extra_channel_ids: vec!["UC_empty".to_string(), "UC_missing".to_string()],
+            ..Default::default()
+        };
+        let results = check_channels_at(&config, &format!("{}/search", server.uri())).await;
+
+        assert_eq!(results[0], ("UC_reachable".to_string(), ChannelStatus::Reachable));
+        assert_eq!(results[1], ("UC_empty".to_string(), ChannelStatus::Empty));
+        assert!(matches!(&results[2].1, ChannelStatus::Error { category: "api", .. }));
+
+        let report = format_channel_report(&results);
+        assert!(report.lines().next().unwrap().starts_with("CHANNEL"));
+        assert!(report.contains("UC_reachable  reachable"));
+        assert!(report.contains("UC_empty      empty"));
+        assert!(report.contains("UC_missing    error (api)"));
+        assert!(!report.contains("test-key-123"));
+    }
+
+    #[test]
+    fn test_map_search_results_from_fixture() {
+        let root: Root = serde_json::from_str(include_str!("../mock_data/example_search.json")).unwrap();
+        let mapped = map_search_results(&root);
+
+        // The channel result has no video id and is dropped
+        assert_eq!(mapped.len(), 2);
+        assert_eq!(
+            mapped[0],
+            SearchResult {
+                video_id: "JB5FbXxSZ3o".to_string(),
+                link: "https://www.youtube.com/watch?v=JB5FbXxSZ3o".to_string(),
+                title: "KS Forward 15 Jan 2025".to_string(),
+                publish_time: "2025-01-15T01:00:00Z".to_string(),
+                snippet: root.items[0].snippet.clone(),
+            }
+        );
+        assert_eq!(mapped[1].link, "https://www.youtube.com/watch?v=k3Xy9QpLm2A");
+        assert_eq!(mapped[1].title, "");
+        assert_eq!(mapped[1].publish_time, "");
+    }
+
+    #[test]
+    fn test_max_results_clamped_to_api_limits() {
+        assert_eq!(clamp_max_results(0), 5);
+        assert_eq!(clamp_max_results(5), 5);
+        assert_eq!(clamp_max_results(50), 50);
+        assert_eq!(clamp_max_results(100), 50);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_channel_id_from_handle() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("forHandle", "ksforward"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"kind":"youtube#channelListResponse","etag":"e","items":[{"kind":"youtube#channel","etag":"c","id":"UCabcdefghijklmnopqrstuv"}]}"#,
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("forHandle", "nobody"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"kind":"youtube#channelListResponse","etag":"e","pageInfo":{"totalResults":0,"resultsPerPage":5}}"#,
+            ))
+            .mount(&server)
+            .await;
+
+        let config = Config {
+            youtube_api_key: "test-key-123".to_string(),
+            ..Default::default()
+        };
+        let url = format!("{}/channels", server.uri());
+        let client = http_client::client();
+
+        let id = resolve_channel_id_at(&config, client, &url, "@ksforward").await.unwrap();
+        assert_eq!(id, "UCabcdefghijklmnopqrstuv");
+        // The leading @ is optional
+        let id = resolve_channel_id_at(&config, client, &url, "ksforward").await.unwrap();
+        assert_eq!(id, "UCabcdefghijklmnopqrstuv");
+
+        let err = resolve_channel_id_at(&config, client, &url, "@nobody").await.unwrap_err();
+        assert!(matches!(err, AppError::YouTube(_)));
+        assert!(err.to_string().contains("@nobody"));
+    }
+
+    #[tokio::test]
+    async fn test_forbidden_search_is_api_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(r#"{"error":{"code":403}}"#))
+            .mount(&server)
+            .await;
+
+        let config = Config {
+            youtube_api_key: "test-key-123".to_string(),
+            ..Default::default()
+        };
+        let url = format!("{}/search", server.uri());
+        let err = search_channel(&config, http_client::client(), &url, "UC123", 5, true)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::ApiError { status: 403, .. }));
+        assert!(!err.is_retryable());
+        // The API key travels in the query string and must not leak into the error
+        assert!(!err.to_string().contains("test-key-123"));
+    }
+
+    #[tokio::test]
+    async fn test_search_retries_only_retryable_errors() {
+        let config = Config {
+            youtube_api_key: "test-key-123".to_string(),
+            ..Default::default()
+        };
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..api_retry_policy()
+        };
+
+        for (status, expected_attempts) in [(403, 1), (503, 3)] {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(status))
+                .expect(expected_attempts)
+                .mount(&server)
+                .await;
+            let url = format!("{}/search", server.uri());
+
+            let err = search_with_retry(&policy, &config, http_client::client(), &url, "UC123", 5)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::ApiError { status: s, .. } if s == status));
         }
     }
-    Err("Could not extract video ID from URL".into())
 }
@@ -0,0 +1,149 @@
+//! Offline extractive summarization
+//!
+//! Sentences are scored by the average frequency of their content words across
+//! the whole transcript, with a small bonus for appearing early. The top
+//! sentences are returned in their original order, so no AI call is needed.
+
+use std::collections::HashMap;
+
+/// Words shorter than this are ignored when scoring
+const MIN_WORD_CHARS: usize = 3;
+
+/// Words per pseudo-sentence when the text has almost no punctuation
+const FALLBACK_SENTENCE_WORDS: usize = 25;
+
+/// Common English words that carry no topic signal
+const STOPWORDS: [&str; 24] = [
+    "the", "and", "for", "that", "this", "with", "you", "are", "was", "but", "have", "not", "they",
+    "his", "her", "she", "him", "our", "its", "from", "what", "all", "can", "will",
+];
+
+/// Pick the `count` highest-scoring sentences of `text`, in original order,
+/// formatted as a bullet list
+pub fn summarize(text: &str, count: usize) -> String {
+    let sentences = split_sentences(text);
+    if sentences.is_empty() || count == 0 {
+        return String::new();
+    }
+
+    let mut frequencies: HashMap<String, usize> = HashMap::new();
+    for sentence in &sentences {
+        for word in content_words(sentence) {
+            *frequencies.entry(word).or_default() += 1;
+        }
+    }
+
+    let total = sentences.len() as f64;
+    let mut scored: Vec<(usize, f64)> = sentences
+        .iter()
+        .enumerate()
+        .map(|(index, sentence)| {
+            let words = content_words(sentence);
+            let term_score = if words.is_empty() {
+                0.0
+            } else {
+                words.iter().map(|w| frequencies[w] as f64).sum::<f64>() / words.len() as f64
+            };
+            // Up to 20% bonus for sentences near the start
+            let position_bonus = 1.0 + 0.2 * (1.0 - index as f64 / total);
+            (index, term_score * position_bonus)
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mut top: Vec<usize> = scored.into_iter().take(count).map(|(index, _)| index).collect();
+    top.sort_unstable();
+
+    top.into_iter()
+        .map(|index| format!("• {}", sentences[index]))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Split on sentence punctuation and line breaks; unpunctuated text (common in
+/// auto-generated captions) is cut into fixed-size word windows instead. Thai
+/// has no sentence punctuation and no spaces between words, so Thai text is
+/// split on every space and line break.
+fn split_sentences(text: &str) -> Vec<String> {
+    if text.chars().any(is_thai) {
+        return text.split_whitespace().map(str::to_string).collect();
+    }
+
+    let sentences: Vec<String> = text
+        .split_inclusive(['.', '!', '?', '。', '\n'])
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if sentences.len() > 1 || words.len() <= FALLBACK_SENTENCE_WORDS {
+        return sentences;
+    }
+
+    words
+        .chunks(FALLBACK_SENTENCE_WORDS)
+        .map(|chunk| chunk.join(" "))
+        .collect()
+}
+
+/// Lowercased words that count towards a sentence's score. The length and
+/// stopword filters are English-only, so non-Latin words are always kept.
+fn content_words(sentence: &str) -> Vec<String> {
+    sentence
+        .split(|c: char| !c.is_alphanumeric() && !is_thai(c))
+        .map(str::to_lowercase)
+        .filter(|w| {
+            if w.is_ascii() {
+                w.len() >= MIN_WORD_CHARS && !STOPWORDS.contains(&w.as_str())
+            } else {
+                !w.is_empty()
+            }
+        })
+        .collect()
+}
+
+/// Thai block, including the tone marks `is_alphanumeric` rejects
+fn is_thai(c: char) -> bool {
+    ('\u{0E00}'..='\u{0E7F}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selects_sentences_about_the_main_topic() {
+        let text = "Bank stocks led the market today. \
+                    The weather was sunny in Bangkok. \
+                    Analysts expect bank earnings to lift the market further. \
+                    My cat enjoyed a nap. \
+                    Bank dividends keep the market attractive.";
+
+        let summary = summarize(text, 2);
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.to_lowercase().contains("bank")));
+        // Original order is preserved
+        assert!(lines[0].starts_with("• Bank stocks") || lines[0].starts_with("• Analysts"));
+        assert!(!summary.contains("weather") && !summary.contains("cat"));
+    }
+
+    #[test]
+    fn test_unpunctuated_text_uses_word_windows() {
+        let text = "word ".repeat(60);
+        assert_eq!(split_sentences(&text).len(), 3);
+        assert_eq!(summarize("", 3), "");
+    }
+
+    #[test]
+    fn test_thai_text_splits_on_spaces_and_keeps_words_whole() {
+        let text = "ตลาดหุ้นไทยปรับตัวขึ้น หุ้นธนาคารนำตลาด\nราคาน้ำมันลดลง";
+        assert_eq!(
+            split_sentences(text),
+            ["ตลาดหุ้นไทยปรับตัวขึ้น", "หุ้นธนาคารนำตลาด", "ราคาน้ำมันลดลง"]
+        );
+        // Tone marks do not break a word, and short Thai words are not dropped
+        assert_eq!(content_words("ราคาน้ำมัน the SET ดี"), ["ราคาน้ำมัน", "set", "ดี"]);
+        assert_eq!(summarize(text, 2).lines().count(), 2);
+    }
+}
@@ -0,0 +1,61 @@
+//! Script-based guess of a text's dominant language
+//!
+//! Counts letters per script and names the script with the most letters. This
+//! is enough to tell Thai summaries from English ones without a language model.
+
+/// Share of letters a script needs before it counts as dominant
+const MIN_SHARE: f64 = 0.3;
+
+/// Name of the dominant language in `text`, or `None` when there are no letters
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let mut counts = [0usize; 4];
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        let index = match c {
+            '\u{0E00}'..='\u{0E7F}' => 0,
+            '\u{3040}'..='\u{30FF}' => 1,
+            '\u{4E00}'..='\u{9FFF}' => 2,
+            _ if c.is_ascii_alphabetic() => 3,
+            _ => continue,
+        };
+        counts[index] += 1;
+    }
+
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return None;
+    }
+
+    // Japanese text mixes kana with kanji, so any notable kana wins over Chinese
+    if counts[1] as f64 / total as f64 >= MIN_SHARE / 3.0 {
+        return Some("Japanese");
+    }
+
+    let (index, count) = counts
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, count)| **count)
+        .unwrap_or((3, &0));
+    if (*count as f64 / total as f64) < MIN_SHARE {
+        return None;
+    }
+
+    Some(match index {
+        0 => "Thai",
+        2 => "Chinese",
+        _ => "English",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_thai_and_english() {
+        assert_eq!(detect_language("ตลาดหุ้นไทยปรับตัวขึ้นจากหุ้นกลุ่มธนาคาร"), Some("Thai"));
+        assert_eq!(detect_language("Thai stocks rose, led by the banks."), Some("English"));
+        // Mostly Thai with a few English tickers
+        assert_eq!(detect_language("หุ้น PTT และ SCB ปรับตัวขึ้นแรงในวันนี้"), Some("Thai"));
+        assert_eq!(detect_language("12345 !!!"), None);
+    }
+}
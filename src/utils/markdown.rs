@@ -0,0 +1,139 @@
+//! Convert GitHub-flavored markdown into constructs Discord renders well
+//!
+//! Discord has no table support and only partial heading support in embeds,
+//! so tables become bullet lists and `#` headings become bold lines. Fenced
+//! code blocks are passed through untouched.
+
+/// Rewrite unsupported markdown constructs into Discord-friendly equivalents
+pub fn to_discord_markdown(input: &str) -> String {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut in_code_block = false;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            out.push(line.to_string());
+            i += 1;
+            continue;
+        }
+
+        if in_code_block {
+            out.push(line.to_string());
+            i += 1;
+            continue;
+        }
+
+        if is_table_row(trimmed) && i + 1 < lines.len() && is_separator_row(lines[i + 1].trim()) {
+            let headers = split_cells(trimmed);
+            i += 2;
+            while i < lines.len() && is_table_row(lines[i].trim_start()) {
+                out.push(format_table_row(&headers, &split_cells(lines[i].trim_start())));
+                i += 1;
+            }
+            continue;
+        }
+
+        out.push(convert_heading(line).unwrap_or_else(|| line.to_string()));
+        i += 1;
+    }
+
+    out.join("\n")
+}
+
+/// `## Title` → `**Title**`; returns None for non-heading lines
+fn convert_heading(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = &trimmed[level..];
+    if !rest.starts_with(' ') {
+        return None;
+    }
+
+    let text = rest.trim().trim_end_matches('#').trim();
+    if text.is_empty() {
+        return Some(String::new());
+    }
+    Some(format!("**{}**", text))
+}
+
+fn is_table_row(line: &str) -> bool {
+    line.starts_with('|')
+}
+
+fn is_separator_row(line: &str) -> bool {
+    line.starts_with('|')
+        && line.contains('-')
+        && line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+fn split_cells(line: &str) -> Vec<String> {
+    line.trim()
+        .trim_start_matches('|')
+        .trim_end_matches('|')
+        .split('|')
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
+
+/// Render one table row as a bullet: `- **Header**: value · **Header**: value`
+fn format_table_row(headers: &[String], cells: &[String]) -> String {
+    let parts: Vec<String> = cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| !cell.is_empty())
+        .map(|(idx, cell)| match headers.get(idx).filter(|h| !h.is_empty()) {
+            Some(header) => format!("**{}**: {}", header, cell),
+            None => cell.clone(),
+        })
+        .collect();
+
+    format!("- {}", parts.join(" · "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_becomes_bullets() {
+        let input = "Market recap:\n\
+                     | Index | Close | Change |\n\
+                     |:------|------:|-------:|\n\
+                     | SET | 1,400 | +0.5% |\n\
+                     | S&P 500 | 5,900 | -0.2% |\n\
+                     Done.";
+        let expected = "Market recap:\n\
+                        - **Index**: SET · **Close**: 1,400 · **Change**: +0.5%\n\
+                        - **Index**: S&P 500 · **Close**: 5,900 · **Change**: -0.2%\n\
+                        Done.";
+        assert_eq!(to_discord_markdown(input), expected);
+    }
+
+    #[test]
+    fn test_headings_become_bold() {
+        let input = "# สรุปตลาด\n## Key Points ##\nNot a #heading\n#hashtag";
+        let expected = "**สรุปตลาด**\n**Key Points**\nNot a #heading\n#hashtag";
+        assert_eq!(to_discord_markdown(input), expected);
+    }
+
+    #[test]
+    fn test_code_blocks_untouched() {
+        let input = "```\n# comment\n| a | b |\n|---|---|\n```";
+        assert_eq!(to_discord_markdown(input), input);
+    }
+
+    #[test]
+    fn test_pipe_line_without_separator_is_kept() {
+        let input = "| not a table";
+        assert_eq!(to_discord_markdown(input), input);
+    }
+}
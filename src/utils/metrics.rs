@@ -0,0 +1,88 @@
+//! Per-run counters for the summary line logged at the end of a run
+//!
+//! Counters live in a task-local scope, so code anywhere in the run can record
+//! without threading a struct through every call; outside a scope recording is
+//! a no-op.
+use std::cell::RefCell;
+use std::future::Future;
+use std::time::Duration;
+
+/// What one run did, for observability
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunMetrics {
+    pub channels_scanned: usize,
+    /// Search results whose title matched
+    pub videos_found: usize,
+    pub transcripts_fetched: usize,
+    pub ai_calls: usize,
+    pub discord_posts: usize,
+}
+
+tokio::task_local! {
+    static RUN_METRICS: RefCell<RunMetrics>;
+}
+
+impl RunMetrics {
+    /// One-line summary, e.g. `2 channel(s) scanned, 3 video(s) found, ... in 4.2s`
+    pub fn summary_line(&self, elapsed: Duration) -> String {
+        format!(
+            "{} channel(s) scanned, {} video(s) found, {} transcript(s) fetched, {} AI call(s), {} Discord post(s) in {:.1}s",
+            self.channels_scanned,
+            self.videos_found,
+            self.transcripts_fetched,
+            self.ai_calls,
+            self.discord_posts,
+            elapsed.as_secs_f64()
+        )
+    }
+}
+
+/// Run `fut` with fresh counters and return its output with what it recorded
+pub async fn scope<F: Future>(fut: F) -> (F::Output, RunMetrics) {
+    RUN_METRICS
+        .scope(RefCell::new(RunMetrics::default()), async {
+            let output = fut.await;
+            (output, RUN_METRICS.with(|metrics| *metrics.borrow()))
+        })
+        .await
+}
+
+/// Update the current run's counters, if a scope is active
+pub fn record(update: impl FnOnce(&mut RunMetrics)) {
+    let _ = RUN_METRICS.try_with(|metrics| update(&mut metrics.borrow_mut()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scope_collects_recorded_counts() {
+        // Outside a scope nothing is recorded and nothing panics
+        record(|m| m.ai_calls += 1);
+
+        let (value, metrics) = scope(async {
+            record(|m| m.channels_scanned += 2);
+            record(|m| m.videos_found += 3);
+            tokio::task::yield_now().await;
+            record(|m| m.ai_calls += 1);
+            "done"
+        })
+        .await;
+
+        assert_eq!(value, "done");
+        assert_eq!(
+            metrics,
+            RunMetrics {
+                channels_scanned: 2,
+                videos_found: 3,
+                ai_calls: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            metrics.summary_line(Duration::from_millis(4_250)),
+            "2 channel(s) scanned, 3 video(s) found, 0 transcript(s) fetched, 1 AI call(s), 0 Discord post(s) in 4.2s"
+        );
+    }
+}
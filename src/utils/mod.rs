@@ -0,0 +1,9 @@
+pub mod markdown;
+pub mod redact;
+pub mod simhash;
+pub mod retry;
+pub mod text_cleanup;
+pub mod language;
+pub mod extractive;
+pub mod sponsor_filter;
+pub mod metrics;
@@ -0,0 +1,70 @@
+//! Helpers for logging values that may contain secrets or non-ASCII text
+
+/// Replacement text for redacted secrets
+const REDACTED: &str = "***";
+
+/// Secrets shorter than this are ignored to avoid masking unrelated text
+const MIN_SECRET_LEN: usize = 4;
+
+/// Replace every occurrence of each secret in `text` with `***`
+pub fn redact(text: &str, secrets: &[&str]) -> String {
+    let mut sorted: Vec<&str> = secrets
+        .iter()
+        .copied()
+        .filter(|s| s.len() >= MIN_SECRET_LEN)
+        .collect();
+    // Longest first so a secret containing another is masked whole
+    sorted.sort_by_key(|s| std::cmp::Reverse(s.len()));
+
+    sorted
+        .into_iter()
+        .fold(text.to_string(), |acc, secret| acc.replace(secret, REDACTED))
+}
+
+/// Size of `text` for logs, e.g. "4 chars (12 bytes)". The char count is what a
+/// reader sees (Thai is three bytes per char); bytes matter only for payload limits.
+pub fn size_label(text: &str) -> String {
+    format!("{} chars ({} bytes)", text.chars().count(), text.len())
+}
+
+/// Truncate `text` to at most `max_chars` characters, noting how much was cut
+pub fn truncate_chars(text: &str, max_chars: usize) -> String {
+    let total = text.chars().count();
+    if total <= max_chars {
+        return text.to_string();
+    }
+
+    let kept: String = text.chars().take(max_chars).collect();
+    format!("{}… ({} more chars)", kept, total - max_chars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_label_counts_chars_not_bytes() {
+        let thai = "สวัสดีครับ";
+        assert_eq!(thai.chars().count(), 10);
+        assert_eq!(thai.len(), 30);
+        assert_eq!(size_label(thai), "10 chars (30 bytes)");
+        assert_eq!(size_label("hello"), "5 chars (5 bytes)");
+    }
+
+    #[test]
+    fn test_redact_replaces_all_occurrences() {
+        let text = "key=abcd1234&other=abcd1234";
+        assert_eq!(redact(text, &["abcd1234"]), "key=***&other=***");
+    }
+
+    #[test]
+    fn test_redact_ignores_short_and_empty_secrets() {
+        assert_eq!(redact("a b c", &["", "a"]), "a b c");
+    }
+
+    #[test]
+    fn test_truncate_chars_is_char_aware() {
+        assert_eq!(truncate_chars("สวัสดี", 10), "สวัสดี");
+        assert_eq!(truncate_chars("สวัสดีครับ", 3), "สวั… (7 more chars)");
+    }
+}
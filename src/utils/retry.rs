@@ -0,0 +1,215 @@
+//! Generic async retry with exponential backoff, jitter and an elapsed-time budget
+
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use tokio::time::{sleep, Duration, Instant};
+
+/// How often and how long to keep retrying an operation
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: usize,
+    /// Delay before the second attempt; doubles after each failure
+    pub base_delay: Duration,
+    /// Upper bound for a single delay
+    pub max_delay: Duration,
+    /// Stop retrying once this much time has passed since the first attempt
+    pub max_elapsed: Duration,
+    /// How each delay is randomized around its backoff
+    pub jitter: Jitter,
+}
+
+/// Randomization applied to a retry delay, so parallel runs do not retry in lockstep
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Jitter {
+    /// Wait exactly the backoff
+    None,
+    /// Between half and all of the backoff
+    #[default]
+    Half,
+    /// Within ±25% of the backoff
+    Symmetric,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            max_elapsed: Duration::from_secs(60),
+            jitter: Jitter::Half,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay after the given (1-based) failed attempt
+    fn delay_for(&self, attempt: usize) -> Duration {
+        self.delay_with_seed(attempt, random_u64())
+    }
+
+    /// `delay_for` with the randomness supplied by `seed`
+    fn delay_with_seed(&self, attempt: usize, seed: u64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16) as u32;
+        let backoff = self.base_delay.saturating_mul(2u32.pow(exponent)).min(self.max_delay);
+        match self.jitter {
+            Jitter::None => backoff,
+            Jitter::Half => {
+                let half = backoff / 2;
+                let spread = half.as_millis() as u64;
+                let extra = if spread == 0 { 0 } else { seed % (spread + 1) };
+                half + Duration::from_millis(extra)
+            }
+            Jitter::Symmetric => {
+                // -250..=250 per mille of the backoff
+                let offset = (seed % 501) as f64 - 250.0;
+                backoff.mul_f64(1.0 + offset / 1000.0)
+            }
+        }
+    }
+}
+
+/// Run `op` until it succeeds, `should_retry` rejects its error, attempts run
+/// out, or the next delay would exceed `max_elapsed`. The last error is returned.
+pub async fn retry<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    should_retry: impl Fn(&E) -> bool,
+    mut op: F,
+) -> Result<T, E>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: std::fmt::Display,
+{
+    let started = Instant::now();
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let err = match op(attempt).await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        if attempt >= policy.max_attempts || !should_retry(&err) {
+            return Err(err);
+        }
+
+        let delay = policy.delay_for(attempt);
+        if started.elapsed().saturating_add(delay) > policy.max_elapsed {
+            tracing::warn!(
+                "Retry budget of {:?} exhausted after {} attempt(s): {}",
+                policy.max_elapsed,
+                attempt,
+                err
+            );
+            return Err(err);
+        }
+
+        tracing::warn!(
+            "Attempt {}/{} failed: {}. Retrying in {:?}",
+            attempt,
+            policy.max_attempts,
+            err,
+            delay
+        );
+        sleep(delay).await;
+    }
+}
+
+/// Random value without a `rand` dependency (`RandomState` is seeded per instance)
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_elapsed_budget_stops_before_attempts_run_out() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            max_elapsed: Duration::from_millis(120),
+            jitter: Jitter::Half,
+        };
+        let calls = AtomicUsize::new(0);
+
+        let result: Result<(), String> = retry(&policy, |_| true, |attempt| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                sleep(Duration::from_millis(50)).await;
+                Err(format!("attempt {} failed", attempt))
+            }
+        })
+        .await;
+
+        let calls = calls.load(Ordering::SeqCst);
+        assert!(calls < policy.max_attempts, "made {} attempts", calls);
+        assert_eq!(result.unwrap_err(), format!("attempt {} failed", calls));
+    }
+
+    #[tokio::test]
+    async fn test_stops_on_non_retryable_error() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+        let calls = AtomicUsize::new(0);
+
+        let result: Result<(), &str> = retry(&policy, |e| *e != "fatal", |_| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err("fatal") }
+        })
+        .await;
+
+        assert_eq!(result, Err("fatal"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_jitter_stays_within_backoff() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            ..RetryPolicy::default()
+        };
+        for attempt in 1..6 {
+            let delay = policy.delay_for(attempt);
+            let backoff = Duration::from_millis(100 * 2u64.pow(attempt as u32 - 1)).min(policy.max_delay);
+            assert!(delay >= backoff / 2 && delay <= backoff, "{:?} vs {:?}", delay, backoff);
+        }
+    }
+
+    #[test]
+    fn test_symmetric_jitter_is_bounded() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_secs(2),
+            max_delay: Duration::MAX,
+            jitter: Jitter::Symmetric,
+            ..RetryPolicy::default()
+        };
+        for seed in 0..2_000u64 {
+            for (attempt, nominal) in [(1, 2.0), (2, 4.0), (3, 8.0)] {
+                let delay = policy.delay_with_seed(attempt, seed).as_secs_f64();
+                assert!(
+                    (nominal * 0.75..=nominal * 1.25).contains(&delay),
+                    "attempt {} seed {}: {}s",
+                    attempt,
+                    seed,
+                    delay
+                );
+            }
+        }
+
+        // The extremes are reachable
+        assert_eq!(policy.delay_with_seed(1, 0), Duration::from_millis(1500));
+        assert_eq!(policy.delay_with_seed(1, 500), Duration::from_millis(2500));
+        assert_eq!(policy.delay_with_seed(2, 250), Duration::from_secs(4));
+    }
+}
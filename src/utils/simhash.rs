@@ -0,0 +1,70 @@
+//! SimHash fingerprints for spotting near-duplicate transcripts
+//!
+//! Each word 3-shingle is hashed to 64 bits; every bit of the fingerprint is
+//! set when more shingles have that bit set than unset. Similar texts share
+//! most shingles, so their fingerprints differ in only a few bits.
+
+/// Words per shingle
+const SHINGLE_SIZE: usize = 3;
+
+/// 64-bit SimHash of `text` over lowercase word shingles
+pub fn simhash(text: &str) -> u64 {
+    let words: Vec<String> = text.split_whitespace().map(str::to_lowercase).collect();
+    if words.is_empty() {
+        return 0;
+    }
+
+    let mut weights = [0i64; 64];
+    let shingles: Vec<String> = if words.len() < SHINGLE_SIZE {
+        vec![words.join(" ")]
+    } else {
+        words.windows(SHINGLE_SIZE).map(|w| w.join(" ")).collect()
+    };
+
+    for shingle in &shingles {
+        let hash = fnv1a(shingle.as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash & (1 << bit) != 0 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0u64, |acc, (bit, _)| acc | (1 << bit))
+}
+
+/// Similarity of two fingerprints in `0.0..=1.0` (share of matching bits)
+pub fn similarity(a: u64, b: u64) -> f64 {
+    1.0 - (a ^ b).count_ones() as f64 / 64.0
+}
+
+/// FNV-1a 64-bit hash; stable across runs, unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_similarity_of_near_duplicates() {
+        let base = "today we look at the market outlook for thai stocks and the impact of interest rates \
+                    on bank earnings with a deep dive into energy and tourism sectors for next quarter";
+        let variant = format!("{} thanks for watching", base);
+        let other = "a quick recipe for green curry with chicken basil coconut milk and fresh chillies \
+                     served with jasmine rice and a side of cucumber salad";
+
+        assert_eq!(simhash(base), simhash(base));
+        assert!(similarity(simhash(base), simhash(&variant)) > 0.75);
+        assert!(similarity(simhash(base), simhash(other)) < 0.7);
+    }
+}
@@ -0,0 +1,127 @@
+//! Drop sponsor reads from a transcript before it is summarized
+//!
+//! A sponsor read needs both a sponsor phrase ("sponsored by") and a promo
+//! trigger (a code, a discount, a link) in the same or a neighbouring
+//! sentence. The sentence naming the sponsor is removed, and so are
+//! neighbouring trigger sentences, so a stray "use code" or "link in the
+//! description" in ordinary content is kept.
+
+/// Phrases used when `SPONSOR_PHRASES` is unset
+pub const DEFAULT_SPONSOR_PHRASES: [&str; 7] = [
+    "sponsored by",
+    "thanks to our sponsor",
+    "brought to you by",
+    "our sponsor",
+    "ผู้สนับสนุน",
+    "สนับสนุนโดย",
+    "การสนับสนุนจาก",
+];
+
+/// Promo triggers; a sentence with one is only dropped next to a sponsor phrase
+const PROMO_TRIGGERS: [&str; 13] = [
+    "use code", "use my code", "link in the description", "code", "link", "discount", "% off",
+    "promo", "sign up", "โค้ด", "ส่วนลด", "ลิงก์", "สมัคร",
+];
+
+/// Remove sponsor sentences from `text`, returning the cleaned text and how
+/// many sentences were dropped. Falls back to the default phrases when
+/// `phrases` is empty.
+pub fn strip_sponsor_segments(text: &str, phrases: &[String]) -> (String, usize) {
+    let phrases: Vec<String> = if phrases.is_empty() {
+        DEFAULT_SPONSOR_PHRASES.iter().map(|p| p.to_string()).collect()
+    } else {
+        phrases.iter().map(|p| p.to_lowercase()).collect()
+    };
+
+    let sentences: Vec<&str> = text
+        .split_inclusive(['.', '!', '?', '\n'])
+        .filter(|s| !s.trim().is_empty())
+        .collect();
+    let lowered: Vec<String> = sentences.iter().map(|s| s.to_lowercase()).collect();
+
+    let is_sponsor: Vec<bool> = lowered
+        .iter()
+        .map(|s| phrases.iter().any(|p| s.contains(p.as_str())))
+        .collect();
+    let is_trigger: Vec<bool> = lowered
+        .iter()
+        .map(|s| PROMO_TRIGGERS.iter().any(|trigger| s.contains(trigger)))
+        .collect();
+    let around = |i: usize, flags: &[bool]| {
+        (i.saturating_sub(1)..=(i + 1).min(sentences.len() - 1)).any(|j| flags[j])
+    };
+
+    // A sponsor phrase only counts when a trigger backs it up
+    let is_read: Vec<bool> = (0..sentences.len())
+        .map(|i| is_sponsor[i] && around(i, &is_trigger))
+        .collect();
+    let drop: Vec<bool> = (0..sentences.len())
+        .map(|i| is_read[i] || (is_trigger[i] && around(i, &is_read)))
+        .collect();
+
+    let removed = drop.iter().filter(|d| **d).count();
+    if removed == 0 {
+        return (text.to_string(), 0);
+    }
+
+    let kept: String = sentences
+        .iter()
+        .zip(&drop)
+        .filter(|(_, d)| !**d)
+        .map(|(s, _)| *s)
+        .collect();
+    (kept.trim().to_string(), removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removes_sponsor_read_and_keeps_content() {
+        let transcript = "Bank stocks rallied this morning. \
+                          This episode is sponsored by AcmeVPN. \
+                          Use code KS20 for 20% off at the link below. \
+                          Energy shares fell on lower oil prices. \
+                          The central bank meets next week.";
+
+        let (cleaned, removed) = strip_sponsor_segments(transcript, &[]);
+        assert_eq!(removed, 2);
+        assert!(!cleaned.contains("AcmeVPN") && !cleaned.contains("KS20"));
+        assert!(cleaned.starts_with("Bank stocks rallied this morning."));
+        assert!(cleaned.contains("Energy shares fell on lower oil prices."));
+        assert!(cleaned.ends_with("The central bank meets next week."));
+    }
+
+    #[test]
+    fn test_custom_phrases_and_untouched_text() {
+        let text = "Markets were calm. Paid for by Foo, sign up today. Bonds rose.";
+        let (cleaned, removed) = strip_sponsor_segments(text, &["Paid for by".to_string()]);
+        assert_eq!(removed, 1);
+        assert_eq!(cleaned, "Markets were calm. Bonds rose.");
+
+        assert_eq!(strip_sponsor_segments(text, &[]), (text.to_string(), 0));
+    }
+
+    #[test]
+    fn test_trigger_without_sponsor_is_kept() {
+        let text = "Use code SAVE10 in the broker app to see the fee table. \
+                    Energy shares fell. \
+                    The festival is sponsored by the city. \
+                    Bonds rose. \
+                    The full chart is at the link in the description.";
+        assert_eq!(strip_sponsor_segments(text, &[]), (text.to_string(), 0));
+    }
+
+    #[test]
+    fn test_removes_thai_sponsor_read() {
+        let transcript = "หุ้นกลุ่มธนาคารปรับตัวขึ้นเมื่อเช้านี้\n\
+                          รายการนี้ได้รับการสนับสนุนจาก AcmeVPN\n\
+                          ใช้โค้ด KS20 รับส่วนลด 20%\n\
+                          ราคาน้ำมันลดลงต่อเนื่อง";
+
+        let (cleaned, removed) = strip_sponsor_segments(transcript, &[]);
+        assert_eq!(removed, 2);
+        assert_eq!(cleaned, "หุ้นกลุ่มธนาคารปรับตัวขึ้นเมื่อเช้านี้\nราคาน้ำมันลดลงต่อเนื่อง");
+    }
+}
@@ -0,0 +1,105 @@
+//! Light whitespace and punctuation cleanup for AI-written summaries
+//!
+//! Collapses runs of spaces, removes spaces before punctuation, trims line
+//! ends and squeezes repeated blank lines. Leading indentation (nested lists),
+//! fenced code blocks and inline `code` spans are left untouched.
+
+/// Punctuation that should not be preceded by a space
+const TIGHT_PUNCTUATION: [char; 6] = [',', '.', ';', ':', '!', '?'];
+
+/// Tidy whitespace and punctuation spacing in `input`
+pub fn tidy(input: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+    let mut blank_run = 0;
+
+    for line in input.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            blank_run = 0;
+            out.push(line.trim_end().to_string());
+            continue;
+        }
+        if in_code_block {
+            out.push(line.to_string());
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            blank_run += 1;
+            if blank_run == 1 {
+                out.push(String::new());
+            }
+            continue;
+        }
+        blank_run = 0;
+
+        let indent_len = line.len() - line.trim_start().len();
+        let (indent, body) = line.split_at(indent_len);
+        out.push(format!("{}{}", indent, tidy_inline(body.trim_end())));
+    }
+
+    out.join("\n").trim().to_string()
+}
+
+/// Clean one line of prose, skipping inline code spans
+fn tidy_inline(text: &str) -> String {
+    text.split('`')
+        .enumerate()
+        .map(|(i, part)| if i % 2 == 1 { part.to_string() } else { tidy_prose(part) })
+        .collect::<Vec<_>>()
+        .join("`")
+}
+
+/// Collapse whitespace runs and drop spaces before punctuation that ends a clause
+fn tidy_prose(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if !c.is_whitespace() {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+
+        let run_end = (i..chars.len()).find(|&j| !chars[j].is_whitespace()).unwrap_or(chars.len());
+        let next = chars.get(run_end).copied();
+        let after_next = chars.get(run_end + 1).copied();
+        // " ," → "," but keep " .NET" or " ..." style tokens intact
+        let before_punctuation = next.is_some_and(|n| TIGHT_PUNCTUATION.contains(&n))
+            && after_next.is_none_or(|a| a.is_whitespace());
+        if !before_punctuation {
+            out.push(' ');
+        }
+        i = run_end;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapses_spaces_and_fixes_punctuation() {
+        assert_eq!(tidy("Banks  rose ,  energy fell .  "), "Banks rose, energy fell.");
+        assert_eq!(tidy("Really ?  Yes !"), "Really? Yes!");
+        assert_eq!(tidy("Use .NET and ... wait"), "Use .NET and ... wait");
+    }
+
+    #[test]
+    fn test_squeezes_blank_lines_and_keeps_indentation() {
+        let input = "\n\nTitle\n\n\n\n- item\n  - nested  item\n";
+        assert_eq!(tidy(input), "Title\n\n- item\n  - nested item");
+    }
+
+    #[test]
+    fn test_preserves_code() {
+        let input = "Run `a  ,  b` now\n```\nlet x  =  1 ,\n\n\n```";
+        assert_eq!(tidy(input), "Run `a  ,  b` now\n```\nlet x  =  1 ,\n\n\n```");
+    }
+}
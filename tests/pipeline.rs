@@ -0,0 +1,144 @@
+//! End-to-end runs of the binary against stubbed external services
+//!
+//! Every dependency the `run` command talks to (YouTube search, the transcript
+//! API, the AI backend and a Discord webhook) is served by one wiremock server,
+//! reached through the configurable base URLs. The binary runs with a cleared
+//! environment in a temporary directory so a developer's `.env` is not loaded.
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use tokio::process::Command;
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const CHANNEL_ID: &str = "UCxxxxxxxxxxxxxxxxxx";
+const WEBHOOK_PATH: &str = "/api/webhooks/1/token";
+const SUMMARY: &str = "สรุปตลาดเช้านี้: หุ้นธนาคารปรับตัวขึ้น";
+
+/// Stub every external endpoint a successful run uses
+async fn stub_services(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/youtube/v3/search"))
+        .and(query_param("channelId", CHANNEL_ID))
+        .and(query_param("key", "yt-test-key-0123"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(include_str!("../src/mock_data/example_search.json")))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/transcript"))
+        .and(header("x-api-key", "sd-test-key"))
+        .and(query_param("url", "https://www.youtube.com/watch?v=JB5FbXxSZ3o"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(include_str!("../src/mock_data/example_transcript.json")),
+        )
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/ai/chat"))
+        .and(header("X-API-Key", "ai-test-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "answer": SUMMARY,
+            "events": [],
+            "session_id": "test-session",
+            "context_used": false
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(WEBHOOK_PATH))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(server)
+        .await;
+}
+
+/// Run the binary's default command with only the given configuration
+async fn run(server: &MockServer, workdir: &Path) -> Output {
+    let uri = server.uri();
+    Command::new(env!("CARGO_BIN_EXE_schRust"))
+        .arg("run")
+        .current_dir(workdir)
+        .env_clear()
+        .env("RUST_LOG", "warn")
+        .env("API_URL", &uri)
+        .env("TOKEN", "test-token")
+        .env("YOUTUBE_API_KEY", "yt-test-key-0123")
+        .env("YOUTUBE_API_BASE", format!("{}/youtube/v3", uri))
+        .env("SUPABASE_API_KEY", "sd-test-key")
+        .env("SUPADATA_BASE_URL", &uri)
+        .env("KSFORWORD_CHANNEL_ID", CHANNEL_ID)
+        .env("MY_AI_API_URL", format!("{}/ai/chat", uri))
+        .env("MY_AI_API_KEY", "ai-test-key")
+        .env("DISCORD_KS_BOT_TOKEN", format!("{}{}", uri, WEBHOOK_PATH))
+        .env("CACHE_DIR", workdir.join("cache"))
+        .output()
+        .await
+        .expect("failed to run the binary")
+}
+
+fn temp_workdir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ks-pipeline-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+async fn requests_to(server: &MockServer, request_path: &str) -> Vec<Request> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|request| request.url.path() == request_path)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_latest_video_is_summarized_and_posted() {
+    let server = MockServer::start().await;
+    stub_services(&server).await;
+    let workdir = temp_workdir();
+
+    let output = run(&server, &workdir).await;
+    assert!(
+        output.status.success(),
+        "run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The transcript reached the AI backend
+    let ai_requests = requests_to(&server, "/ai/chat").await;
+    assert_eq!(ai_requests.len(), 1);
+    let ai_body: Value = serde_json::from_slice(&ai_requests[0].body).unwrap();
+    assert!(ai_body["messages"].to_string().contains("สวัสดีครับ"));
+
+    // One Discord post, titled after the newest matching video, carrying the summary
+    let posts = requests_to(&server, WEBHOOK_PATH).await;
+    assert_eq!(posts.len(), 1);
+    let post: Value = serde_json::from_slice(&posts[0].body).unwrap();
+    let header = &post["embeds"][0];
+    assert_eq!(header["title"], "KS Forward 15 Jan 2025");
+    assert_eq!(header["url"], "https://www.youtube.com/watch?v=JB5FbXxSZ3o");
+    assert!(header["description"].as_str().unwrap().contains(SUMMARY));
+
+    let _ = std::fs::remove_dir_all(&workdir);
+}
+
+#[tokio::test]
+async fn test_second_run_does_not_repost() {
+    let server = MockServer::start().await;
+    stub_services(&server).await;
+    let workdir = temp_workdir();
+
+    assert!(run(&server, &workdir).await.status.success());
+    let output = run(&server, &workdir).await;
+    assert!(
+        output.status.success(),
+        "second run failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The processed-video list under CACHE_DIR stops the second post
+    assert_eq!(requests_to(&server, WEBHOOK_PATH).await.len(), 1);
+    assert_eq!(requests_to(&server, "/ai/chat").await.len(), 1);
+
+    let _ = std::fs::remove_dir_all(&workdir);
+}